@@ -0,0 +1,75 @@
+//! 插件中间件示例：统计插件自身处理的请求数
+//!
+//! 运行 `cargo run --example request_counter`，多次访问 http://127.0.0.1:3000/counter
+//! 可以看到计数增加；挂载在其他路径的插件不受该中间件影响
+
+use axum::{
+    extract::{Request, State},
+    middleware::{self, Next},
+    response::Response,
+    routing::get,
+    Router,
+};
+use htmx_core::{HtmxApp, HtmxPlugin, PluginLayer};
+use sqlx::SqlitePool;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// 统计自身请求数的插件
+struct CounterPlugin {
+    requests: Arc<AtomicU64>,
+}
+
+impl HtmxPlugin for CounterPlugin {
+    fn name(&self) -> &str {
+        "counter"
+    }
+
+    fn routes(&self) -> Router {
+        Router::new()
+            .route("/", get(show_count))
+            .with_state(self.requests.clone())
+    }
+
+    fn layer(&self) -> Option<PluginLayer> {
+        Some(PluginLayer::new(middleware::from_fn_with_state(
+            self.requests.clone(),
+            count_requests,
+        )))
+    }
+}
+
+/// 响应完成后计数
+async fn count_requests(
+    State(requests): State<Arc<AtomicU64>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let path = req.uri().path().to_string();
+    let response = next.run(req).await;
+    let total = requests.fetch_add(1, Ordering::Relaxed) + 1;
+    println!("{} {} -> 第 {} 个请求", response.status(), path, total);
+    response
+}
+
+async fn show_count(State(requests): State<Arc<AtomicU64>>) -> String {
+    format!("已处理 {} 个请求", requests.load(Ordering::Relaxed))
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let pool = SqlitePool::connect("sqlite::memory:").await?;
+
+    let app = HtmxApp::new()
+        .plugin(CounterPlugin {
+            requests: Arc::new(AtomicU64::new(0)),
+        })
+        .with_db(pool)
+        .build()
+        .await?;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await?;
+    println!("🚀 http://127.0.0.1:3000/counter");
+    axum::serve(listener, app).await?;
+    Ok(())
+}
@@ -1,5 +1,7 @@
+use crate::auth::{auth_middleware, AuthProvider, AuthState, NoAuth};
+use crate::migration::run_plugin_migrations;
 use crate::plugin::{HtmxPlugin, PluginContext};
-use axum::{Extension, Router};
+use axum::{middleware, Extension, Router};
 use sqlx::SqlitePool;
 use std::error::Error;
 use std::sync::Arc;
@@ -7,10 +9,15 @@ use std::sync::Arc;
 /// HTMX 应用构建器
 ///
 /// 用于组装插件和配置应用
+///
+/// 所有插件共享同一个认证提供者，默认为 [`NoAuth`]，可通过 [`HtmxApp::with_auth_provider`] 替换。
+/// 每个插件的路由都会经过认证中间件：认证成功时注入 `Principal`，
+/// `requires_auth` 为 true 的插件在没有主体时返回 401
 pub struct HtmxApp {
     plugins: Vec<Box<dyn HtmxPlugin>>,
     pool: Option<SqlitePool>,
     config: serde_json::Value,
+    auth_provider: Arc<dyn AuthProvider>,
 }
 
 impl HtmxApp {
@@ -20,6 +27,7 @@ impl HtmxApp {
             plugins: Vec::new(),
             pool: None,
             config: serde_json::json!({}),
+            auth_provider: Arc::new(NoAuth),
         }
     }
 
@@ -41,6 +49,12 @@ impl HtmxApp {
         self
     }
 
+    /// 设置认证提供者
+    pub fn with_auth_provider<A: AuthProvider>(mut self, provider: A) -> Self {
+        self.auth_provider = Arc::new(provider);
+        self
+    }
+
     /// 构建应用
     ///
     /// 执行迁移、初始化插件、组装路由
@@ -52,16 +66,11 @@ impl HtmxApp {
             config: Arc::new(self.config),
         };
 
-        // 运行数据库迁移
+        // 运行数据库迁移，已执行的版本记录在 plugin_migrations 中
         for plugin in &self.plugins {
             tracing::info!("Running migrations for plugin: {}", plugin.name());
-            for (idx, migration) in plugin.migrations().iter().enumerate() {
-                tracing::debug!("Running migration {} for {}", idx + 1, plugin.name());
-                sqlx::query(migration)
-                    .execute(&ctx.pool)
-                    .await
-                    .map_err(|e| format!("Migration failed for {}: {}", plugin.name(), e))?;
-            }
+            let applied = run_plugin_migrations(&ctx.pool, plugin.as_ref()).await?;
+            tracing::debug!("Applied {} migrations for {}", applied, plugin.name());
         }
 
         // 初始化插件
@@ -81,7 +90,19 @@ impl HtmxApp {
                 mount_path
             );
 
-            let routes = plugin.routes().layer(Extension(ctx.pool.clone()));
+            let auth_state = AuthState {
+                provider: self.auth_provider.clone(),
+                required: plugin.requires_auth(),
+            };
+
+            let mut routes = plugin.routes();
+            if let Some(layer) = plugin.layer() {
+                routes = layer.apply(routes);
+            }
+
+            let routes = routes
+                .layer(middleware::from_fn_with_state(auth_state, auth_middleware))
+                .layer(Extension(ctx.pool.clone()));
 
             app = app.nest(&mount_path, routes);
         }
@@ -0,0 +1,39 @@
+//! 插件静态资源
+//!
+//! 插件用 `#[derive(RustEmbed)]` 打包静态资源，在自己的路由中挂载 `/static/*path`，
+//! 由 [`embedded_asset`] 按通配参数返回文件。通配参数是相对插件挂载路径的，
+//! 插件不需要知道自己被挂载在哪里
+
+use axum::{
+    body::Body,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use rust_embed::RustEmbed;
+
+/// 嵌入资源的缓存时间，资源随插件版本发布，允许长期缓存
+const ASSET_CACHE_CONTROL: &str = "public, max-age=31536000";
+
+/// 返回嵌入目录中的文件，不存在时返回 404
+///
+/// ```ignore
+/// async fn serve_static(Path(path): Path<String>) -> Response {
+///     htmx_core::embedded_asset::<StaticAssets>(&path)
+/// }
+/// ```
+pub fn embedded_asset<E: RustEmbed>(path: &str) -> Response {
+    match E::get(path) {
+        Some(content) => {
+            let mime = mime_guess::from_path(path).first_or_octet_stream();
+            (
+                [
+                    (header::CONTENT_TYPE, mime.as_ref()),
+                    (header::CACHE_CONTROL, ASSET_CACHE_CONTROL),
+                ],
+                Body::from(content.data),
+            )
+                .into_response()
+        }
+        None => (StatusCode::NOT_FOUND, "404 Not Found").into_response(),
+    }
+}
@@ -0,0 +1,223 @@
+use axum::{
+    async_trait,
+    extract::{Request, State},
+    http::{header, request::Parts, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::sync::Arc;
+
+/// 已认证的主体
+///
+/// 由 [`AuthProvider`] 生成，认证中间件会把它注入请求扩展，处理器可通过 `Extension<Principal>` 读取
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    /// 主体标识（用户 id、服务名等）
+    pub id: String,
+    /// 主体拥有的角色
+    pub roles: Vec<String>,
+}
+
+impl Principal {
+    /// 创建不带角色的主体
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            roles: Vec::new(),
+        }
+    }
+
+    /// 添加角色
+    pub fn with_role(mut self, role: impl Into<String>) -> Self {
+        self.roles.push(role.into());
+        self
+    }
+
+    /// 是否拥有指定角色
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+}
+
+/// 认证提供者 trait
+///
+/// 实现此 trait 以接入不同的认证方式（Bearer、JWT、Session 等），
+/// 无法识别请求时返回 `None`
+#[async_trait]
+pub trait AuthProvider: Send + Sync + 'static {
+    /// 根据请求头等信息认证请求
+    async fn authenticate(&self, parts: &Parts) -> Option<Principal>;
+
+    /// 插件要求认证但认证失败时的 401 响应
+    ///
+    /// 默认返回通用的 JSON 错误，提供者可覆盖以说明具体原因（如令牌过期）
+    fn unauthorized(&self, _parts: &Parts) -> Response {
+        unauthorized_response("unauthorized", "需要认证")
+    }
+}
+
+/// 构建 401 JSON 响应
+pub fn unauthorized_response(error: &str, message: &str) -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(serde_json::json!({
+            "error": error,
+            "message": message,
+        })),
+    )
+        .into_response()
+}
+
+/// 默认的认证提供者，不认证任何请求
+///
+/// 配合 `requires_auth` 为 true 的插件时，该插件的所有请求都会返回 401
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoAuth;
+
+#[async_trait]
+impl AuthProvider for NoAuth {
+    async fn authenticate(&self, _parts: &Parts) -> Option<Principal> {
+        None
+    }
+}
+
+/// 静态令牌认证示例
+///
+/// 请求头 `Authorization: Bearer <token>` 与配置的令牌一致时认证为指定主体，
+/// 适合内部服务或开发环境
+#[derive(Debug, Clone)]
+pub struct StaticTokenAuth {
+    token: String,
+    principal: Principal,
+}
+
+impl StaticTokenAuth {
+    /// 创建静态令牌认证
+    pub fn new(token: impl Into<String>, principal: Principal) -> Self {
+        Self {
+            token: token.into(),
+            principal,
+        }
+    }
+}
+
+#[async_trait]
+impl AuthProvider for StaticTokenAuth {
+    async fn authenticate(&self, parts: &Parts) -> Option<Principal> {
+        let token = bearer_token(parts)?;
+
+        (token == self.token).then(|| self.principal.clone())
+    }
+}
+
+/// 读取 `Authorization: Bearer <token>` 中的令牌
+pub(crate) fn bearer_token(parts: &Parts) -> Option<&str> {
+    parts
+        .headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// 认证中间件状态
+#[derive(Clone)]
+pub(crate) struct AuthState {
+    pub provider: Arc<dyn AuthProvider>,
+    /// 所属插件是否要求认证
+    pub required: bool,
+}
+
+/// 认证中间件
+///
+/// 运行认证提供者，成功时注入 [`Principal`]；插件要求认证但没有主体时返回 401
+pub(crate) async fn auth_middleware(
+    State(state): State<AuthState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let (mut parts, body) = req.into_parts();
+    let principal = state.provider.authenticate(&parts).await;
+
+    match principal {
+        Some(principal) => {
+            parts.extensions.insert(principal);
+        }
+        None if state.required => {
+            return state.provider.unauthorized(&parts);
+        }
+        None => {}
+    }
+
+    next.run(Request::from_parts(parts, body)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Extension, Router};
+    use tower::ServiceExt;
+
+    fn router(provider: impl AuthProvider, required: bool) -> Router {
+        let state = AuthState {
+            provider: Arc::new(provider),
+            required,
+        };
+        Router::new()
+            .route(
+                "/",
+                get(|principal: Option<Extension<Principal>>| async move {
+                    principal.map_or_else(|| "anonymous".to_string(), |Extension(p)| p.id)
+                }),
+            )
+            .layer(middleware::from_fn_with_state(state, auth_middleware))
+    }
+
+    async fn get_with(router: Router, authorization: Option<&str>) -> (StatusCode, String) {
+        let mut req = Request::builder().uri("/");
+        if let Some(authorization) = authorization {
+            req = req.header(header::AUTHORIZATION, authorization);
+        }
+        let response = router
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    fn static_auth() -> StaticTokenAuth {
+        StaticTokenAuth::new("s3cret", Principal::new("ops").with_role("admin"))
+    }
+
+    #[tokio::test]
+    async fn injects_principal_for_valid_token() {
+        let (status, body) = get_with(router(static_auth(), true), Some("Bearer s3cret")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "ops");
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_or_missing_token_when_required() {
+        for authorization in [Some("Bearer wrong"), Some("Basic s3cret"), None] {
+            let (status, body) = get_with(router(static_auth(), true), authorization).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{:?}", authorization);
+            assert!(body.contains("\"error\":\"unauthorized\""));
+        }
+    }
+
+    #[tokio::test]
+    async fn optional_auth_passes_anonymous_requests() {
+        let (status, body) = get_with(router(static_auth(), false), Some("Bearer wrong")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "anonymous");
+
+        let (status, _) = get_with(router(NoAuth, true), Some("Bearer s3cret")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,193 @@
+use crate::auth::{unauthorized_response, Principal};
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request},
+    http::{request::Parts, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+
+/// 角色定义
+///
+/// 由使用方按自己的角色模型声明，例如：
+///
+/// ```ignore
+/// struct Admin;
+/// impl Role for Admin {
+///     const NAME: &'static str = "admin";
+/// }
+/// ```
+pub trait Role: Send + Sync + 'static {
+    /// 与 `Principal::roles` 比较的角色名
+    const NAME: &'static str;
+}
+
+/// 角色守卫提取器
+///
+/// 读取认证中间件注入的 [`Principal`]：没有主体时返回 401，缺少角色 `R` 时返回 403
+pub struct RequireRole<R: Role> {
+    pub principal: Principal,
+    _role: PhantomData<R>,
+}
+
+#[async_trait]
+impl<R, S> FromRequestParts<S> for RequireRole<R>
+where
+    R: Role,
+    S: Send + Sync,
+{
+    type Rejection = RoleRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let principal = check_role(parts.extensions.get::<Principal>(), R::NAME)?;
+        Ok(Self {
+            principal,
+            _role: PhantomData,
+        })
+    }
+}
+
+/// 角色守卫中间件
+///
+/// 配合 `route_layer` 使用，保护单个路由或一组路由：
+///
+/// ```ignore
+/// Router::new()
+///     .route("/api/todos/:id", delete(delete_todo))
+///     .route_layer(middleware::from_fn(require_role("admin")))
+/// ```
+pub fn require_role(
+    role: &'static str,
+) -> impl Fn(Request, Next) -> Pin<Box<dyn Future<Output = Response> + Send>> + Clone {
+    move |req: Request, next: Next| {
+        Box::pin(async move {
+            match check_role(req.extensions().get::<Principal>(), role) {
+                Ok(_) => next.run(req).await,
+                Err(rejection) => rejection.into_response(),
+            }
+        })
+    }
+}
+
+/// 角色守卫的拒绝原因
+#[derive(Debug, Clone, Copy)]
+pub enum RoleRejection {
+    /// 没有已认证的主体
+    Unauthenticated,
+    /// 主体缺少指定角色
+    Forbidden(&'static str),
+}
+
+impl IntoResponse for RoleRejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Unauthenticated => unauthorized_response("unauthorized", "需要认证"),
+            Self::Forbidden(role) => (
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({
+                    "error": "forbidden",
+                    "message": format!("需要 {} 角色", role),
+                })),
+            )
+                .into_response(),
+        }
+    }
+}
+
+/// 检查主体是否拥有指定角色
+fn check_role(
+    principal: Option<&Principal>,
+    role: &'static str,
+) -> Result<Principal, RoleRejection> {
+    let principal = principal.ok_or(RoleRejection::Unauthenticated)?;
+
+    if !principal.has_role(role) {
+        tracing::debug!("Principal '{}' lacks role '{}'", principal.id, role);
+        return Err(RoleRejection::Forbidden(role));
+    }
+
+    Ok(principal.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    struct Admin;
+
+    impl Role for Admin {
+        const NAME: &'static str = "admin";
+    }
+
+    /// 中间件和提取器两种用法各挂一个路由，按给定主体（可为空）请求
+    async fn statuses(principal: Option<Principal>) -> (StatusCode, StatusCode) {
+        let router = Router::new()
+            .route("/guarded", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn(require_role("admin")))
+            .route(
+                "/extracted",
+                get(
+                    |RequireRole { principal, .. }: RequireRole<Admin>| async move { principal.id },
+                ),
+            );
+
+        let mut results = Vec::new();
+        for uri in ["/guarded", "/extracted"] {
+            let mut req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            if let Some(principal) = principal.clone() {
+                req.extensions_mut().insert(principal);
+            }
+            results.push(router.clone().oneshot(req).await.unwrap().status());
+        }
+        (results[0], results[1])
+    }
+
+    #[tokio::test]
+    async fn allows_principal_with_role() {
+        let admin = Principal::new("u1").with_role("viewer").with_role("admin");
+        assert_eq!(
+            statuses(Some(admin)).await,
+            (StatusCode::OK, StatusCode::OK)
+        );
+    }
+
+    #[tokio::test]
+    async fn forbids_principal_without_role() {
+        let viewer = Principal::new("u2").with_role("viewer");
+        assert_eq!(
+            statuses(Some(viewer)).await,
+            (StatusCode::FORBIDDEN, StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            statuses(None).await,
+            (StatusCode::UNAUTHORIZED, StatusCode::UNAUTHORIZED)
+        );
+    }
+
+    #[test]
+    fn check_role_denies_missing_role_and_principal() {
+        let viewer = Principal::new("u1").with_role("viewer");
+        assert!(matches!(
+            check_role(Some(&viewer), "admin"),
+            Err(RoleRejection::Forbidden("admin"))
+        ));
+        assert!(matches!(
+            check_role(None, "admin"),
+            Err(RoleRejection::Unauthenticated)
+        ));
+        assert_eq!(
+            RoleRejection::Forbidden("admin").into_response().status(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            RoleRejection::Unauthenticated.into_response().status(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+}
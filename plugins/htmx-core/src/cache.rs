@@ -0,0 +1,46 @@
+//! 响应缓存策略
+//!
+//! 主应用（`routes::htmx` 重新导出）和插件处理器都用它声明 `Cache-Control`，
+//! 不必手写头部字符串
+
+use axum::{
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+};
+
+/// 按路由声明的响应缓存策略
+///
+/// 由各处理器根据数据特性选择，例如静态片段可公开缓存，数据片段禁止缓存
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// 禁止任何缓存，用于频繁变化的数据片段
+    NoStore,
+    /// 仅允许浏览器缓存指定秒数
+    Private(u32),
+    /// 允许代理共享缓存指定秒数
+    Public(u32),
+}
+
+impl CachePolicy {
+    /// 对应的 `Cache-Control` 头部值
+    pub fn header_value(&self) -> HeaderValue {
+        match self {
+            Self::NoStore => HeaderValue::from_static("no-store"),
+            Self::Private(max_age) => {
+                HeaderValue::from_str(&format!("private, max-age={}", max_age))
+                    .expect("max-age 头部值总是合法的")
+            }
+            Self::Public(max_age) => HeaderValue::from_str(&format!("public, max-age={}", max_age))
+                .expect("max-age 头部值总是合法的"),
+        }
+    }
+
+    /// 为响应设置 `Cache-Control`，覆盖已有值
+    pub fn apply(self, response: impl IntoResponse) -> Response {
+        let mut response = response.into_response();
+        response
+            .headers_mut()
+            .insert(header::CACHE_CONTROL, self.header_value());
+        response
+    }
+}
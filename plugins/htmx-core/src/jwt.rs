@@ -0,0 +1,220 @@
+use crate::auth::{bearer_token, unauthorized_response, AuthProvider, Principal};
+use axum::{async_trait, http::request::Parts, response::Response};
+use jsonwebtoken::{errors::ErrorKind, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+
+/// JWT 认证配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtConfig {
+    /// HS256 签名密钥
+    pub secret: String,
+    /// 期望的签发者（`iss`），为空时不校验
+    pub issuer: Option<String>,
+    /// 校验过期时间时允许的时钟偏差（秒）
+    #[serde(default = "default_leeway_seconds")]
+    pub leeway_seconds: u64,
+}
+
+fn default_leeway_seconds() -> u64 {
+    30
+}
+
+/// JWT 声明
+///
+/// `sub` 作为主体标识，`roles` 作为主体角色
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// 主体标识
+    pub sub: String,
+    /// 过期时间（Unix 秒）
+    pub exp: u64,
+    /// 签发者
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// 角色列表
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+impl From<Claims> for Principal {
+    fn from(claims: Claims) -> Self {
+        Self {
+            id: claims.sub,
+            roles: claims.roles,
+        }
+    }
+}
+
+/// 基于 JWT 的认证提供者
+///
+/// 校验 `Authorization: Bearer <token>` 的签名、过期时间和签发者，
+/// 解码密钥和校验规则在创建时构建一次并复用
+pub struct JwtAuth {
+    decoding_key: DecodingKey,
+    validation: Validation,
+}
+
+impl JwtAuth {
+    /// 根据配置创建 JWT 认证提供者
+    pub fn new(config: &JwtConfig) -> Self {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.leeway = config.leeway_seconds;
+        validation.set_required_spec_claims(&["exp", "sub"]);
+        if let Some(issuer) = &config.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+
+        Self {
+            decoding_key: DecodingKey::from_secret(config.secret.as_bytes()),
+            validation,
+        }
+    }
+
+    /// 解码并校验令牌
+    fn decode(&self, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+        jsonwebtoken::decode::<Claims>(token, &self.decoding_key, &self.validation)
+            .map(|data| data.claims)
+    }
+}
+
+#[async_trait]
+impl AuthProvider for JwtAuth {
+    async fn authenticate(&self, parts: &Parts) -> Option<Principal> {
+        let token = bearer_token(parts)?;
+
+        match self.decode(token) {
+            Ok(claims) => Some(claims.into()),
+            Err(e) => {
+                tracing::debug!("JWT validation failed: {}", e);
+                None
+            }
+        }
+    }
+
+    fn unauthorized(&self, parts: &Parts) -> Response {
+        let Some(token) = bearer_token(parts) else {
+            return unauthorized_response("missing_token", "缺少 Bearer 令牌");
+        };
+
+        match self.decode(token).map_err(|e| e.into_kind()) {
+            Err(ErrorKind::ExpiredSignature) => {
+                unauthorized_response("token_expired", "令牌已过期")
+            }
+            Err(ErrorKind::InvalidIssuer) => {
+                unauthorized_response("invalid_issuer", "令牌签发者无效")
+            }
+            Err(ErrorKind::InvalidSignature) => {
+                unauthorized_response("invalid_signature", "令牌签名无效")
+            }
+            _ => unauthorized_response("invalid_token", "令牌无效"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{header, Request, StatusCode};
+    use jsonwebtoken::{EncodingKey, Header};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    const SECRET: &str = "test-secret";
+
+    fn config() -> JwtConfig {
+        JwtConfig {
+            secret: SECRET.to_string(),
+            issuer: Some("htmx-rs".to_string()),
+            leeway_seconds: 0,
+        }
+    }
+
+    /// 以当前时间为基准签发令牌，`expires_in` 为负时已过期
+    fn token(secret: &str, expires_in: i64, issuer: &str) -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let claims = Claims {
+            sub: "42".to_string(),
+            exp: now.saturating_add_signed(expires_in),
+            iss: Some(issuer.to_string()),
+            roles: vec!["admin".to_string()],
+        };
+        jsonwebtoken::encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    fn parts(token: &str) -> Parts {
+        Request::builder()
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0
+    }
+
+    /// 认证失败时 401 响应中的错误码
+    async fn rejection(auth: &JwtAuth, token: &str) -> String {
+        let parts = parts(token);
+        assert_eq!(auth.authenticate(&parts).await, None);
+
+        let response = auth.unauthorized(&parts);
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        body["error"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn accepts_valid_token_as_principal() {
+        let auth = JwtAuth::new(&config());
+        let principal = auth
+            .authenticate(&parts(&token(SECRET, 3600, "htmx-rs")))
+            .await
+            .unwrap();
+        assert_eq!(principal, Principal::new("42").with_role("admin"));
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_issuer_and_missing_token() {
+        let auth = JwtAuth::new(&config());
+        let foreign = token(SECRET, 3600, "someone-else");
+        assert_eq!(rejection(&auth, &foreign).await, "invalid_issuer");
+
+        let parts = Request::builder().body(()).unwrap().into_parts().0;
+        assert_eq!(auth.authenticate(&parts).await, None);
+        assert_eq!(auth.unauthorized(&parts).status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn rejects_expired_token() {
+        let auth = JwtAuth::new(&config());
+        let expired = token(SECRET, -60, "htmx-rs");
+        assert_eq!(rejection(&auth, &expired).await, "token_expired");
+    }
+
+    #[tokio::test]
+    async fn rejects_tampered_token() {
+        let auth = JwtAuth::new(&config());
+        let valid = token(SECRET, 3600, "htmx-rs");
+        // 换成延长了过期时间的载荷，保留原签名
+        let forged_payload = token("other", 7200, "htmx-rs");
+        let mut segments: Vec<&str> = valid.split('.').collect();
+        segments[1] = forged_payload.split('.').nth(1).unwrap();
+        let tampered = segments.join(".");
+        assert_eq!(rejection(&auth, &tampered).await, "invalid_signature");
+    }
+
+    #[tokio::test]
+    async fn rejects_token_signed_with_wrong_secret() {
+        let auth = JwtAuth::new(&config());
+        let foreign = token("wrong-secret", 3600, "htmx-rs");
+        assert_eq!(rejection(&auth, &foreign).await, "invalid_signature");
+    }
+}
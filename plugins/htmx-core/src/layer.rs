@@ -0,0 +1,37 @@
+use axum::extract::Request;
+use axum::response::IntoResponse;
+use axum::routing::Route;
+use axum::Router;
+use std::convert::Infallible;
+use tower::{Layer, Service};
+
+/// 插件中间件
+///
+/// `tower::Layer` 的服务类型是关联类型，无法直接作为 trait 对象从 [`HtmxPlugin::layer`]
+/// 返回；`PluginLayer` 在创建时捕获具体类型，只保留“套到 Router 上”这一操作
+///
+/// [`HtmxPlugin::layer`]: crate::HtmxPlugin::layer
+pub struct PluginLayer {
+    apply: Box<dyn FnOnce(Router) -> Router + Send>,
+}
+
+impl PluginLayer {
+    /// 包装任意可用于 `Router::layer` 的中间件，如 `middleware::from_fn(...)`、`ServiceBuilder`
+    pub fn new<L>(layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + 'static,
+        L::Service: Service<Request> + Clone + Send + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        Self {
+            apply: Box::new(move |router| router.layer(layer)),
+        }
+    }
+
+    /// 应用到插件路由
+    pub(crate) fn apply(self, router: Router) -> Router {
+        (self.apply)(router)
+    }
+}
@@ -1,5 +1,23 @@
 mod app;
+mod asset;
+mod auth;
+mod authz;
+mod cache;
+mod jwt;
+mod layer;
+mod migration;
+mod number;
 mod plugin;
+mod template;
 
 pub use app::HtmxApp;
+pub use asset::embedded_asset;
+pub use auth::{unauthorized_response, AuthProvider, NoAuth, Principal, StaticTokenAuth};
+pub use authz::{require_role, RequireRole, Role, RoleRejection};
+pub use cache::CachePolicy;
+pub use jwt::{Claims, JwtAuth, JwtConfig};
+pub use layer::PluginLayer;
+pub use migration::MigrationFile;
+pub use number::{format_compact, format_grouped, format_number, NumberLocale, NumberStyle};
 pub use plugin::{HtmxPlugin, PluginContext};
+pub use template::is_namespaced_template;
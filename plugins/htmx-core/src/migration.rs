@@ -0,0 +1,118 @@
+use rust_embed::RustEmbed;
+use sqlx::SqlitePool;
+use std::borrow::Cow;
+use std::error::Error;
+
+use crate::plugin::HtmxPlugin;
+
+/// 插件迁移记录表
+const CREATE_PLUGIN_MIGRATIONS: &str = r#"
+CREATE TABLE IF NOT EXISTS plugin_migrations (
+    plugin TEXT NOT NULL,
+    version TEXT NOT NULL,
+    applied_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (plugin, version)
+)
+"#;
+
+/// 嵌入目录中的迁移文件
+///
+/// 通过 [`MigrationFile::from_embed`] 从 `RustEmbed` 目录加载，文件名即迁移版本
+pub struct MigrationFile {
+    /// 文件名（相对嵌入目录），如 `001_users.sql`
+    pub name: Cow<'static, str>,
+    /// 文件内容，执行前按 UTF-8 校验
+    pub data: Cow<'static, [u8]>,
+}
+
+impl MigrationFile {
+    /// 加载嵌入目录中所有 `.sql` 文件，按文件名字典序排序
+    ///
+    /// 排序只看文件名，因此序号需补零到相同位数（`001_users.sql`、`002_sessions.sql`、…、`010_xxx.sql`），
+    /// 否则 `10_xxx.sql` 会排在 `2_xxx.sql` 之前。已执行的文件不能改名，也不要修改内容，新变更请追加新文件
+    pub fn from_embed<E: RustEmbed>() -> Vec<Self> {
+        let mut names: Vec<_> = E::iter().filter(|name| name.ends_with(".sql")).collect();
+        names.sort();
+
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let file = E::get(&name)?;
+                Some(Self {
+                    name,
+                    data: file.data,
+                })
+            })
+            .collect()
+    }
+}
+
+/// 执行插件中尚未执行的迁移
+///
+/// 先执行 `migrations()` 中的内联迁移（版本为 `inline-0001` 起的序号），再执行
+/// `migration_files()` 中的文件迁移（版本为文件名）。每个迁移与其记录在同一事务中提交，
+/// 已记录在 `plugin_migrations` 中的版本会跳过
+pub(crate) async fn run_plugin_migrations(
+    pool: &SqlitePool,
+    plugin: &dyn HtmxPlugin,
+) -> Result<usize, Box<dyn Error>> {
+    sqlx::query(CREATE_PLUGIN_MIGRATIONS).execute(pool).await?;
+
+    let inline = plugin
+        .migrations()
+        .into_iter()
+        .enumerate()
+        .map(|(idx, sql)| (format!("inline-{:04}", idx + 1), Cow::Borrowed(sql)));
+
+    let files = plugin
+        .migration_files()
+        .into_iter()
+        .map(|file| {
+            let sql = match file.data {
+                Cow::Borrowed(bytes) => std::str::from_utf8(bytes).map(Cow::Borrowed),
+                Cow::Owned(bytes) => String::from_utf8(bytes)
+                    .map(Cow::Owned)
+                    .map_err(|e| e.utf8_error()),
+            }
+            .map_err(|e| {
+                format!(
+                    "Migration file {} for {} is not valid UTF-8: {}",
+                    file.name,
+                    plugin.name(),
+                    e
+                )
+            })?;
+            Ok((file.name.into_owned(), sql))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mut applied = 0;
+    for (version, sql) in inline.chain(files) {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM plugin_migrations WHERE plugin = ? AND version = ?)",
+        )
+        .bind(plugin.name())
+        .bind(&version)
+        .fetch_one(pool)
+        .await?;
+        if exists {
+            continue;
+        }
+
+        tracing::debug!("Running migration {} for {}", version, plugin.name());
+        let mut tx = pool.begin().await?;
+        sqlx::raw_sql(&sql)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Migration {} failed for {}: {}", version, plugin.name(), e))?;
+        sqlx::query("INSERT INTO plugin_migrations (plugin, version) VALUES (?, ?)")
+            .bind(plugin.name())
+            .bind(&version)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        applied += 1;
+    }
+
+    Ok(applied)
+}
@@ -0,0 +1,153 @@
+//! 数字格式化
+//!
+//! 统计类数据在页面上展示时使用，服务端统一格式化，保证各插件（官网统计、管理后台等）
+//! 显示一致。语言与主应用的 `Locale` 对应：中文紧凑格式使用「万」「亿」，英文使用 `k`、`M`、`B`
+
+use serde::{Deserialize, Serialize};
+
+/// 数字格式化使用的语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NumberLocale {
+    #[default]
+    Zh,
+    En,
+}
+
+impl NumberLocale {
+    /// 按主语言标签匹配（`en-US` → `En`），不支持的语言返回 `None`
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split('-').next()?;
+        if primary.eq_ignore_ascii_case("zh") {
+            Some(Self::Zh)
+        } else if primary.eq_ignore_ascii_case("en") {
+            Some(Self::En)
+        } else {
+            None
+        }
+    }
+}
+
+/// 数字展示样式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NumberStyle {
+    /// 原样输出，如 `1234567`
+    #[default]
+    Plain,
+    /// 千位分隔，如 `1,234,567`
+    Grouped,
+    /// 紧凑格式，如 `123.5万`（中文）或 `1.2M`（英文）
+    Compact,
+}
+
+/// 按样式和语言格式化数字
+pub fn format_number(n: u64, style: NumberStyle, locale: NumberLocale) -> String {
+    match style {
+        NumberStyle::Plain => n.to_string(),
+        NumberStyle::Grouped => format_grouped(n),
+        NumberStyle::Compact => format_compact(n, locale),
+    }
+}
+
+/// 千位分隔，如 `1000` → `1,000`
+pub fn format_grouped(n: u64) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+/// 紧凑格式，保留一位小数并去掉末尾的 `.0`
+///
+/// 中文以万（10⁴）、亿（10⁸）为单位：`12000` → `1.2万`；英文以 k、M、B 为单位：`1200` → `1.2k`。
+/// 不足一个单位时原样输出，四舍五入进位到下一单位时改用下一单位（`999950` → `1M`）
+pub fn format_compact(n: u64, locale: NumberLocale) -> String {
+    let units: &[(u64, &str)] = match locale {
+        NumberLocale::Zh => &[(100_000_000, "亿"), (10_000, "万")],
+        NumberLocale::En => &[(1_000_000_000, "B"), (1_000_000, "M"), (1_000, "k")],
+    };
+
+    for (i, &(size, suffix)) in units.iter().enumerate() {
+        if n < size {
+            continue;
+        }
+        // 以十分之一为单位四舍五入，避免浮点误差
+        let tenths = (u128::from(n) * 10 + u128::from(size) / 2) / u128::from(size);
+        if i > 0 {
+            let (larger, larger_suffix) = units[i - 1];
+            if tenths * u128::from(size) >= u128::from(larger) * 10 {
+                return format!("1{}", larger_suffix);
+            }
+        }
+        return if tenths.is_multiple_of(10) {
+            format!("{}{}", tenths / 10, suffix)
+        } else {
+            format!("{}.{}{}", tenths / 10, tenths % 10, suffix)
+        };
+    }
+    n.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grouped_inserts_separators_every_three_digits() {
+        assert_eq!(format_grouped(0), "0");
+        assert_eq!(format_grouped(999), "999");
+        assert_eq!(format_grouped(1_000), "1,000");
+        assert_eq!(format_grouped(1_234_567), "1,234,567");
+        assert_eq!(format_grouped(u64::MAX), "18,446,744,073,709,551,615");
+    }
+
+    #[test]
+    fn compact_uses_locale_units() {
+        assert_eq!(format_compact(9_999, NumberLocale::Zh), "9999");
+        assert_eq!(format_compact(12_000, NumberLocale::Zh), "1.2万");
+        assert_eq!(format_compact(1_234_567, NumberLocale::Zh), "123.5万");
+        assert_eq!(format_compact(300_000_000, NumberLocale::Zh), "3亿");
+
+        assert_eq!(format_compact(999, NumberLocale::En), "999");
+        assert_eq!(format_compact(1_200, NumberLocale::En), "1.2k");
+        assert_eq!(format_compact(1_234_567, NumberLocale::En), "1.2M");
+        assert_eq!(format_compact(2_000_000_000, NumberLocale::En), "2B");
+    }
+
+    #[test]
+    fn compact_rounding_carries_into_next_unit() {
+        assert_eq!(format_compact(999_950, NumberLocale::En), "1M");
+        assert_eq!(format_compact(999_949, NumberLocale::En), "999.9k");
+        assert_eq!(format_compact(99_999_500, NumberLocale::Zh), "1亿");
+        assert_eq!(format_compact(u64::MAX, NumberLocale::En), "18446744073.7B");
+    }
+
+    #[test]
+    fn format_number_dispatches_on_style() {
+        assert_eq!(
+            format_number(1_234, NumberStyle::Plain, NumberLocale::En),
+            "1234"
+        );
+        assert_eq!(
+            format_number(1_234, NumberStyle::Grouped, NumberLocale::En),
+            "1,234"
+        );
+        assert_eq!(
+            format_number(1_234, NumberStyle::Compact, NumberLocale::En),
+            "1.2k"
+        );
+    }
+
+    #[test]
+    fn locale_from_tag_matches_primary_subtag() {
+        assert_eq!(NumberLocale::from_tag("en-US"), Some(NumberLocale::En));
+        assert_eq!(NumberLocale::from_tag("ZH-cn"), Some(NumberLocale::Zh));
+        assert_eq!(NumberLocale::from_tag("fr"), None);
+    }
+}
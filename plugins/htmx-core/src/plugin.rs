@@ -1,3 +1,5 @@
+use crate::layer::PluginLayer;
+use crate::migration::MigrationFile;
 use axum::Router;
 use sqlx::SqlitePool;
 use std::error::Error;
@@ -26,6 +28,14 @@ pub trait HtmxPlugin: Send + Sync + 'static {
     /// 返回包含所有路由的 Router
     fn routes(&self) -> Router;
 
+    /// 插件自己的中间件
+    ///
+    /// 只作用于该插件挂载的路由，位于认证中间件之内：未通过认证的请求不会到达这里，
+    /// 中间件中可以读取 `Principal` 和数据库连接池。可用于请求统计、缓存预热、额外的日志等
+    fn layer(&self) -> Option<PluginLayer> {
+        None
+    }
+
     /// 数据库迁移 SQL
     ///
     /// 返回迁移 SQL 字符串数组，按顺序执行
@@ -33,9 +43,17 @@ pub trait HtmxPlugin: Send + Sync + 'static {
         vec![]
     }
 
+    /// 嵌入目录中的迁移文件
+    ///
+    /// 在内联迁移之后按文件名顺序执行，通常用 [`MigrationFile::from_embed`] 加载
+    /// `#[derive(RustEmbed)]` 的迁移目录，文件名约定见该函数
+    fn migration_files(&self) -> Vec<MigrationFile> {
+        vec![]
+    }
+
     /// 是否需要认证
     ///
-    /// 如果返回 true，所有路由将应用认证中间件
+    /// 如果返回 true，未通过 `HtmxApp` 认证提供者认证的请求将返回 401
     fn requires_auth(&self) -> bool {
         false
     }
@@ -0,0 +1,66 @@
+//! 插件模板命名空间
+//!
+//! askama 在编译期按**当前 crate** 的 `CARGO_MANIFEST_DIR` 解析模板路径：默认根目录是
+//! `templates/`，可在该 crate 的 `askama.toml` 中用 `dirs` 修改。主应用和各插件各自编译，
+//! 互不可见，同名的 `index.html` 本身不会冲突；但一旦某个 crate 把其他目录加入 `dirs`
+//! （例如复用主应用的布局），或多个插件共享同一模板目录，`index.html` 就会按 `dirs`
+//! 的顺序取第一个匹配，出现难以察觉的覆盖。
+//!
+//! 因此约定插件模板放在以插件名命名的子目录下（`templates/<插件名>/index.html`），
+//! 并通过 [`plugin_template!`](crate::plugin_template) 在编译期检查
+
+/// `path` 是否位于 `namespace/` 子目录下
+///
+/// `namespace` 必须非空且不含 `/`，`path` 在前缀之后还需有文件名
+pub const fn is_namespaced_template(path: &str, namespace: &str) -> bool {
+    let path = path.as_bytes();
+    let namespace = namespace.as_bytes();
+    if namespace.is_empty() || path.len() <= namespace.len() + 1 || path[namespace.len()] != b'/' {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < namespace.len() {
+        if namespace[i] == b'/' || path[i] != namespace[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// 定义插件模板，并在编译期检查模板路径位于插件命名空间下
+///
+/// 宏会替结构体派生 `askama::Template`，因此插件需依赖 `askama`，且不要再手写
+/// `#[derive(Template)]`。`#[template(...)]` 必须是第一个属性：
+///
+/// ```ignore
+/// htmx_core::plugin_template! {
+///     namespace = "landing";
+///     #[template(path = "landing/index.html")]
+///     struct IndexTemplate {
+///         title: String,
+///     }
+/// }
+/// ```
+///
+/// 路径不在 `landing/` 下时编译失败
+#[macro_export]
+macro_rules! plugin_template {
+    (
+        namespace = $namespace:literal;
+        #[template(path = $path:literal $(, $key:ident = $value:literal)* $(,)?)]
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident $($rest:tt)*
+    ) => {
+        const _: () = assert!(
+            $crate::is_namespaced_template($path, $namespace),
+            concat!("插件模板 \"", $path, "\" 必须放在 \"", $namespace, "/\" 子目录下")
+        );
+
+        #[derive(::askama::Template)]
+        #[template(path = $path $(, $key = $value)*)]
+        $(#[$attr])*
+        $vis struct $name $($rest)*
+    };
+}
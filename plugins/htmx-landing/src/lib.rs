@@ -2,9 +2,8 @@ mod models;
 mod routes;
 mod static_handler;
 
-use askama::Template;
 use axum::Router;
-use htmx_core::HtmxPlugin;
+use htmx_core::{HtmxPlugin, NumberLocale, NumberStyle};
 use serde::{Deserialize, Serialize};
 
 pub use routes::create_routes;
@@ -15,6 +14,12 @@ pub struct LandingConfig {
     pub title: String,
     pub subtitle: String,
     pub features: Vec<Feature>,
+    /// 统计数字的展示样式，默认原样输出
+    #[serde(default)]
+    pub stats_style: NumberStyle,
+    /// 统计数字紧凑格式使用的语言
+    #[serde(default)]
+    pub number_locale: NumberLocale,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +51,8 @@ impl Default for LandingConfig {
                     description: "Rust 的类型系统确保代码的安全性".to_string(),
                 },
             ],
+            stats_style: NumberStyle::default(),
+            number_locale: NumberLocale::default(),
         }
     }
 }
@@ -76,6 +83,12 @@ impl LandingPlugin {
         self.config.subtitle = subtitle.into();
         self
     }
+
+    pub fn with_number_format(mut self, style: NumberStyle, locale: NumberLocale) -> Self {
+        self.config.stats_style = style;
+        self.config.number_locale = locale;
+        self
+    }
 }
 
 impl Default for LandingPlugin {
@@ -90,7 +103,7 @@ impl HtmxPlugin for LandingPlugin {
     }
 
     fn routes(&self) -> Router {
-        create_routes(self.config.clone())
+        create_routes(self.config.clone(), &self.mount_path())
     }
 
     fn requires_auth(&self) -> bool {
@@ -1,52 +1,237 @@
 use crate::{models::Stats, static_handler::serve_static, LandingConfig};
 use askama::Template;
 use askama_axum::IntoResponse;
-use axum::{routing::get, Router};
+use axum::{
+    body::Body,
+    extract::{FromRef, State},
+    http::{header, StatusCode},
+    response::Response,
+    routing::get,
+    Router,
+};
+use futures::{future, stream, StreamExt};
+use htmx_core::{format_number, CachePolicy};
+use std::convert::Infallible;
+use std::sync::Arc;
 
-#[derive(Template)]
-#[template(path = "index.html")]
-struct IndexTemplate {
-    title: String,
-    subtitle: String,
-    features: Vec<crate::Feature>,
+htmx_core::plugin_template! {
+    namespace = "landing";
+    #[template(path = "landing/index.html")]
+    /// 首页首屏部分（`<head>`、hero、特性列表），先于统计数据发送
+    struct IndexTemplate {
+        mount_path: String,
+        title: String,
+        subtitle: String,
+        features: Vec<crate::Feature>,
+    }
 }
 
-#[derive(Template)]
-#[template(path = "stats.html")]
-struct StatsTemplate {
-    user_count: u64,
-    project_count: u64,
-    satisfaction: u64,
+htmx_core::plugin_template! {
+    namespace = "landing";
+    #[template(path = "landing/stats.html")]
+    /// 统计卡片，同时提供原始数值（`data-value`）和按配置格式化后的展示值
+    struct StatsTemplate {
+        user_count: u64,
+        user_count_display: String,
+        project_count: u64,
+        project_count_display: String,
+        satisfaction: u64,
+    }
 }
 
-/// 首页处理器
-async fn index(
-    axum::extract::State(config): axum::extract::State<LandingConfig>,
-) -> impl IntoResponse {
-    IndexTemplate {
-        title: config.title,
-        subtitle: config.subtitle,
-        features: config.features,
+htmx_core::plugin_template! {
+    namespace = "landing";
+    #[template(path = "landing/stats_section.html")]
+    /// 首页中内联渲染的统计区块
+    struct StatsSectionTemplate {
+        user_count: u64,
+        user_count_display: String,
+        project_count: u64,
+        project_count_display: String,
+        satisfaction: u64,
     }
 }
 
-/// 统计数据处理器
-async fn stats() -> impl IntoResponse {
+impl StatsTemplate {
+    fn new(stats: Stats, config: &LandingConfig) -> Self {
+        let format = |n| format_number(n, config.stats_style, config.number_locale);
+        Self {
+            user_count: stats.user_count,
+            user_count_display: format(stats.user_count),
+            project_count: stats.project_count,
+            project_count_display: format(stats.project_count),
+            satisfaction: stats.satisfaction,
+        }
+    }
+}
+
+impl From<StatsTemplate> for StatsSectionTemplate {
+    fn from(stats: StatsTemplate) -> Self {
+        Self {
+            user_count: stats.user_count,
+            user_count_display: stats.user_count_display,
+            project_count: stats.project_count,
+            project_count_display: stats.project_count_display,
+            satisfaction: stats.satisfaction,
+        }
+    }
+}
+
+htmx_core::plugin_template! {
+    namespace = "landing";
+    #[template(path = "landing/stats_placeholder.html")]
+    /// 统计区块渲染失败时的占位，由浏览器通过 `/stats` 重新加载
+    struct StatsPlaceholderTemplate {
+        mount_path: String,
+    }
+}
+
+htmx_core::plugin_template! {
+    namespace = "landing";
+    #[template(path = "landing/footer.html")]
+    struct FooterTemplate {
+        title: String,
+    }
+}
+
+/// 加载统计数据
+async fn load_stats() -> Stats {
     // 这里可以从数据库获取真实数据
-    let stats = Stats::default();
+    Stats::default()
+}
+
+/// 插件路由共享的状态
+#[derive(Clone)]
+struct LandingState {
+    config: LandingConfig,
+    /// 插件挂载路径（如 `/landing`），模板中的静态资源和片段地址以此为前缀
+    mount_path: Arc<str>,
+}
 
-    StatsTemplate {
-        user_count: stats.user_count,
-        project_count: stats.project_count,
-        satisfaction: stats.satisfaction,
+impl FromRef<LandingState> for LandingConfig {
+    fn from_ref(state: &LandingState) -> Self {
+        state.config.clone()
     }
 }
 
+/// 首页处理器
+///
+/// 以分块传输流式输出：首屏部分渲染后立即发送，统计数据加载完成后再发送统计区块和页脚，
+/// 首字节时间不再受统计查询拖累
+async fn index(State(LandingState { config, mount_path }): State<LandingState>) -> Response {
+    let head = IndexTemplate {
+        mount_path: mount_path.to_string(),
+        title: config.title.clone(),
+        subtitle: config.subtitle.clone(),
+        features: config.features.clone(),
+    }
+    .render();
+    let footer = FooterTemplate {
+        title: config.title.clone(),
+    }
+    .render();
+
+    let (head, footer) = match (head, footer) {
+        (Ok(head), Ok(footer)) => (head, footer),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::error!("Failed to render landing page: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let body = stream::once(future::ready(head))
+        .chain(stream::once(render_stats_section(config, mount_path)))
+        .chain(stream::once(future::ready(footer)))
+        .map(Ok::<_, Infallible>);
+
+    (
+        [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
+
+/// 加载统计数据并渲染统计区块，失败时退回到懒加载占位
+async fn render_stats_section(config: LandingConfig, mount_path: Arc<str>) -> String {
+    let stats = load_stats().await;
+    StatsSectionTemplate::from(StatsTemplate::new(stats, &config))
+        .render()
+        .or_else(|e| {
+            tracing::warn!("Failed to render landing stats section: {}", e);
+            StatsPlaceholderTemplate {
+                mount_path: mount_path.to_string(),
+            }
+            .render()
+        })
+        .unwrap_or_default()
+}
+
+/// 统计数据处理器
+///
+/// 统计数据变化缓慢，允许代理公开缓存 60 秒
+async fn stats(State(config): State<LandingConfig>) -> impl IntoResponse {
+    let stats = load_stats().await;
+
+    CachePolicy::Public(60).apply(StatsTemplate::new(stats, &config))
+}
+
 /// 创建路由
-pub fn create_routes(config: LandingConfig) -> Router {
+///
+/// `mount_path` 须与插件实际挂载的路径一致（通常是 [`HtmxPlugin::mount_path`](htmx_core::HtmxPlugin::mount_path)），
+/// 用于生成页面中的静态资源和片段地址
+pub fn create_routes(config: LandingConfig, mount_path: &str) -> Router {
     Router::new()
         .route("/", get(index))
         .route("/stats", get(stats))
         .route("/static/*path", get(serve_static))
-        .with_state(config)
+        .with_state(LandingState {
+            config,
+            mount_path: Arc::from(mount_path.trim_end_matches('/')),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Request, http::StatusCode};
+    use tower::ServiceExt;
+
+    async fn body_text(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn assets_follow_custom_mount_path() {
+        let app = Router::new().nest("/site", create_routes(LandingConfig::default(), "/site/"));
+
+        let response = app
+            .clone()
+            .oneshot(Request::get("/site").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let html = body_text(response).await;
+        assert!(html.contains("href=\"/site/static/style.css\""), "{}", html);
+
+        let response = app
+            .oneshot(
+                Request::get("/site/static/style.css")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/css"));
+        assert_eq!(
+            body_text(response).await,
+            include_str!("../static/style.css")
+        );
+    }
 }
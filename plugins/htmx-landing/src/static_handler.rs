@@ -1,31 +1,13 @@
-use axum::{
-    body::Body,
-    http::{header, StatusCode, Uri},
-    response::{IntoResponse, Response},
-};
+use axum::{extract::Path, response::Response};
 use rust_embed::RustEmbed;
 
 #[derive(RustEmbed)]
 #[folder = "static/"]
 pub struct StaticAssets;
 
-pub async fn serve_static(uri: Uri) -> impl IntoResponse {
-    let path = uri.path().trim_start_matches("/landing/static/");
-
-    match StaticAssets::get(path) {
-        Some(content) => {
-            let mime = mime_guess::from_path(path).first_or_octet_stream();
-
-            Response::builder()
-                .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, mime.as_ref())
-                .header(header::CACHE_CONTROL, "public, max-age=31536000")
-                .body(Body::from(content.data))
-                .unwrap()
-        }
-        None => Response::builder()
-            .status(StatusCode::NOT_FOUND)
-            .body(Body::from("404 Not Found"))
-            .unwrap(),
-    }
+/// 静态资源处理器
+///
+/// `path` 是 `/static/*path` 的通配部分，与插件的挂载路径无关
+pub async fn serve_static(Path(path): Path<String>) -> Response {
+    htmx_core::embedded_asset::<StaticAssets>(&path)
 }
@@ -1,23 +1,46 @@
+use futures::{stream, Stream, StreamExt};
+use log::LevelFilter;
 use sqlx::{
-    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions},
-    Error as SqlxError, Transaction,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteRow},
+    ConnectOptions, Connection, Error as SqlxError, FromRow, Transaction,
 };
 use std::path::PathBuf;
 use std::str::FromStr;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 use thiserror::Error;
+use tokio::sync::mpsc;
+
+use crate::domain::Email;
+use crate::helpers::config::DatabaseConfig;
+use crate::helpers::monitoring::{count_db_query, record_db_query_duration};
+use crate::helpers::security::sanitize_log_message;
 
 /// 数据库操作错误类型
 #[derive(Error, Debug)]
 pub enum DbError {
     #[error("数据库连接错误: {0}")]
-    Connection(#[from] SqlxError),
+    Connection(#[source] SqlxError),
+    #[error("数据冲突: {0}")]
+    Conflict(String),
     #[error("数据库迁移错误: {0}")]
     Migration(String),
     #[error("事务操作错误: {0}")]
     Transaction(String),
 }
 
+impl From<SqlxError> for DbError {
+    fn from(e: SqlxError) -> Self {
+        // 唯一约束冲突单独归类，便于调用方区分重复数据和真正的故障
+        if e.as_database_error()
+            .is_some_and(|db_error| db_error.is_unique_violation())
+        {
+            return Self::Conflict(e.to_string());
+        }
+        Self::Connection(e)
+    }
+}
+
 /// 数据库迁移信息
 #[derive(Debug, Clone)]
 pub struct MigrationInfo {
@@ -62,6 +85,22 @@ static MIGRATIONS: &[MigrationInfo] = &[
         CREATE INDEX IF NOT EXISTS idx_todos_id_desc ON todos(id DESC);
         "#,
     },
+    MigrationInfo {
+        version: 3,
+        sql: r#"
+        -- 审计日志：记录所有写操作请求
+        CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            principal TEXT,
+            method TEXT NOT NULL,
+            route TEXT NOT NULL,
+            target_id TEXT,
+            status INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_audit_log_created_at ON audit_log(created_at);
+        "#,
+    },
 ];
 
 /// 获取可执行文件所在目录的数据库路径
@@ -80,12 +119,113 @@ fn get_default_db_path() -> String {
     format!("sqlite://{}?mode=rwc", db_path.display())
 }
 
+/// 主库和只读副本的连接池
+///
+/// 写操作（以及需要读到自己刚写入数据的读操作）使用 `write`，列表、搜索等只读查询使用 `read`。
+/// 未配置 `database.read_url` 时两者是同一个连接池
+#[derive(Clone)]
+pub struct Db {
+    pub write: SqlitePool,
+    pub read: SqlitePool,
+}
+
 /// 创建数据库连接池
-pub async fn create_pool() -> Result<SqlitePool, DbError> {
+///
+/// SQLite pragma 取自 `DatabaseConfig`，连接池大小仍可通过环境变量调整。
+/// 配置了 `read_url` 时另建一个只读连接池
+pub async fn create_pool(config: &DatabaseConfig) -> Result<Db, DbError> {
     let database_url = std::env::var("DATABASE_URL").unwrap_or_else(|_| get_default_db_path());
 
     tracing::info!("📂 数据库路径: {}", database_url);
 
+    // 创建连接选项
+    let synchronous = config.synchronous.to_uppercase();
+    let temp_store = config.temp_store.to_uppercase();
+    let options = SqliteConnectOptions::from_str(&database_url)?
+        .journal_mode(SqliteJournalMode::Wal) // 使用WAL模式提高并发性能
+        .busy_timeout(Duration::from_secs(config.busy_timeout_seconds)) // 处理并发写入
+        .create_if_missing(true)
+        .pragma("synchronous", synchronous.clone()) // 默认 NORMAL 优化写入性能
+        .pragma("temp_store", temp_store.clone()) // 默认临时表使用内存
+        .pragma("cache_size", config.cache_size.to_string()) // 默认约64MB
+        .log_statements(statement_log_level(&config.log_statements))
+        .log_slow_statements(
+            slow_statement_log_level(config),
+            slow_statement_threshold(config),
+        );
+
+    tracing::info!(
+        "🔧 SQLite pragma [busy_timeout: {}s, synchronous: {}, temp_store: {}, cache_size: {}]",
+        config.busy_timeout_seconds,
+        synchronous,
+        temp_store,
+        config.cache_size
+    );
+
+    let write = connect_pool(config, options).await?;
+
+    let read = match &config.read_url {
+        Some(read_url) => {
+            // 地址可能带认证参数，与其他配置值一样脱敏后再记录
+            tracing::info!("📖 只读副本: {}", sanitize_log_message(read_url));
+            // 副本由复制工具维护，只读打开，不修改日志模式也不创建文件
+            let options = SqliteConnectOptions::from_str(read_url)?
+                .read_only(true)
+                .busy_timeout(Duration::from_secs(config.busy_timeout_seconds))
+                .pragma("temp_store", temp_store)
+                .pragma("cache_size", config.cache_size.to_string())
+                .log_statements(statement_log_level(&config.log_statements))
+                .log_slow_statements(
+                    slow_statement_log_level(config),
+                    slow_statement_threshold(config),
+                );
+            connect_pool(config, options).await?
+        }
+        None => write.clone(),
+    };
+
+    Ok(Db { write, read })
+}
+
+/// 语句日志级别（`database.log_statements`，已在配置验证中校验）
+///
+/// sqlx 以 `sqlx::query` 为 target 输出语句日志，还需日志过滤器放行对应级别才能看到。
+/// debug 级别的日志包含完整 SQL，排查时注意不要把含敏感数据的日志外传
+fn statement_log_level(level: &str) -> LevelFilter {
+    match level.to_lowercase().as_str() {
+        "off" => LevelFilter::Off,
+        "error" => LevelFilter::Error,
+        "warn" => LevelFilter::Warn,
+        "info" => LevelFilter::Info,
+        _ => LevelFilter::Debug,
+    }
+}
+
+/// 慢语句日志级别，阈值为 0 时关闭
+fn slow_statement_log_level(config: &DatabaseConfig) -> LevelFilter {
+    if config.log_slow_statements_threshold_ms == 0 {
+        LevelFilter::Off
+    } else {
+        LevelFilter::Warn
+    }
+}
+
+/// 慢语句阈值
+fn slow_statement_threshold(config: &DatabaseConfig) -> Duration {
+    Duration::from_millis(config.log_slow_statements_threshold_ms)
+}
+
+/// 启动时连接重试的单次最长等待时间
+const MAX_CONNECT_BACKOFF: Duration = Duration::from_secs(10);
+
+/// 按环境变量中的连接池配置建立连接池
+///
+/// 连接失败时按 `connect_retries`、`connect_retry_backoff_ms` 重试，
+/// 总等待时间不超过 `connect_max_wait_seconds`
+async fn connect_pool(
+    config: &DatabaseConfig,
+    options: SqliteConnectOptions,
+) -> Result<SqlitePool, DbError> {
     // 从环境变量获取连接池配置（用于生产环境调整）
     let max_connections = std::env::var("DB_MAX_CONNECTIONS")
         .ok()
@@ -107,37 +247,132 @@ pub async fn create_pool() -> Result<SqlitePool, DbError> {
         .and_then(|s| s.parse().ok())
         .unwrap_or(600); // 延长空闲超时以保持连接热备
 
-    // 创建连接选项
-    let options = SqliteConnectOptions::from_str(&database_url)?
-        .journal_mode(SqliteJournalMode::Wal) // 使用WAL模式提高并发性能
-        .busy_timeout(Duration::from_secs(10)) // 增加busy_timeout以处理并发写入
-        .create_if_missing(true)
-        .pragma("synchronous", "NORMAL") // 优化写入性能
-        .pragma("temp_store", "MEMORY") // 临时表使用内存
-        .pragma("cache_size", "-65536"); // 增加缓存大小约512MB
-
     // 配置连接池
-    let pool = SqlitePoolOptions::new()
+    // 取出连接前先 ping 一次，坏连接会被丢弃并换一个新连接，调用方无感知；
+    // 代价是每次取连接多一次往返（SQLite 为进程内调用，通常在微秒级）
+    let test_before_acquire = config.test_before_acquire;
+    let pool_options = SqlitePoolOptions::new()
+        .test_before_acquire(false)
+        .before_acquire(move |conn, _meta| {
+            Box::pin(async move {
+                if !test_before_acquire {
+                    return Ok(true);
+                }
+                match conn.ping().await {
+                    Ok(()) => Ok(true),
+                    Err(e) => {
+                        metrics::increment_counter!("db_connections_recycled_total");
+                        tracing::warn!("⚠️  检测到失效的数据库连接，已丢弃并重新获取: {}", e);
+                        Ok(false)
+                    }
+                }
+            })
+        })
         .max_connections(max_connections)
         .min_connections(min_connections)
         .acquire_timeout(Duration::from_secs(acquire_timeout))
         .idle_timeout(Duration::from_secs(idle_timeout))
-        .max_lifetime(Duration::from_secs(3600)) // 添加最大生命周期，防止连接泄漏
-        .connect_with(options)
-        .await?;
+        .max_lifetime(Duration::from_secs(3600)); // 添加最大生命周期，防止连接泄漏
+
+    // 容器编排中数据库（挂载卷、副本文件等）可能晚于应用就绪，失败后按指数退避重试
+    let started = Instant::now();
+    let max_wait = Duration::from_secs(config.connect_max_wait_seconds);
+    let mut backoff = Duration::from_millis(config.connect_retry_backoff_ms);
+    let mut retries = 0;
+    let pool = loop {
+        match pool_options.clone().connect_with(options.clone()).await {
+            Ok(pool) => break pool,
+            Err(e)
+                if retries < config.connect_retries && started.elapsed() + backoff <= max_wait =>
+            {
+                retries += 1;
+                tracing::warn!(
+                    "⚠️  数据库连接失败，{:?} 后第 {}/{} 次重试: {}",
+                    backoff,
+                    retries,
+                    config.connect_retries,
+                    e
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_CONNECT_BACKOFF);
+            }
+            Err(e) => {
+                if retries > 0 {
+                    tracing::error!(
+                        "❌ 数据库连接重试 {} 次（{:?}）后仍然失败",
+                        retries,
+                        started.elapsed()
+                    );
+                }
+                return Err(e.into());
+            }
+        }
+    };
 
     tracing::info!(
-        "✅ 数据库连接池创建成功 [最大: {}, 最小: {}, 超时: {}s]",
+        "✅ 数据库连接池创建成功 [最大: {}, 最小: {}, 超时: {}s, 取用前检测: {}]",
         max_connections,
         min_connections,
-        acquire_timeout
+        acquire_timeout,
+        test_before_acquire
     );
     Ok(pool)
 }
 
+/// 等待迁移写锁时的重试间隔
+const MIGRATION_LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 错误是否为 SQLite 的 `SQLITE_BUSY`（含扩展错误码），即数据库被其他连接锁定
+fn is_busy_error(e: &SqlxError) -> bool {
+    e.as_database_error()
+        .and_then(|db_error| db_error.code())
+        .and_then(|code| code.parse::<i32>().ok())
+        .is_some_and(|code| code & 0xff == 5)
+}
+
+/// 以 `BEGIN IMMEDIATE` 开启迁移事务
+///
+/// 多个实例同时启动时（如滚动部署），先拿到写锁的实例执行迁移，其他实例在此等待：
+/// 每次尝试最多等待一个 busy_timeout，仍被占用时按固定间隔重试，
+/// 直到超过 `migration_lock_wait_seconds`。拿到锁后读取的已应用版本包含先前实例的迁移，
+/// 因此不会重复执行。普通的 `BEGIN` 先加读锁，两个实例都读到旧版本后升级写锁时有一方会直接失败
+async fn begin_migration(
+    pool: &SqlitePool,
+    max_wait: Duration,
+) -> Result<Transaction<'_, sqlx::Sqlite>, DbError> {
+    let started = Instant::now();
+    let mut waiting_logged = false;
+
+    loop {
+        match pool.begin_with("BEGIN IMMEDIATE").await {
+            Ok(tx) => return Ok(tx),
+            Err(e) if is_busy_error(&e) && started.elapsed() < max_wait => {
+                if !waiting_logged {
+                    tracing::info!("⏳ 数据库正被其他实例锁定（可能正在迁移），等待其完成...");
+                    waiting_logged = true;
+                }
+                tokio::time::sleep(MIGRATION_LOCK_RETRY_INTERVAL).await;
+            }
+            Err(e) => {
+                return Err(DbError::Migration(format!(
+                    "获取迁移写锁失败（已等待 {:?}）: {}",
+                    started.elapsed(),
+                    e
+                )))
+            }
+        }
+    }
+}
+
 /// 执行结构化的数据库迁移
-pub async fn run_migrations(pool: &SqlitePool) -> Result<(), DbError> {
-    let mut tx = start_transaction(pool).await?;
+///
+/// 迁移在持有写锁的单个事务中执行，多个实例并发启动时只有一个实例应用迁移
+pub async fn run_migrations(pool: &SqlitePool, config: &DatabaseConfig) -> Result<(), DbError> {
+    let mut tx = begin_migration(
+        pool,
+        Duration::from_secs(config.migration_lock_wait_seconds),
+    )
+    .await?;
 
     // 确保schema_migrations表存在
     sqlx::query(
@@ -181,6 +416,65 @@ pub async fn run_migrations(pool: &SqlitePool) -> Result<(), DbError> {
     Ok(())
 }
 
+/// 首个支持 `RETURNING` 子句的 SQLite 版本
+const RETURNING_MIN_VERSION: (u32, u32, u32) = (3, 35, 0);
+
+/// 当前数据库是否支持 `RETURNING`，启动时由 [`detect_returning_support`] 设置
+static RETURNING_SUPPORTED: AtomicBool = AtomicBool::new(true);
+
+/// 写操作能否用 `RETURNING` 直接取回写入的行，不支持时应先写入再按 id 查询
+pub fn returning_supported() -> bool {
+    RETURNING_SUPPORTED.load(Ordering::Relaxed)
+}
+
+/// 启动时检测一次 `RETURNING` 支持情况
+///
+/// `configured` 为 `database.use_returning`，设置时直接采用；否则按 `sqlite_version()` 判断，
+/// 查询失败时保守地使用回退路径
+pub async fn detect_returning_support(pool: &SqlitePool, configured: Option<bool>) {
+    let supported = match configured {
+        Some(supported) => supported,
+        None => match sqlx::query_scalar::<_, String>("SELECT sqlite_version()")
+            .fetch_one(pool)
+            .await
+        {
+            Ok(version) => {
+                let supported = version_supports_returning(&version);
+                tracing::debug!("SQLite 版本: {}", version);
+                supported
+            }
+            Err(e) => {
+                tracing::warn!("⚠️  无法获取 SQLite 版本，不使用 RETURNING: {}", e);
+                false
+            }
+        },
+    };
+
+    RETURNING_SUPPORTED.store(supported, Ordering::Relaxed);
+    if supported {
+        tracing::info!("🔧 写操作使用 RETURNING 取回写入的行");
+    } else {
+        tracing::info!("🔧 写操作不使用 RETURNING，写入后按 id 查询");
+    }
+}
+
+/// 该 SQLite 版本是否支持 `RETURNING`，无法解析时视为不支持
+fn version_supports_returning(version: &str) -> bool {
+    parse_sqlite_version(version).is_some_and(|version| version >= RETURNING_MIN_VERSION)
+}
+
+/// 解析 `3.45.1` 形式的版本号
+fn parse_sqlite_version(version: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = version
+        .trim()
+        .split('.')
+        .map(|part| part.parse::<u32>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().flatten().unwrap_or(0);
+    let patch = parts.next().flatten().unwrap_or(0);
+    Some((major, minor, patch))
+}
+
 /// 开始数据库事务
 pub async fn start_transaction(
     pool: &SqlitePool,
@@ -190,6 +484,67 @@ pub async fn start_transaction(
         .map_err(|e| DbError::Transaction(e.to_string()))
 }
 
+/// 流式查询每次向消费者预取的行数
+const STREAM_BUFFER_ROWS: usize = 64;
+
+/// 流式读取查询结果，逐行产出而不是一次性加载到 `Vec`
+///
+/// 查询在独立任务中执行，通过有界通道向消费者推送，消费者读取变慢时查询随之暂停。
+/// 连接只在查询进行期间占用：结果读完、出错或消费者提前丢弃流（如客户端断开）时立即归还连接池。
+/// 中途出错时错误作为最后一项产出，之后流结束
+pub fn fetch_stream<T>(
+    pool: &SqlitePool,
+    query_name: &'static str,
+    sql: String,
+) -> impl Stream<Item = Result<T, SqlxError>> + Send + 'static
+where
+    T: for<'r> FromRow<'r, SqliteRow> + Send + Unpin + 'static,
+{
+    // 查询计数需在请求任务中记录
+    count_db_query(query_name);
+
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER_ROWS);
+    let pool = pool.clone();
+
+    tokio::spawn(async move {
+        let start = Instant::now();
+        let mut rows = sqlx::query_as::<_, T>(&sql).fetch(&pool);
+        let mut success = true;
+
+        while let Some(row) = rows.next().await {
+            success = row.is_ok();
+            // 消费者已丢弃，停止查询并释放连接
+            if tx.send(row).await.is_err() || !success {
+                break;
+            }
+        }
+
+        record_db_query_duration(query_name, start, success);
+    });
+
+    stream::unfold(
+        rx,
+        |mut rx| async move { rx.recv().await.map(|row| (row, rx)) },
+    )
+}
+
+/// 构建 LIKE 子串匹配模式
+///
+/// 转义通配符 `%`、`_` 以及转义符 `\` 本身，需配合 SQL 中的 `ESCAPE '\'` 使用，
+/// 避免用户输入的通配符改变匹配语义
+pub fn like_pattern(query: &str) -> String {
+    let mut pattern = String::with_capacity(query.len() + 2);
+    pattern.push('%');
+    for c in query.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
 /// 插入示例数据
 pub async fn seed_data(pool: &SqlitePool) -> Result<(), DbError> {
     let mut tx = start_transaction(pool).await?;
@@ -222,46 +577,53 @@ pub async fn seed_data(pool: &SqlitePool) -> Result<(), DbError> {
         tracing::info!("✅ 插入待办事项示例数据");
     }
 
-    // 检查用户数据
-    let user_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
-        .fetch_one(&mut *tx)
-        .await?;
-
-    if user_count == 0 {
-        // 插入示例用户（20个）
-        let users = vec![
-            ("张三", "zhangsan@example.com"),
-            ("李四", "lisi@example.com"),
-            ("王五", "wangwu@example.com"),
-            ("赵六", "zhaoliu@example.com"),
-            ("孙七", "sunqi@example.com"),
-            ("周八", "zhouba@example.com"),
-            ("吴九", "wujiu@example.com"),
-            ("郑十", "zhengshi@example.com"),
-            ("陈一一", "chenyiyi@example.com"),
-            ("褚一二", "chuyier@example.com"),
-            ("卫一三", "weiyisan@example.com"),
-            ("蒋一四", "jiangyisi@example.com"),
-            ("沈一五", "shenyiwu@example.com"),
-            ("韩一六", "hanyiliu@example.com"),
-            ("杨一七", "yangyiqi@example.com"),
-            ("朱一八", "zhuyiba@example.com"),
-            ("秦一九", "qinyijiu@example.com"),
-            ("尤二十", "youershi@example.com"),
-            ("许二一", "xueryi@example.com"),
-            ("何二二", "heerer@example.com"),
-        ];
-        let user_count = users.len();
-
-        for (name, email) in users {
-            sqlx::query("INSERT INTO users (name, email) VALUES (?, ?)")
-                .bind(name)
-                .bind(email)
-                .execute(&mut *tx)
-                .await?;
-        }
+    // 示例用户（20个）：不检查表是否为空，逐个按邮箱去重插入，
+    // 之前的部分写入或并发启动留下的同邮箱用户直接跳过，缺少的用户会被补齐
+    let users = vec![
+        ("张三", "zhangsan@example.com"),
+        ("李四", "lisi@example.com"),
+        ("王五", "wangwu@example.com"),
+        ("赵六", "zhaoliu@example.com"),
+        ("孙七", "sunqi@example.com"),
+        ("周八", "zhouba@example.com"),
+        ("吴九", "wujiu@example.com"),
+        ("郑十", "zhengshi@example.com"),
+        ("陈一一", "chenyiyi@example.com"),
+        ("褚一二", "chuyier@example.com"),
+        ("卫一三", "weiyisan@example.com"),
+        ("蒋一四", "jiangyisi@example.com"),
+        ("沈一五", "shenyiwu@example.com"),
+        ("韩一六", "hanyiliu@example.com"),
+        ("杨一七", "yangyiqi@example.com"),
+        ("朱一八", "zhuyiba@example.com"),
+        ("秦一九", "qinyijiu@example.com"),
+        ("尤二十", "youershi@example.com"),
+        ("许二一", "xueryi@example.com"),
+        ("何二二", "heerer@example.com"),
+    ];
+    let total = users.len();
+
+    let mut inserted = 0;
+    for (name, email) in users {
+        let email = Email::parse(email).expect("示例数据中的邮箱应当有效");
+        inserted += sqlx::query("INSERT OR IGNORE INTO users (name, email) VALUES (?, ?)")
+            .bind(name)
+            .bind(email)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    }
 
-        tracing::info!("✅ 插入 {} 个用户示例数据", user_count);
+    if inserted == 0 {
+        tracing::debug!("示例用户均已存在，跳过插入");
+    } else if inserted < total as u64 {
+        tracing::info!(
+            "✅ 插入 {} 个用户示例数据（{} 个邮箱已存在，已跳过）",
+            inserted,
+            total as u64 - inserted
+        );
+    } else {
+        tracing::info!("✅ 插入 {} 个用户示例数据", inserted);
     }
 
     tx.commit().await?;
@@ -271,5 +633,167 @@ pub async fn seed_data(pool: &SqlitePool) -> Result<(), DbError> {
 /// 简化的数据库初始化函数（兼容旧接口）
 #[allow(dead_code)]
 pub async fn init_db(pool: &SqlitePool) -> Result<(), DbError> {
-    run_migrations(pool).await
+    run_migrations(pool, &DatabaseConfig::default()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 已执行迁移的单连接内存数据库
+    async fn migrated_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        run_migrations(&pool, &DatabaseConfig::default())
+            .await
+            .unwrap();
+        pool
+    }
+
+    async fn user_count(pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM users")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reseed_tops_up_partial_seed() {
+        let pool = migrated_pool().await;
+        seed_data(&pool).await.unwrap();
+        assert_eq!(user_count(&pool).await, 20);
+
+        // 模拟部分写入：表中有其他用户，但缺少部分示例用户
+        sqlx::query("INSERT INTO users (name, email) VALUES ('额外', 'extra@example.com')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE email IN ('lisi@example.com', 'heerer@example.com')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        seed_data(&pool).await.unwrap();
+        assert_eq!(user_count(&pool).await, 21);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn concurrent_migrations_apply_each_version_once() {
+        let path = std::env::temp_dir().join(format!(
+            "htmx-rs-template-migrations-{}.db",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let options = SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal);
+
+        // 两个连接池模拟同时启动的两个实例
+        let mut tasks = Vec::new();
+        for _ in 0..2 {
+            let pool = SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect_with(options.clone())
+                .await
+                .unwrap();
+            tasks.push(tokio::spawn(async move {
+                run_migrations(&pool, &DatabaseConfig::default()).await
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        let pool = SqlitePool::connect_with(options).await.unwrap();
+        let applied: Vec<(i64, i64)> =
+            sqlx::query_as("SELECT version, COUNT(*) FROM schema_migrations GROUP BY version")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        pool.close().await;
+        let _ = std::fs::remove_file(&path);
+
+        let expected: Vec<(i64, i64)> = MIGRATIONS.iter().map(|m| (m.version, 1)).collect();
+        assert_eq!(applied, expected);
+    }
+
+    #[test]
+    fn returning_requires_sqlite_3_35() {
+        for (version, supported) in [
+            ("3.34.1", false),
+            ("3.35.0", true),
+            ("3.45.1", true),
+            ("4", true),
+            ("not-a-version", false),
+        ] {
+            assert_eq!(
+                version_supports_returning(version),
+                supported,
+                "{}",
+                version
+            );
+        }
+    }
+
+    /// 位于尚不存在的目录中的数据库文件，目录创建前连接会失败
+    fn delayed_db_options(name: &str) -> (PathBuf, SqliteConnectOptions) {
+        let dir =
+            std::env::temp_dir().join(format!("htmx-rs-template-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let options = SqliteConnectOptions::new()
+            .filename(dir.join("app.db"))
+            .create_if_missing(true);
+        (dir, options)
+    }
+
+    #[tokio::test]
+    async fn connect_retries_until_database_is_available() {
+        let (dir, options) = delayed_db_options("connect-retry");
+        let config = DatabaseConfig {
+            connect_retries: 10,
+            connect_retry_backoff_ms: 50,
+            connect_max_wait_seconds: 5,
+            ..DatabaseConfig::default()
+        };
+
+        // 模拟数据库晚于应用就绪：稍后才创建数据库所在目录
+        let create_dir = {
+            let dir = dir.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(150)).await;
+                std::fs::create_dir_all(dir).unwrap();
+            })
+        };
+
+        let pool = connect_pool(&config, options).await.unwrap();
+        create_dir.await.unwrap();
+        let one: i64 = sqlx::query_scalar("SELECT 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(one, 1);
+    }
+
+    #[tokio::test]
+    async fn connect_gives_up_after_retries() {
+        let (_dir, options) = delayed_db_options("connect-give-up");
+        let config = DatabaseConfig {
+            connect_retries: 2,
+            connect_retry_backoff_ms: 10,
+            connect_max_wait_seconds: 5,
+            ..DatabaseConfig::default()
+        };
+
+        let started = Instant::now();
+        assert!(connect_pool(&config, options).await.is_err());
+        // 两次重试共等待 10ms + 20ms，不会等满总时长上限
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
 }
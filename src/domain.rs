@@ -0,0 +1,250 @@
+//! 领域类型模块
+//!
+//! 在构造时完成校验和规范化的值类型，避免无效数据流入处理器和数据库
+
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    encode::IsNull,
+    error::BoxDynError,
+    sqlite::{Sqlite, SqliteArgumentValue, SqliteTypeInfo, SqliteValueRef},
+    Decode, Encode, Type,
+};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// 邮箱格式错误
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EmailError {
+    #[error("邮箱不能为空")]
+    Empty,
+    #[error("邮箱格式无效: {0}")]
+    Invalid(String),
+}
+
+/// 经过校验的邮箱地址
+///
+/// 输入路径通过 [`Email::parse`] 构造：去除首尾空白、转为小写并校验格式，
+/// 因此同一地址的不同大小写写法会被视为同一个邮箱。从数据库读取时不再校验
+/// （见 [`Email::from_trusted`]），早期写入的不合规数据不会导致查询失败
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Email(String);
+
+impl Email {
+    /// 校验并规范化邮箱
+    pub fn parse(raw: &str) -> Result<Self, EmailError> {
+        let normalized = raw.trim().to_lowercase();
+
+        if normalized.is_empty() {
+            return Err(EmailError::Empty);
+        }
+        if !validator::validate_email(&normalized) {
+            return Err(EmailError::Invalid(normalized));
+        }
+
+        Ok(Self(normalized))
+    }
+
+    /// 包装已存储的邮箱，不做校验和规范化
+    ///
+    /// 仅用于读取数据库中已有的值，用户输入必须使用 [`Email::parse`]
+    pub fn from_trusted(stored: String) -> Self {
+        Self(stored)
+    }
+
+    /// 规范化后的邮箱字符串
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Email {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for Email {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Email {
+    type Err = EmailError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for Email {
+    type Error = EmailError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl From<Email> for String {
+    fn from(email: Email) -> Self {
+        email.0
+    }
+}
+
+// 数据库中按 TEXT 存储；写入前已由 `Email::parse` 校验，读取时原样还原
+
+impl Type<Sqlite> for Email {
+    fn type_info() -> SqliteTypeInfo {
+        <String as Type<Sqlite>>::type_info()
+    }
+
+    fn compatible(ty: &SqliteTypeInfo) -> bool {
+        <String as Type<Sqlite>>::compatible(ty)
+    }
+}
+
+impl<'q> Encode<'q, Sqlite> for Email {
+    fn encode_by_ref(&self, buf: &mut Vec<SqliteArgumentValue<'q>>) -> Result<IsNull, BoxDynError> {
+        <String as Encode<'q, Sqlite>>::encode_by_ref(&self.0, buf)
+    }
+}
+
+impl<'r> Decode<'r, Sqlite> for Email {
+    fn decode(value: SqliteValueRef<'r>) -> Result<Self, BoxDynError> {
+        let stored = <String as Decode<'r, Sqlite>>::decode(value)?;
+        Ok(Self::from_trusted(stored))
+    }
+}
+
+/// 用户列表的排序字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserSortField {
+    Id,
+    Name,
+    Email,
+}
+
+/// 用户列表排序
+///
+/// 只接受白名单内的字段和方向，ORDER BY 子句均为静态字符串，不拼接用户输入
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UserSort {
+    pub field: UserSortField,
+    pub descending: bool,
+}
+
+impl Default for UserSort {
+    /// 静态的兜底排序 `id:asc`，与 `ui.default_user_sort` 的默认值一致；
+    /// 不读取配置，处理器使用配置的默认排序
+    fn default() -> Self {
+        Self {
+            field: UserSortField::Id,
+            descending: false,
+        }
+    }
+}
+
+impl UserSort {
+    /// 解析 `字段:方向`（方向可省略，默认升序），字段或方向不在白名单内时返回 `None`
+    pub fn try_parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        let (field, direction) = raw.split_once(':').unwrap_or((raw, "asc"));
+        let field = match field.to_ascii_lowercase().as_str() {
+            "id" => UserSortField::Id,
+            "name" => UserSortField::Name,
+            "email" => UserSortField::Email,
+            _ => return None,
+        };
+        let descending = match direction.to_ascii_lowercase().as_str() {
+            "asc" => false,
+            "desc" => true,
+            _ => return None,
+        };
+
+        Some(Self { field, descending })
+    }
+
+    /// ORDER BY 子句
+    ///
+    /// name/email 排序以 id 作为次序键，可直接使用 `idx_users_name`/`idx_users_email`
+    /// （索引隐含 rowid 即 id），降序时反向扫描同一索引
+    pub fn order_by(&self) -> &'static str {
+        match (self.field, self.descending) {
+            (UserSortField::Id, false) => "id ASC",
+            (UserSortField::Id, true) => "id DESC",
+            (UserSortField::Name, false) => "name ASC, id ASC",
+            (UserSortField::Name, true) => "name DESC, id DESC",
+            (UserSortField::Email, false) => "email ASC, id ASC",
+            (UserSortField::Email, true) => "email DESC, id DESC",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_valid_emails() {
+        for raw in ["alice@example.com", "bob.smith+tag@mail.example.org"] {
+            assert_eq!(Email::parse(raw).unwrap().as_str(), raw);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_emails() {
+        assert_eq!(Email::parse("   "), Err(EmailError::Empty));
+        for raw in ["alice", "alice@", "@example.com", "a b@example.com"] {
+            assert!(matches!(Email::parse(raw), Err(EmailError::Invalid(_))));
+        }
+    }
+
+    #[test]
+    fn normalizes_case_and_whitespace() {
+        let email = Email::parse("  Alice@Example.COM ").unwrap();
+        assert_eq!(email.as_str(), "alice@example.com");
+        assert_eq!(email, Email::parse("alice@example.com").unwrap());
+    }
+
+    #[tokio::test]
+    async fn decodes_stored_values_without_validation() {
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        let email: Email = sqlx::query_scalar("SELECT 'Legacy Value'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(email.as_str(), "Legacy Value");
+    }
+
+    #[test]
+    fn user_sort_parses_each_option() {
+        let cases = [
+            ("id:asc", "id ASC"),
+            ("id:desc", "id DESC"),
+            ("name:asc", "name ASC, id ASC"),
+            ("name:desc", "name DESC, id DESC"),
+            ("email:asc", "email ASC, id ASC"),
+            ("email:desc", "email DESC, id DESC"),
+            ("name", "name ASC, id ASC"),
+            (" EMAIL:DESC ", "email DESC, id DESC"),
+        ];
+        for (raw, order_by) in cases {
+            assert_eq!(
+                UserSort::try_parse(raw).unwrap().order_by(),
+                order_by,
+                "{}",
+                raw
+            );
+        }
+    }
+
+    #[test]
+    fn user_sort_rejects_unknown_fields_and_directions() {
+        for raw in ["", "age:asc", "name:up", "name;drop", "name:sideways"] {
+            assert_eq!(UserSort::try_parse(raw), None, "{}", raw);
+        }
+        assert_eq!(UserSort::default().order_by(), "id ASC");
+    }
+}
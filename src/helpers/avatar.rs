@@ -0,0 +1,78 @@
+//! 用户头像辅助函数
+//!
+//! 用户没有上传头像时的回退方案：启用 Gravatar 时根据邮箱生成 Gravatar 地址，
+//! 否则在服务端生成带首字母的 SVG
+
+use md5::{Digest, Md5};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::domain::Email;
+use crate::helpers::config::CONFIG;
+use crate::helpers::html::escape_html;
+
+/// 首字母头像的背景色（Bootstrap 主题色）
+const AVATAR_COLORS: [&str; 6] = [
+    "#0d6efd", "#6610f2", "#d63384", "#fd7e14", "#198754", "#0dcaf0",
+];
+
+/// 根据邮箱生成 Gravatar 地址
+///
+/// `Email` 已规范化为小写，直接对其做 MD5 即可
+pub fn gravatar_url(email: &Email, size: u32, default_style: &str) -> String {
+    let digest = Md5::digest(email.as_str().as_bytes());
+    let hash: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "https://www.gravatar.com/avatar/{}?s={}&d={}",
+        hash, size, default_style
+    )
+}
+
+/// 取姓名首字母：多个单词时取首尾单词的首字母（如 `John Smith` → `JS`），否则取第一个字符
+pub fn initials(name: &str) -> String {
+    let mut words = name.split_whitespace();
+    let first = words.next().and_then(|w| w.chars().next());
+    let last = words.last().and_then(|w| w.chars().next());
+
+    match (first, last) {
+        (Some(first), Some(last)) => first.to_uppercase().chain(last.to_uppercase()).collect(),
+        (Some(first), None) => first.to_uppercase().collect(),
+        _ => "?".to_string(),
+    }
+}
+
+/// 生成首字母 SVG 头像，背景色由姓名决定，同一用户颜色稳定
+pub fn initials_svg(name: &str, size: u32) -> String {
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    let color = AVATAR_COLORS[(hasher.finish() % AVATAR_COLORS.len() as u64) as usize];
+
+    format!(
+        "<svg class=\"avatar rounded-circle\" xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" \
+         viewBox=\"0 0 100 100\" role=\"img\" aria-label=\"{label}\">\
+         <rect width=\"100\" height=\"100\" fill=\"{color}\"/>\
+         <text x=\"50\" y=\"50\" dy=\".35em\" text-anchor=\"middle\" fill=\"#fff\" \
+         font-size=\"40\" font-weight=\"bold\" font-family=\"sans-serif\">{text}</text></svg>",
+        size = size,
+        label = escape_html(name),
+        color = color,
+        text = escape_html(&initials(name)),
+    )
+}
+
+/// 渲染用户头像 HTML，按配置选择 Gravatar 或首字母 SVG
+pub fn avatar_html(name: &str, email: &Email, size: u32) -> String {
+    let config = &CONFIG.avatar;
+
+    if !config.gravatar_enabled {
+        return initials_svg(name, size);
+    }
+
+    // 请求 2 倍尺寸以适配高分屏
+    format!(
+        "<img class=\"avatar rounded-circle\" src=\"{}\" width=\"{size}\" height=\"{size}\" alt=\"{}\" loading=\"lazy\">",
+        escape_html(&gravatar_url(email, size * 2, &config.gravatar_default)),
+        escape_html(name),
+        size = size,
+    )
+}
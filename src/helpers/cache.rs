@@ -1,60 +1,214 @@
 //! 缓存管理模块
 //!
-//! 提供通用的缓存存储、获取和失效管理功能，优化的并发性能和自动过期清理机制
+//! 提供通用的缓存存储、获取和失效管理功能，优化的并发性能和自动过期清理机制。
+//! 默认使用进程内缓存；启用 `redis` feature 并配置 `cache.backend = "redis"` 后，
+//! 缓存数据以 JSON 序列化后存入 Redis，可跨实例共享并在重启后保留
 
 use metrics::{gauge, increment_counter};
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, RwLock};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 use std::thread;
 use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 
+use crate::helpers::config::{CacheConfig, CONFIG};
+
+/// 可缓存的数据
+///
+/// 除克隆外还要求可序列化，以便写入 Redis 等外部后端；进程内后端不会序列化
+pub trait CacheValue: Clone + Serialize + DeserializeOwned + Send + Sync + 'static {}
+
+impl<T> CacheValue for T where T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static {}
+
+/// 缓存后端
+pub trait Cache: Send + Sync {
+    /// 获取未过期、未失效的缓存数据
+    fn get<T: CacheValue>(&self, key: &str) -> impl Future<Output = Option<T>> + Send;
+
+    /// 写入缓存，`duration` 为空时使用默认缓存时长
+    fn set<T: CacheValue>(
+        &self,
+        key: &str,
+        data: T,
+        duration: Option<Duration>,
+    ) -> impl Future<Output = ()> + Send;
+
+    /// 使缓存键失效
+    fn invalidate(&self, key: &str) -> impl Future<Output = ()> + Send;
+
+    /// 一次性失效一组缓存，每项为缓存键或以 `*` 结尾的前缀
+    fn invalidate_patterns(&self, patterns: &[String]) -> impl Future<Output = ()> + Send;
+}
+
+/// 把失效模式拆分为精确的缓存键和前缀（去掉末尾的 `*`）
+pub(crate) fn split_patterns(patterns: &[String]) -> (Vec<&str>, Vec<&str>) {
+    let mut keys = Vec::new();
+    let mut prefixes = Vec::new();
+    for pattern in patterns {
+        match pattern.strip_suffix('*') {
+            Some(prefix) => prefixes.push(prefix),
+            None => keys.push(pattern.as_str()),
+        }
+    }
+    (keys, prefixes)
+}
+
 /// 通用缓存项
 /// 存储数据和过期时间点
 struct CacheItem<T> {
     data: T,
     expiration: Instant,
+    #[allow(dead_code)]
     creation_time: Instant, // 添加创建时间，便于调试和统计
 }
 
+/// 缓存键的命名空间：第一个 `:` 之前的部分，如 `initial_users:12` 属于 `initial_users`
+fn key_namespace(key: &str) -> Option<&str> {
+    key.split_once(':').map(|(namespace, _)| namespace)
+}
+
+/// 缓存指标的 `namespace` 标签：键的命名空间，没有 `:` 的键（如 `todos`）取键本身
+///
+/// 不直接用完整的键作为标签，避免搜索词等动态部分让指标的标签基数无限增长
+pub(crate) fn metric_namespace(key: &str) -> String {
+    key_namespace(key).unwrap_or(key).to_string()
+}
+
+/// 按命名空间限制缓存键数量
+///
+/// 只跟踪配置了容量的命名空间，按写入顺序记录键，超出容量时淘汰最早写入的键，
+/// 避免同一命名空间下按参数区分的大量键（如 `initial_users:<数量>`）挤占其他缓存
+#[derive(Default)]
+struct NamespaceIndex {
+    /// 各命名空间的最大键数
+    capacities: HashMap<String, usize>,
+    /// 各命名空间中的键及其写入序号，按写入顺序排列
+    keys: HashMap<String, VecDeque<(String, u64)>>,
+}
+
+impl NamespaceIndex {
+    /// 记录一次写入（`seq` 为该次写入的序号），返回因超出容量需要淘汰的键和对应序号
+    fn record(&mut self, key: &str, seq: u64) -> Vec<(String, u64)> {
+        let Some(namespace) = key_namespace(key) else {
+            return Vec::new();
+        };
+        let Some(&capacity) = self.capacities.get(namespace) else {
+            return Vec::new();
+        };
+
+        let keys = self.keys.entry(namespace.to_string()).or_default();
+        // 重复写入同一个键时移到队尾（容量有上限，线性查找开销可控）
+        if let Some(pos) = keys.iter().position(|(k, _)| k == key) {
+            keys.remove(pos);
+        }
+        keys.push_back((key.to_string(), seq));
+
+        let overflow = keys.len().saturating_sub(capacity);
+        keys.drain(..overflow).collect()
+    }
+
+    /// 键已从缓存中删除，不再计入命名空间容量
+    fn forget(&mut self, key: &str) {
+        if let Some(keys) = key_namespace(key).and_then(|namespace| self.keys.get_mut(namespace)) {
+            if let Some(pos) = keys.iter().position(|(k, _)| k == key) {
+                keys.remove(pos);
+            }
+        }
+    }
+}
+
+/// 分片中的缓存项，`seq` 为写入序号，用于判断淘汰时键是否已被重新写入
+struct Slot {
+    seq: u64,
+    item: Box<dyn std::any::Any + Send + Sync>,
+}
+
+/// 缓存数据分片
+type CacheShard = RwLock<HashMap<String, Slot>>;
+
 /// 通用缓存管理器
 /// 提供缓存数据的存储、获取和失效管理功能，包含自动过期清理机制
+///
+/// 缓存数据按键的哈希分散到多个分片，每个分片单独加锁，
+/// 写入时只阻塞同一分片上的读写，突发写入（如缓存预热叠加正常流量）不再互相串行
 struct CacheManager {
-    /// 存储缓存数据的映射表
-    cache_data: RwLock<HashMap<String, Box<dyn std::any::Any + Send + Sync>>>,
+    /// 存储缓存数据的分片
+    shards: Box<[CacheShard]>,
+    /// 计算键所在分片的哈希器
+    hasher: RandomState,
+    /// 各分片缓存项数之和，随插入和删除更新，上报大小指标时不必锁定所有分片
+    item_count: AtomicUsize,
+    /// 下一次写入的序号
+    next_seq: AtomicU64,
     /// 存储缓存失效信号的映射表
     invalid_signals: RwLock<HashMap<String, bool>>,
     /// 默认缓存持续时间
     default_duration: RwLock<Duration>,
+    /// 命名空间容量限制
+    namespaces: Mutex<NamespaceIndex>,
     /// 停止标志，用于安全关闭清理线程
     stop_flag: Arc<AtomicBool>,
     /// 清理线程句柄
-    cleanup_thread: Option<JoinHandle<()>>,
+    cleanup_thread: Mutex<Option<JoinHandle<()>>>,
     /// 清理间隔
     cleanup_interval: Duration,
 }
 
 impl CacheManager {
-    /// 创建新的缓存管理器实例
-    fn new() -> Self {
+    /// 创建新的缓存管理器实例，`shard_count` 为数据分片数（至少 1 个）
+    fn new(shard_count: usize) -> Self {
         let stop_flag = Arc::new(AtomicBool::new(false));
         let cleanup_interval = Duration::from_secs(30); // 默认30秒清理一次
 
         Self {
-            cache_data: RwLock::new(HashMap::new()),
+            shards: (0..shard_count.max(1))
+                .map(|_| RwLock::new(HashMap::new()))
+                .collect(),
+            hasher: RandomState::new(),
+            item_count: AtomicUsize::new(0),
+            next_seq: AtomicU64::new(0),
             invalid_signals: RwLock::new(HashMap::new()),
             default_duration: RwLock::new(Duration::from_secs(60)), // 默认缓存1分钟
+            namespaces: Mutex::new(NamespaceIndex::default()),
             stop_flag,
-            cleanup_thread: None, // 初始化时不启动线程
+            cleanup_thread: Mutex::new(None), // 初始化时不启动线程
             cleanup_interval,
         }
     }
 
+    /// 键所在分片的下标
+    fn shard_index(&self, key: &str) -> usize {
+        (self.hasher.hash_one(key) % self.shards.len() as u64) as usize
+    }
+
+    /// 键所在的分片
+    fn shard(&self, key: &str) -> &CacheShard {
+        &self.shards[self.shard_index(key)]
+    }
+
+    /// 各分片缓存项数之和
+    fn len(&self) -> usize {
+        self.item_count.load(Ordering::Relaxed)
+    }
+
+    /// 记录删除的缓存项数
+    fn record_removed(&self, count: usize) {
+        if count > 0 {
+            self.item_count.fetch_sub(count, Ordering::Relaxed);
+        }
+    }
+
     /// 启动后台清理线程
-    fn start_cleanup_thread(&mut self) {
+    fn start_cleanup_thread(&self) {
+        let mut cleanup_thread_slot = self.cleanup_thread.lock().unwrap();
+
         // 如果线程已存在，则不重复启动
-        if self.cleanup_thread.is_some() {
+        if cleanup_thread_slot.is_some() {
             return;
         }
 
@@ -71,12 +225,12 @@ impl CacheManager {
                     manager.cleanup_expired();
                 }
 
-                // 休眠指定时间
-                thread::sleep(cleanup_interval);
+                // 休眠指定时间，停止时会被 unpark 提前唤醒
+                thread::park_timeout(cleanup_interval);
             }
         });
 
-        self.cleanup_thread = Some(cleanup_thread);
+        *cleanup_thread_slot = Some(cleanup_thread);
     }
 
     /// 获取缓存项
@@ -88,29 +242,29 @@ impl CacheManager {
             // 使用较小的作用域减少锁持有时间
             let invalid_map = self.invalid_signals.read().unwrap();
             if invalid_map.get(key).copied().unwrap_or(false) {
-                increment_counter!("cache_misses_total", "key" => key.to_string(), "reason" => "invalid");
+                increment_counter!("cache_misses_total", "namespace" => metric_namespace(key), "reason" => "invalid");
                 return None;
             }
         }
 
         // 获取缓存数据
-        let cache_map = self.cache_data.read().unwrap();
-        if let Some(item) = cache_map.get(key) {
+        let cache_map = self.shard(key).read().unwrap();
+        if let Some(slot) = cache_map.get(key) {
             // 尝试将Any转换为CacheItem<T>
-            if let Some(cache_item) = item.downcast_ref::<CacheItem<T>>() {
+            if let Some(cache_item) = slot.item.downcast_ref::<CacheItem<T>>() {
                 // 检查是否过期（快速检查，主要清理工作由后台线程完成）
                 if Instant::now() < cache_item.expiration {
                     // 记录缓存命中
-                    increment_counter!("cache_hits_total", "key" => key.to_string());
+                    increment_counter!("cache_hits_total", "namespace" => metric_namespace(key));
                     return Some(cache_item.data.clone());
                 } else {
                     // 记录缓存未命中 - 过期
-                    increment_counter!("cache_misses_total", "key" => key.to_string(), "reason" => "expired");
+                    increment_counter!("cache_misses_total", "namespace" => metric_namespace(key), "reason" => "expired");
                 }
             }
         } else {
             // 记录缓存未命中 - 未找到
-            increment_counter!("cache_misses_total", "key" => key.to_string(), "reason" => "not_found");
+            increment_counter!("cache_misses_total", "namespace" => metric_namespace(key), "reason" => "not_found");
         }
         None
     }
@@ -127,21 +281,54 @@ impl CacheManager {
             creation_time: now, // 记录创建时间
         };
 
-        // 写入缓存
-        let mut cache_map = self.cache_data.write().unwrap();
-        let is_new = !cache_map.contains_key(key);
-        cache_map.insert(key.to_string(), Box::new(cache_item));
+        // 写入缓存，只锁定键所在的分片
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let slot = Slot {
+            seq,
+            item: Box::new(cache_item),
+        };
+        if self
+            .shard(key)
+            .write()
+            .unwrap()
+            .insert(key.to_string(), slot)
+            .is_none()
+        {
+            self.item_count.fetch_add(1, Ordering::Relaxed);
+        }
+
+        // 命名空间超出容量时淘汰最早写入的键，被淘汰的键可能位于其他分片
+        let evicted = self.namespaces.lock().unwrap().record(key, seq);
+        self.evict(&evicted);
 
         // 记录缓存设置
-        increment_counter!("cache_sets_total", "key" => key.to_string());
+        increment_counter!("cache_sets_total", "namespace" => metric_namespace(key));
 
         // 更新缓存大小指标
-        gauge!("cache_size_items", cache_map.len() as f64);
+        gauge!("cache_size_items", self.len() as f64);
 
         // 设置缓存后自动重置失效状态
         self.reset(key);
     }
 
+    /// 删除因命名空间超出容量而被淘汰的键
+    ///
+    /// 写入与登记之间不持有分片锁，被淘汰的键可能已被其他线程重新写入，
+    /// 因此只在分片中仍是登记时的那次写入（序号相同）时才删除
+    fn evict(&self, evicted: &[(String, u64)]) {
+        for (key, seq) in evicted {
+            let mut shard = self.shard(key).write().unwrap();
+            if shard.get(key).is_some_and(|slot| slot.seq == *seq) {
+                shard.remove(key);
+                drop(shard);
+                self.record_removed(1);
+                if let Some(namespace) = key_namespace(key) {
+                    increment_counter!("cache_namespace_evictions_total", "namespace" => namespace.to_string());
+                }
+            }
+        }
+    }
+
     /// 设置指定缓存键的失效信号
     fn invalidate(&self, key: &str) {
         // 快速路径：直接在invalid_signals中标记为失效
@@ -151,25 +338,72 @@ impl CacheManager {
             .insert(key.to_string(), true);
 
         // 记录缓存失效
-        increment_counter!("cache_invalidations_total", "key" => key.to_string());
+        increment_counter!("cache_invalidations_total", "namespace" => metric_namespace(key));
 
         // 可选优化：同时从缓存中删除过期项，减少内存占用
         // 这里使用try_write来避免潜在的死锁
-        if let Ok(mut cache_map) = self.cache_data.try_write() {
-            cache_map.remove(key);
+        if let Ok(mut cache_map) = self.shard(key).try_write() {
+            let removed = cache_map.remove(key).is_some();
+            drop(cache_map);
+            self.record_removed(usize::from(removed));
+            self.namespaces.lock().unwrap().forget(key);
             // 更新缓存大小指标
-            gauge!("cache_size_items", cache_map.len() as f64);
+            gauge!("cache_size_items", self.len() as f64);
         }
     }
 
-    /// 检查指定缓存键是否已被标记为失效
-    fn is_invalid(&self, key: &str) -> bool {
-        self.invalid_signals
-            .read()
-            .unwrap()
-            .get(key)
-            .copied()
-            .unwrap_or(false)
+    /// 一次性失效一组缓存键和前缀
+    ///
+    /// 按下标顺序锁定全部分片后再删除，其他线程不会读到只失效了一部分的状态；
+    /// 其他路径同一时间最多持有一个分片的锁，因此不会死锁
+    fn invalidate_patterns(&self, patterns: &[String]) {
+        let (keys, prefixes) = split_patterns(patterns);
+
+        // 与 invalidate 一致，先标记精确键失效
+        {
+            let mut invalid_map = self.invalid_signals.write().unwrap();
+            for key in &keys {
+                invalid_map.insert(key.to_string(), true);
+            }
+        }
+
+        let mut removed = Vec::new();
+        {
+            let mut shards: Vec<_> = self
+                .shards
+                .iter()
+                .map(|shard| shard.write().unwrap())
+                .collect();
+            for key in &keys {
+                if shards[self.shard_index(key)].remove(*key).is_some() {
+                    removed.push(key.to_string());
+                }
+            }
+            if !prefixes.is_empty() {
+                for shard in shards.iter_mut() {
+                    shard.retain(|key, _| {
+                        let matched = prefixes.iter().any(|prefix| key.starts_with(prefix));
+                        if matched {
+                            removed.push(key.clone());
+                        }
+                        !matched
+                    });
+                }
+            }
+        }
+
+        self.record_removed(removed.len());
+        let mut namespaces = self.namespaces.lock().unwrap();
+        for key in &removed {
+            namespaces.forget(key);
+        }
+        drop(namespaces);
+
+        for pattern in patterns {
+            increment_counter!("cache_invalidations_total", "namespace" => metric_namespace(pattern));
+        }
+        // 更新缓存大小指标
+        gauge!("cache_size_items", self.len() as f64);
     }
 
     /// 重置指定缓存键的失效状态
@@ -177,6 +411,11 @@ impl CacheManager {
         self.invalid_signals.write().unwrap().remove(key);
     }
 
+    /// 设置各命名空间的最大键数
+    fn set_namespace_capacities(&self, capacities: HashMap<String, usize>) {
+        self.namespaces.lock().unwrap().capacities = capacities;
+    }
+
     /// 获取默认缓存持续时间
     fn get_default_duration(&self) -> Duration {
         *self.default_duration.read().unwrap()
@@ -196,13 +435,19 @@ impl CacheManager {
 
         // 2. 从缓存中删除这些键对应的项
         if !invalid_keys.is_empty() {
-            let mut cache_map = self.cache_data.write().unwrap();
+            let removed = invalid_keys
+                .iter()
+                .filter(|key| self.shard(key).write().unwrap().remove(*key).is_some())
+                .count();
+            self.record_removed(removed);
+            let mut namespaces = self.namespaces.lock().unwrap();
             for key in &invalid_keys {
-                cache_map.remove(key);
+                namespaces.forget(key);
             }
+            drop(namespaces);
 
             // 更新缓存大小指标
-            gauge!("cache_size_items", cache_map.len() as f64);
+            gauge!("cache_size_items", self.len() as f64);
 
             // 记录清理的项数
             increment_counter!("cache_cleanup_items", "count" => invalid_keys.len().to_string());
@@ -213,17 +458,142 @@ impl CacheManager {
     }
 
     /// 安全停止清理线程
-    fn stop_cleanup_thread(&mut self) {
+    fn stop_cleanup_thread(&self) {
         // 设置停止标志
         self.stop_flag.store(true, Ordering::Relaxed);
 
-        // 等待清理线程结束
-        if let Some(thread) = self.cleanup_thread.take() {
+        // 唤醒休眠中的线程并等待其结束
+        if let Some(thread) = self.cleanup_thread.lock().unwrap().take() {
+            thread.thread().unpark();
             thread.join().ok();
         }
     }
 }
 
+impl Cache for CacheManager {
+    fn get<T: CacheValue>(&self, key: &str) -> impl Future<Output = Option<T>> + Send {
+        std::future::ready(CacheManager::get(self, key))
+    }
+
+    fn set<T: CacheValue>(
+        &self,
+        key: &str,
+        data: T,
+        duration: Option<Duration>,
+    ) -> impl Future<Output = ()> + Send {
+        CacheManager::set(self, key, data, duration);
+        std::future::ready(())
+    }
+
+    fn invalidate(&self, key: &str) -> impl Future<Output = ()> + Send {
+        CacheManager::invalidate(self, key);
+        std::future::ready(())
+    }
+
+    fn invalidate_patterns(&self, patterns: &[String]) -> impl Future<Output = ()> + Send {
+        CacheManager::invalidate_patterns(self, patterns);
+        std::future::ready(())
+    }
+}
+
+/// 运行时选择的缓存后端
+enum CacheBackend {
+    /// 进程内缓存（默认）
+    Memory,
+    /// Redis 缓存
+    #[cfg(feature = "redis")]
+    Redis(super::redis_cache::RedisCache),
+}
+
+impl Cache for CacheBackend {
+    async fn get<T: CacheValue>(&self, key: &str) -> Option<T> {
+        match self {
+            Self::Memory => Cache::get(&**CACHE_MANAGER, key).await,
+            #[cfg(feature = "redis")]
+            Self::Redis(cache) => cache.get(key).await,
+        }
+    }
+
+    async fn set<T: CacheValue>(&self, key: &str, data: T, duration: Option<Duration>) {
+        match self {
+            Self::Memory => Cache::set(&**CACHE_MANAGER, key, data, duration).await,
+            #[cfg(feature = "redis")]
+            Self::Redis(cache) => cache.set(key, data, duration).await,
+        }
+    }
+
+    async fn invalidate(&self, key: &str) {
+        match self {
+            Self::Memory => Cache::invalidate(&**CACHE_MANAGER, key).await,
+            #[cfg(feature = "redis")]
+            Self::Redis(cache) => cache.invalidate(key).await,
+        }
+    }
+
+    async fn invalidate_patterns(&self, patterns: &[String]) {
+        match self {
+            Self::Memory => Cache::invalidate_patterns(&**CACHE_MANAGER, patterns).await,
+            #[cfg(feature = "redis")]
+            Self::Redis(cache) => cache.invalidate_patterns(patterns).await,
+        }
+    }
+}
+
+/// 未初始化时使用的默认后端
+static DEFAULT_BACKEND: CacheBackend = CacheBackend::Memory;
+
+/// 启动时选定的缓存后端
+static CACHE_BACKEND: OnceLock<CacheBackend> = OnceLock::new();
+
+fn backend() -> &'static CacheBackend {
+    CACHE_BACKEND.get().unwrap_or(&DEFAULT_BACKEND)
+}
+
+/// 实体到依赖缓存的映射（`cache.dependencies` 合并内置依赖）
+static DEPENDENCIES: OnceLock<HashMap<String, Vec<String>>> = OnceLock::new();
+
+/// 生效的依赖映射，未调用 [`init_cache_backend`] 时按全局配置初始化
+fn dependencies() -> &'static HashMap<String, Vec<String>> {
+    DEPENDENCIES.get_or_init(|| CONFIG.cache.effective_dependencies())
+}
+
+/// 根据配置初始化缓存后端，应在处理请求前调用一次
+///
+/// 未调用时使用进程内缓存
+pub fn init_cache_backend(config: &CacheConfig) -> Result<(), String> {
+    CACHE_MANAGER.set_namespace_capacities(config.namespace_capacity.clone());
+    DEPENDENCIES
+        .set(config.effective_dependencies())
+        .map_err(|_| "缓存依赖已初始化".to_string())?;
+
+    let backend = match config.backend.as_str() {
+        #[cfg(feature = "redis")]
+        "redis" => {
+            let url = config
+                .redis_url
+                .as_deref()
+                .ok_or_else(|| "cache.backend 为 redis 时必须配置 cache.redis_url".to_string())?;
+            let cache = super::redis_cache::RedisCache::new(
+                url,
+                &config.key_prefix,
+                CACHE_MANAGER.get_default_duration(),
+            )
+            .map_err(|e| format!("创建 Redis 连接池失败: {}", e))?;
+            tracing::info!("🗄️  缓存后端: Redis");
+            CacheBackend::Redis(cache)
+        }
+        #[cfg(not(feature = "redis"))]
+        "redis" => {
+            return Err("cache.backend 为 redis，但编译时未启用 redis feature".to_string());
+        }
+        _ => CacheBackend::Memory,
+    };
+
+    CACHE_BACKEND
+        .set(backend)
+        .map_err(|_| "缓存后端已初始化".to_string())
+}
+
 // 辅助函数：尝试获取缓存管理器实例
 // 这个函数在清理线程中使用，避免直接引用CACHE_MANAGER导致的初始化顺序问题
 fn try_get_cache_manager() -> Result<Arc<CacheManager>, ()> {
@@ -236,7 +606,7 @@ fn try_get_cache_manager() -> Result<Arc<CacheManager>, ()> {
 lazy_static::lazy_static! {
     static ref CACHE_MANAGER: Arc<CacheManager> = {
         // 创建一个临时的CacheManager实例用于初始化
-        let mut temp_manager = CacheManager::new();
+        let temp_manager = CacheManager::new(CONFIG.cache.shards);
 
         // 启动清理线程
         temp_manager.start_cleanup_thread();
@@ -246,21 +616,24 @@ lazy_static::lazy_static! {
     };
 }
 
-/// 使指定缓存键失效
+/// 使实体的所有依赖缓存失效
 ///
-/// # 参数
-/// - `key`: 缓存键，由调用方提供的字符串标识
+/// 依赖由 `cache.dependencies` 配置，如 `todos` 对应列表缓存和 `search:todos:*` 搜索缓存，
+/// 数据变更后调用一次即可，无需逐个失效。未配置依赖的实体按同名缓存键失效
 ///
 /// # 示例
 /// ```
-/// // 使待办事项缓存失效
-/// invalidate_cache("todos");
-///
-/// // 使用户缓存失效
-/// invalidate_cache("users");
+/// // 待办事项变更后，同时失效列表和搜索缓存
+/// invalidate_entity("todos").await;
 /// ```
-pub fn invalidate_cache(key: &str) {
-    CACHE_MANAGER.invalidate(key);
+pub async fn invalidate_entity(entity: &str) {
+    match dependencies().get(entity) {
+        Some(patterns) => backend().invalidate_patterns(patterns).await,
+        None => {
+            tracing::debug!("缓存实体 {} 未配置依赖，按同名缓存键失效", entity);
+            backend().invalidate(entity).await;
+        }
+    }
 }
 
 /// 从缓存获取数据
@@ -271,14 +644,17 @@ pub fn invalidate_cache(key: &str) {
 /// # 返回值
 /// - 如果缓存存在且未过期且未被标记为失效，则返回缓存的克隆，否则返回 `None`
 ///
+/// 数据类型需满足 [`CacheValue`]（`Clone + Serialize + DeserializeOwned`），
+/// 以便在 Redis 后端中以 JSON 存储
+///
 /// # 示例
 /// ```
-/// if let Some(todos) = get_from_cache("todos") {
+/// if let Some(todos) = get_from_cache("todos").await {
 ///     // 使用缓存的待办事项
 /// }
 /// ```
-pub fn get_from_cache<T: Clone + 'static>(key: &str) -> Option<T> {
-    CACHE_MANAGER.get(key)
+pub async fn get_from_cache<T: CacheValue>(key: &str) -> Option<T> {
+    backend().get(key).await
 }
 
 /// 向缓存中设置数据
@@ -291,12 +667,204 @@ pub fn get_from_cache<T: Clone + 'static>(key: &str) -> Option<T> {
 /// # 示例
 /// ```
 /// // 使用默认缓存时间缓存待办事项
-/// set_to_cache("todos", todo_list, None);
+/// set_to_cache("todos", todo_list, None).await;
 ///
 /// // 使用自定义缓存时间（30秒）
 /// use std::time::Duration;
-/// set_to_cache("todos", todo_list, Some(Duration::from_secs(30)));
+/// set_to_cache("todos", todo_list, Some(Duration::from_secs(30))).await;
 /// ```
-pub fn set_to_cache<T: 'static + Send + Sync>(key: &str, data: T, duration: Option<Duration>) {
-    CACHE_MANAGER.set(key, data, duration);
+pub async fn set_to_cache<T: CacheValue>(key: &str, data: T, duration: Option<Duration>) {
+    backend().set(key, data, duration).await;
+}
+
+/// 停止缓存后台清理线程
+///
+/// 由关闭协调器在应用关闭时调用，会阻塞直到清理线程退出
+pub fn stop_cache_cleanup() {
+    CACHE_MANAGER.stop_cleanup_thread();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 写入后失效实体，断言列表和搜索缓存一起被清除
+    async fn assert_entity_clears(entity: &str, keys: &[&str]) {
+        for key in keys {
+            set_to_cache(key, vec![1_i64], None).await;
+            assert_eq!(get_from_cache::<Vec<i64>>(key).await, Some(vec![1]));
+        }
+
+        invalidate_entity(entity).await;
+
+        for key in keys {
+            assert_eq!(get_from_cache::<Vec<i64>>(key).await, None, "{}", key);
+        }
+    }
+
+    #[test]
+    fn metric_namespace_drops_dynamic_key_parts() {
+        assert_eq!(metric_namespace("search:users:alice"), "search");
+        assert_eq!(metric_namespace("initial_users:12"), "initial_users");
+        assert_eq!(metric_namespace("search:todos:*"), "search");
+        assert_eq!(metric_namespace("todos"), "todos");
+    }
+
+    #[test]
+    fn item_count_tracks_inserts_and_removals() {
+        let manager = CacheManager::new(4);
+        manager.set("todos", 1_i64, None);
+        manager.set("users", 2_i64, None);
+        manager.set("users", 3_i64, None);
+        assert_eq!(manager.len(), 2);
+
+        manager.invalidate("todos");
+        assert_eq!(manager.len(), 1);
+
+        manager.set("search:users:a", 1_i64, None);
+        manager.set("search:users:b", 1_i64, None);
+        manager.invalidate_patterns(&["users".to_string(), "search:users:*".to_string()]);
+        assert_eq!(manager.len(), 0);
+    }
+
+    #[test]
+    fn namespace_eviction_skips_rewritten_key() {
+        let manager = CacheManager::new(4);
+        manager.set_namespace_capacities(HashMap::from([("search".to_string(), 1)]));
+
+        manager.set("search:a", 1_i64, None);
+        manager.set("search:b", 2_i64, None);
+        assert_eq!(manager.get::<i64>("search:a"), None);
+        assert_eq!(manager.len(), 1);
+
+        // 模拟并发：`search:c` 的写入决定淘汰 `search:b` 后、删除之前，`search:b` 被重新写入
+        let evicted = manager
+            .namespaces
+            .lock()
+            .unwrap()
+            .record("search:c", u64::MAX);
+        manager.set("search:b", 3_i64, None);
+        manager.evict(&evicted);
+        assert_eq!(manager.get::<i64>("search:b"), Some(3));
+        assert_eq!(manager.len(), 1);
+    }
+
+    #[test]
+    fn default_namespace_capacity_evicts_oldest_initial_users() {
+        use crate::routes::pages::initial_users_cache_key;
+
+        let capacity = CacheConfig::default().namespace_capacity["initial_users"];
+        let manager = CacheManager::new(4);
+        manager.set_namespace_capacities(CacheConfig::default().namespace_capacity);
+        manager.set("users", 0_i64, None);
+
+        // 比容量多写入一个数量，最早的一个被淘汰，其他命名空间不受影响
+        for count in 1..=capacity as u32 + 1 {
+            manager.set(&initial_users_cache_key(count), i64::from(count), None);
+        }
+        assert_eq!(manager.get::<i64>(&initial_users_cache_key(1)), None);
+        assert_eq!(manager.get::<i64>(&initial_users_cache_key(2)), Some(2));
+        assert_eq!(manager.get::<i64>("users"), Some(0));
+        assert_eq!(manager.len(), capacity + 1);
+    }
+
+    #[test]
+    fn search_flood_evicts_only_search_keys() {
+        fn search_evictions(rendered: &str) -> u64 {
+            rendered
+                .lines()
+                .find(|line| {
+                    line.starts_with("cache_namespace_evictions_total{")
+                        && line.contains("namespace=\"search\"")
+                })
+                .and_then(|line| line.rsplit(' ').next())
+                .map_or(0, |count| count.parse().unwrap())
+        }
+
+        // 记录器为全局共享，其他测试也可能淘汰 search 键，只断言增量下限
+        let handle = crate::helpers::monitoring::test_metrics_handle();
+        let before = search_evictions(&handle.render());
+
+        let capacity = CacheConfig::default().namespace_capacity["search"];
+        let overflow = 5;
+        let manager = CacheManager::new(4);
+        manager.set_namespace_capacities(CacheConfig::default().namespace_capacity);
+        manager.set("todos", 1_i64, None);
+        manager.set("users", 2_i64, None);
+
+        for i in 0..capacity + overflow {
+            manager.set(&format!("search:todos:flood-{}", i), i as i64, None);
+        }
+
+        assert_eq!(manager.get::<i64>("todos"), Some(1));
+        assert_eq!(manager.get::<i64>("users"), Some(2));
+        for i in 0..overflow {
+            assert_eq!(
+                manager.get::<i64>(&format!("search:todos:flood-{}", i)),
+                None
+            );
+        }
+        assert_eq!(
+            manager.get::<i64>(&format!("search:todos:flood-{}", overflow)),
+            Some(overflow as i64)
+        );
+        assert_eq!(manager.len(), capacity + 2);
+        assert!(search_evictions(&handle.render()) >= before + overflow as u64);
+    }
+
+    /// 多线程并发读写，返回总耗时
+    fn concurrent_set_get(manager: &CacheManager, threads: usize, ops: usize) -> Duration {
+        let keys: Vec<String> = (0..1024).map(|i| format!("bench:{}", i)).collect();
+        let start = Instant::now();
+        thread::scope(|scope| {
+            for t in 0..threads {
+                let keys = &keys;
+                scope.spawn(move || {
+                    for i in 0..ops {
+                        let key = &keys[(i * 31 + t * 7) % keys.len()];
+                        // 读多写少：每 4 次读取 1 次写入
+                        if i % 5 == 0 {
+                            manager.set(key, i as i64, None);
+                        } else {
+                            std::hint::black_box(manager.get::<i64>(key));
+                        }
+                    }
+                });
+            }
+        });
+        start.elapsed()
+    }
+
+    /// 单分片与默认分片数在并发读写下的耗时对比
+    ///
+    /// 计时结果与机器相关，不作断言，手动运行查看：
+    /// `cargo test --release shard_benchmark -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn shard_benchmark_concurrent_set_get() {
+        let threads = thread::available_parallelism()
+            .map_or(4, |n| n.get())
+            .max(4);
+        let ops = 200_000;
+        for shards in [1, CacheConfig::default().shards] {
+            let manager = CacheManager::new(shards);
+            // 预热，让每个键都已存在
+            concurrent_set_get(&manager, 1, 1024 * 5);
+            let elapsed = concurrent_set_get(&manager, threads, ops);
+            println!(
+                "shards={:>3} threads={} ops={} elapsed={:?} ({:.0} ops/s)",
+                shards,
+                threads,
+                threads * ops,
+                elapsed,
+                (threads * ops) as f64 / elapsed.as_secs_f64()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn invalidate_entity_clears_dependent_caches() {
+        // 待办的失效由 todos::create 的测试经处理器覆盖
+        assert_entity_clears("users", &["users", "initial_users:7", "search:users:alice"]).await;
+    }
 }
@@ -0,0 +1,133 @@
+//! 响应压缩
+//!
+//! 公开路由的响应按 `Accept-Encoding` 使用 br 压缩。小响应（多数 HTMX 片段）压缩收益抵不过
+//! CPU 开销，已压缩的格式（图片、字体等）再压缩也不会变小，这两类由 [`CompressionPredicate`] 跳过
+
+use axum::body::HttpBody;
+use axum::http::{header, Response};
+use std::sync::Arc;
+use tower_http::compression::predicate::Predicate;
+
+use crate::helpers::config::CompressionConfig;
+
+/// 决定响应是否压缩
+#[derive(Debug, Clone)]
+pub struct CompressionPredicate {
+    enabled: bool,
+    min_size_bytes: u64,
+    skip_content_types: Arc<[String]>,
+}
+
+impl CompressionPredicate {
+    pub fn from_config(config: &CompressionConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            min_size_bytes: config.min_size_bytes,
+            skip_content_types: config.skip_content_types.clone().into(),
+        }
+    }
+}
+
+impl Predicate for CompressionPredicate {
+    fn should_compress<B>(&self, response: &Response<B>) -> bool
+    where
+        B: HttpBody,
+    {
+        if !self.enabled {
+            return false;
+        }
+
+        // 大小未知（流式响应）时压缩，与 tower-http 的 `SizeAbove` 一致
+        let size = response.body().size_hint().exact().or_else(|| {
+            response
+                .headers()
+                .get(header::CONTENT_LENGTH)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok())
+        });
+        if size.is_some_and(|size| size < self.min_size_bytes) {
+            return false;
+        }
+
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        !self
+            .skip_content_types
+            .iter()
+            .any(|skipped| content_type.starts_with(skipped.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        extract::Request,
+        response::{Html, IntoResponse},
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+    use tower_http::compression::CompressionLayer;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/fragment", get(|| async { Html("<li>待办</li>") }))
+            .route(
+                "/page",
+                get(|| async { Html(format!("<ul>{}</ul>", "<li>待办</li>".repeat(200))) }),
+            )
+            .route(
+                "/image",
+                get(|| async {
+                    ([(header::CONTENT_TYPE, "image/png")], vec![0u8; 4096]).into_response()
+                }),
+            )
+            .layer(
+                CompressionLayer::new().compress_when(CompressionPredicate::from_config(
+                    &CompressionConfig::default(),
+                )),
+            )
+    }
+
+    async fn content_encoding(path: &str) -> Option<String> {
+        let response = app()
+            .oneshot(
+                Request::get(path)
+                    .header(header::ACCEPT_ENCODING, "br")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        response
+            .headers()
+            .get(header::CONTENT_ENCODING)
+            .map(|value| value.to_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn compresses_large_html_but_not_small_fragments() {
+        assert_eq!(content_encoding("/fragment").await, None);
+        assert_eq!(content_encoding("/page").await.as_deref(), Some("br"));
+    }
+
+    #[tokio::test]
+    async fn skips_already_compressed_types() {
+        assert_eq!(content_encoding("/image").await, None);
+    }
+
+    #[test]
+    fn disabled_never_compresses() {
+        let predicate = CompressionPredicate::from_config(&CompressionConfig {
+            enabled: false,
+            ..CompressionConfig::default()
+        });
+        let response = Html("x".repeat(4096)).into_response();
+        assert!(!predicate.should_compress(&response));
+    }
+}
@@ -6,10 +6,17 @@ use figment::{
     providers::{Env, Format, Toml},
     Error as FigmentError, Figment,
 };
-use serde::Deserialize;
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
 use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
 use thiserror::Error;
 
+use crate::helpers::security::{sanitize_log_message, DEFAULT_FAST_PATH_MAX_LENGTH};
+
 /// 配置加载错误类型
 #[derive(Error, Debug)]
 pub enum ConfigError {
@@ -19,8 +26,23 @@ pub enum ConfigError {
     Validation(String),
 }
 
+impl ConfigError {
+    /// 针对错误类型的修正建议，启动失败时与错误一起输出
+    pub fn guidance(&self) -> &'static str {
+        match self {
+            Self::Loading(_) => {
+                "请检查 config.toml 的 TOML 语法，以及 APP_ 前缀环境变量（如 APP_SERVER.PORT）的取值类型是否与配置项一致"
+            }
+            Self::Validation(_) => {
+                "配置文件和环境变量都已读取，但取值不合法，请按上面的说明修改对应配置项（环境变量优先于 config.toml）"
+            }
+        }
+    }
+}
+
 /// 数据库配置
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct DatabaseConfig {
     #[allow(dead_code)]
     pub url: Option<String>,
@@ -30,6 +52,41 @@ pub struct DatabaseConfig {
     pub acquire_timeout_seconds: u64,
     #[allow(dead_code)]
     pub idle_timeout_seconds: u64,
+    /// SQLite busy_timeout（秒），写锁冲突时的等待时间
+    pub busy_timeout_seconds: u64,
+    /// SQLite synchronous 模式：OFF、NORMAL、FULL 或 EXTRA
+    pub synchronous: String,
+    /// SQLite temp_store：DEFAULT、FILE 或 MEMORY
+    pub temp_store: String,
+    /// SQLite cache_size，负数表示 KiB，正数表示页数
+    pub cache_size: i64,
+    /// 启动时是否插入示例数据，未设置时仅开发环境插入
+    pub seed_on_startup: Option<bool>,
+    /// 单个请求最多写入的行数，避免长写事务阻塞 SQLite 唯一的写连接
+    pub max_batch_rows: usize,
+    /// 取出连接前是否先 ping 检测，失效的连接会被丢弃并自动重建
+    pub test_before_acquire: bool,
+    /// 是否在响应头 `X-DB-Queries` 中返回本次请求的查询次数，未设置时仅开发环境启用
+    pub query_count_header: Option<bool>,
+    /// 空闲连接保活间隔（秒），定期 ping 以免被服务端空闲超时断开；未设置时不启动（SQLite 无需保活）。
+    /// 配置了 `read_url` 时只读副本连接池同样保活
+    pub keepalive_interval_seconds: Option<u64>,
+    /// 是否使用 `INSERT/UPDATE ... RETURNING`，未设置时按 SQLite 版本自动检测（3.35.0 起支持）
+    pub use_returning: Option<bool>,
+    /// 只读副本的连接地址，设置后读多写少的查询走该连接池；未设置时读写共用主库连接池
+    pub read_url: Option<String>,
+    /// 启动时建立连接池失败后的最大重试次数，0 表示不重试
+    pub connect_retries: u32,
+    /// 首次重试前的等待时间（毫秒），之后每次翻倍，单次最长 10 秒
+    pub connect_retry_backoff_ms: u64,
+    /// 启动时等待数据库可用的总时长上限（秒），超出后不再重试
+    pub connect_max_wait_seconds: u64,
+    /// sqlx 记录每条语句的日志级别：off、error、warn、info 或 debug
+    pub log_statements: String,
+    /// 执行时间超过该毫秒数的语句以 warn 级别记录，0 表示不单独记录慢语句
+    pub log_slow_statements_threshold_ms: u64,
+    /// 其他实例正在迁移时等待其完成的总时长上限（秒），0 表示只等待一次 busy_timeout
+    pub migration_lock_wait_seconds: u64,
 }
 
 impl Default for DatabaseConfig {
@@ -40,18 +97,51 @@ impl Default for DatabaseConfig {
             min_connections: 2,
             acquire_timeout_seconds: 5,
             idle_timeout_seconds: 300,
+            busy_timeout_seconds: 10,
+            synchronous: "NORMAL".to_string(),
+            temp_store: "MEMORY".to_string(),
+            cache_size: -65536,
+            seed_on_startup: None,
+            max_batch_rows: 500,
+            test_before_acquire: true,
+            query_count_header: None,
+            keepalive_interval_seconds: None,
+            use_returning: None,
+            read_url: None,
+            connect_retries: 5,
+            connect_retry_backoff_ms: 500,
+            connect_max_wait_seconds: 30,
+            log_statements: "debug".to_string(),
+            log_slow_statements_threshold_ms: 1000,
+            migration_lock_wait_seconds: 120,
         }
     }
 }
 
 /// 服务器配置
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
     #[allow(dead_code)]
     pub worker_threads: Option<usize>,
     pub graceful_shutdown_timeout_seconds: u64,
+    /// 全局请求处理超时（秒），超时返回 503，0 表示不限时；个别路由可单独覆盖
+    pub request_timeout_seconds: u64,
+    /// 批量导入路由（`/api/todos/import`、`/api/users/import`）的超时（秒），覆盖全局值，0 表示不限时
+    pub import_timeout_seconds: u64,
+    /// Unix domain socket 路径，设置后替代 TCP 监听（仅 Unix 平台）
+    pub unix_socket: Option<String>,
+    /// 部署在反向代理子路径下时的路径前缀（如 `/app-name`），默认为空即根路径
+    pub base_path: String,
+    /// `/health/ready` 是否等待缓存预热完成后才报告就绪
+    pub readiness_requires_warmup: bool,
+    /// 不允许搜索引擎索引的路由前缀，响应会带上 `X-Robots-Tag: noindex`（不含 `base_path`）
+    pub noindex_prefixes: Vec<String>,
+    /// 对外访问的根地址（如 `https://example.com/app-name`），用于生成绝对链接；
+    /// 未设置时由监听地址和 `base_path` 推导（见 [`AppConfig::public_base_url`]）
+    pub public_base_url: Option<String>,
 }
 
 impl Default for ServerConfig {
@@ -61,6 +151,18 @@ impl Default for ServerConfig {
             port: 3000,
             worker_threads: None,
             graceful_shutdown_timeout_seconds: 5,
+            request_timeout_seconds: 30,
+            import_timeout_seconds: 120,
+            unix_socket: None,
+            base_path: String::new(),
+            readiness_requires_warmup: true,
+            noindex_prefixes: vec![
+                "/app".to_string(),
+                "/block".to_string(),
+                "/api".to_string(),
+                "/admin".to_string(),
+            ],
+            public_base_url: None,
         }
     }
 }
@@ -70,39 +172,504 @@ impl ServerConfig {
     pub fn server_addr(&self) -> String {
         format!("{}:{}", self.host, self.port)
     }
+
+    /// 全局请求超时，`None` 表示不限时
+    pub fn request_timeout(&self) -> Option<Duration> {
+        (self.request_timeout_seconds > 0)
+            .then(|| Duration::from_secs(self.request_timeout_seconds))
+    }
+
+    /// 批量导入路由的超时，`None` 表示不限时
+    pub fn import_timeout(&self) -> Option<Duration> {
+        (self.import_timeout_seconds > 0).then(|| Duration::from_secs(self.import_timeout_seconds))
+    }
+}
+
+/// TLS 配置
+///
+/// 配置后直接以 HTTPS 提供服务，适用于前面没有反向代理的部署
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TlsConfig {
+    /// PEM 格式的证书链文件
+    pub cert_path: String,
+    /// PEM 格式的私钥文件
+    pub key_path: String,
 }
 
 /// 安全配置
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct SecurityConfig {
+    /// 额外允许的 CORS 来源，与当前环境的默认来源合并（见 [`AppConfig::cors_policy`]）
     pub cors_allow_origins: Vec<String>,
-    #[allow(dead_code)]
+    /// 预检结果的缓存时间（秒，`Access-Control-Max-Age`），0 表示不发送，由浏览器使用默认值
+    pub cors_max_age_seconds: u64,
+    /// 每个客户端每分钟允许的写操作请求数，0 表示不限流
     pub rate_limit_per_minute: u64,
+    /// 预留开关：当前没有 CSRF 中间件，该配置不生效（见 README「CSRF」一节）
     #[allow(dead_code)]
     pub enable_csrf: bool,
+    /// 请求 URI（路径 + 查询串）最大长度，超出返回 414
+    pub max_uri_length: usize,
+    /// 查询串最大长度，超出返回 414
+    pub max_query_length: usize,
+    /// 请求头总字节数（名称 + 值）上限，超出返回 431
+    pub max_header_bytes: usize,
+    /// 请求头数量上限，超出返回 431
+    pub max_header_count: usize,
+    /// 是否记录写操作审计日志
+    pub audit_log_enabled: bool,
+    /// 管理接口的 Bearer 令牌，未设置时管理接口一律返回 401
+    pub admin_token: Option<String>,
+    /// 允许访问 `/metrics` 和 `/admin` 的客户端网段（CIDR 或单个 IP），默认只允许本机
+    ///
+    /// 包含私有网段时必须同时配置 `trusted_proxies`，否则反向代理转发的外部请求
+    /// 会被识别为代理的私有地址而放行
+    pub internal_allow_list: Vec<String>,
+    /// 受信任的反向代理网段，来自这些地址的请求按 `X-Forwarded-For` 识别真实客户端
+    pub trusted_proxies: Vec<String>,
+    /// `Strict-Transport-Security`，仅在启用内置 TLS 时发送
+    pub hsts: HstsConfig,
+    /// 日志脱敏快速路径的长度上限（字节）：不超过该长度且不含敏感标记的消息直接跳过规则，
+    /// 更长的消息总是执行完整规则；0 表示所有消息都执行完整规则
+    pub log_sanitizer_fast_path_max_length: usize,
 }
 
 impl Default for SecurityConfig {
     fn default() -> Self {
         Self {
-            cors_allow_origins: vec![
-                "http://localhost:3000".to_string(),
-                "http://127.0.0.1:3000".to_string(),
-            ],
+            cors_allow_origins: Vec::new(),
+            cors_max_age_seconds: 600,
             rate_limit_per_minute: 60,
             enable_csrf: true,
+            max_uri_length: 4096,
+            max_query_length: 2048,
+            max_header_bytes: 32 * 1024,
+            max_header_count: 64,
+            audit_log_enabled: true,
+            admin_token: None,
+            internal_allow_list: vec!["127.0.0.0/8".to_string(), "::1/128".to_string()],
+            trusted_proxies: Vec::new(),
+            hsts: HstsConfig::default(),
+            log_sanitizer_fast_path_max_length: DEFAULT_FAST_PATH_MAX_LENGTH,
+        }
+    }
+}
+
+impl SecurityConfig {
+    /// 解析后的内部路由允许网段（无效项已在配置验证中拒绝）
+    pub fn internal_allow_networks(&self) -> Vec<IpNet> {
+        self.internal_allow_list
+            .iter()
+            .filter_map(|s| parse_network(s))
+            .collect()
+    }
+
+    /// 解析后的受信任代理网段
+    pub fn trusted_proxy_networks(&self) -> Vec<IpNet> {
+        self.trusted_proxies
+            .iter()
+            .filter_map(|s| parse_network(s))
+            .collect()
+    }
+}
+
+/// 私有网段（RFC 1918 和 IPv6 ULA），反向代理通常位于其中
+const PRIVATE_NETWORKS: [&str; 4] = ["10.0.0.0/8", "172.16.0.0/12", "192.168.0.0/16", "fc00::/7"];
+
+/// 网段是否与私有网段重叠
+fn overlaps_private_network(net: &IpNet) -> bool {
+    PRIVATE_NETWORKS
+        .iter()
+        .filter_map(|s| s.parse::<IpNet>().ok())
+        .any(|private| private.contains(net) || net.contains(&private))
+}
+
+/// 解析 CIDR 网段，单个 IP 视为只包含该地址的网段
+fn parse_network(s: &str) -> Option<IpNet> {
+    let s = s.trim();
+    s.parse::<IpNet>()
+        .ok()
+        .or_else(|| s.parse::<IpAddr>().ok().map(IpNet::from))
+}
+
+/// HSTS 配置
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct HstsConfig {
+    /// 启用 TLS 时是否发送 `Strict-Transport-Security`
+    pub enabled: bool,
+    /// 浏览器强制使用 HTTPS 的时长（秒）
+    pub max_age_seconds: u64,
+    /// 是否同时作用于所有子域名
+    pub include_subdomains: bool,
+    /// 是否声明加入浏览器 HSTS 预加载列表
+    pub preload: bool,
+}
+
+impl Default for HstsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_age_seconds: 31_536_000,
+            include_subdomains: false,
+            preload: false,
+        }
+    }
+}
+
+/// 静态资源配置
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct StaticAssetsConfig {
+    /// 是否使用弱 ETag（`W/"..."`），默认强 ETag
+    pub etag_weak: bool,
+    /// ETag 哈希算法：`sha256`（编译期计算，零开销）或 `siphash`
+    pub etag_algorithm: String,
+    /// 是否提供 `/favicon.ico`，关闭时返回 204
+    pub favicon_enabled: bool,
+    /// `/favicon.ico` 对应的静态资源路径（相对 `static/`），可替换为自定义图标
+    pub favicon_path: String,
+}
+
+impl Default for StaticAssetsConfig {
+    fn default() -> Self {
+        Self {
+            etag_weak: false,
+            etag_algorithm: "sha256".to_string(),
+            favicon_enabled: true,
+            favicon_path: "favicon.ico".to_string(),
+        }
+    }
+}
+
+/// 响应压缩配置（br）
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct CompressionConfig {
+    /// 是否压缩公开路由的响应
+    pub enabled: bool,
+    /// 小于该字节数的响应不压缩，大小未知（流式响应）时总是压缩
+    pub min_size_bytes: u64,
+    /// 不压缩的内容类型前缀（已压缩的图片、字体等）
+    pub skip_content_types: Vec<String>,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_size_bytes: 1024,
+            skip_content_types: [
+                "image/png",
+                "image/jpeg",
+                "image/gif",
+                "image/webp",
+                "image/avif",
+                "font/woff",
+                "application/zip",
+                "application/gzip",
+                "audio/",
+                "video/",
+                "text/event-stream",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        }
+    }
+}
+
+/// 监控指标配置
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct MetricsConfig {
+    /// Prometheus 导出器的独立端口，未设置时不启动导出器
+    pub port: Option<u16>,
+    /// 导出器监听地址，默认只监听本机
+    ///
+    /// 导出器不经过 `internal_allow_list`，改为 `0.0.0.0` 时须由防火墙或网络策略限制访问
+    pub host: IpAddr,
+    /// 是否在应用端口上提供 `/metrics`
+    pub app_route_enabled: bool,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            port: None,
+            host: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            app_route_enabled: true,
+        }
+    }
+}
+
+impl MetricsConfig {
+    /// 指标是否对外暴露（独立导出器或应用内 `/metrics`）
+    pub fn is_exposed(&self) -> bool {
+        self.port.is_some() || self.app_route_enabled
+    }
+}
+
+/// 进程内缓存分片数的上限
+pub const MAX_CACHE_SHARDS: usize = 256;
+
+/// 缓存配置
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// 缓存后端：`memory`（进程内）或 `redis`（需启用 `redis` feature）
+    pub backend: String,
+    /// Redis 连接地址，如 `redis://127.0.0.1:6379`
+    pub redis_url: Option<String>,
+    /// Redis 键前缀，多个应用共用一个 Redis 时避免冲突
+    pub key_prefix: String,
+    /// 各命名空间（缓存键第一个 `:` 之前的部分）的最大键数，超出时淘汰最早写入的键（仅进程内缓存）。
+    /// `search` 为按搜索词缓存结果预留，目前还没有处理器写入 `search:*` 键
+    pub namespace_capacity: HashMap<String, usize>,
+    /// 进程内缓存的分片数，按键的哈希分配到各分片，写入只锁定所在分片（1 到 `MAX_CACHE_SHARDS`）
+    pub shards: usize,
+    /// 实体到依赖缓存的映射，`invalidate_entity` 按此一次性失效所有相关缓存
+    ///
+    /// 每项为缓存键，或以 `*` 结尾的前缀（如 `search:todos:*`）。配置的项追加到内置依赖上
+    /// （见 [`CacheConfig::effective_dependencies`]），不会替换掉 `todos`、`users` 的内置项
+    pub dependencies: HashMap<String, Vec<String>>,
+}
+
+impl CacheConfig {
+    /// 内置依赖与配置的依赖合并后的结果
+    ///
+    /// 配置文件中的 `[cache.dependencies]` 表会整体替换默认值，这里重新补上内置项：
+    /// 同一实体的配置项追加在内置项之后（去重），新增的实体原样加入
+    pub fn effective_dependencies(&self) -> HashMap<String, Vec<String>> {
+        let mut dependencies = Self::default().dependencies;
+        for (entity, patterns) in &self.dependencies {
+            let merged = dependencies.entry(entity.clone()).or_default();
+            for pattern in patterns {
+                if !merged.contains(pattern) {
+                    merged.push(pattern.clone());
+                }
+            }
+        }
+        dependencies
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            backend: "memory".to_string(),
+            redis_url: None,
+            key_prefix: "htmx-rs:".to_string(),
+            namespace_capacity: HashMap::from([
+                ("initial_users".to_string(), 4),
+                ("search".to_string(), 1000),
+            ]),
+            shards: 16,
+            dependencies: HashMap::from([
+                (
+                    "todos".to_string(),
+                    vec!["todos".to_string(), "search:todos:*".to_string()],
+                ),
+                (
+                    "users".to_string(),
+                    vec![
+                        "users".to_string(),
+                        "initial_users:*".to_string(),
+                        "search:users:*".to_string(),
+                    ],
+                ),
+            ]),
+        }
+    }
+}
+
+/// 用户头像配置
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct AvatarConfig {
+    /// 是否使用 Gravatar，关闭时渲染服务端生成的首字母 SVG（不产生外部请求）
+    pub gravatar_enabled: bool,
+    /// Gravatar 未找到头像时的默认样式（`d` 参数）
+    pub gravatar_default: String,
+}
+
+impl Default for AvatarConfig {
+    fn default() -> Self {
+        Self {
+            gravatar_enabled: false,
+            gravatar_default: "identicon".to_string(),
+        }
+    }
+}
+
+/// 请求 ID 配置
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct RequestIdConfig {
+    /// 读取和返回请求 ID 的头部名称，如 `x-request-id` 或 `traceparent`
+    pub header: String,
+    /// 生成格式：`uuid`（UUID v4）、`base62`（22 位）或 `traceparent`（W3C Trace Context）
+    pub format: String,
+}
+
+impl Default for RequestIdConfig {
+    fn default() -> Self {
+        Self {
+            header: "x-request-id".to_string(),
+            format: "uuid".to_string(),
+        }
+    }
+}
+
+/// 界面配置
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct UiConfig {
+    /// 用户页面首屏加载的用户数（1–100），预热缓存使用同一数量
+    pub initial_users: u32,
+    /// 实时搜索输入框的防抖延迟（毫秒），停止输入这么久后才发出请求，0 表示每次输入都请求
+    pub search_debounce_ms: u64,
+    /// 用户搜索单个响应最多渲染的行数，独立于 `per_page` 上限，超出时截断并提示缩小搜索范围
+    pub max_rendered_rows: u32,
+    /// 时间戳的默认显示时区（IANA 时区名，如 `Asia/Shanghai`），请求的 `tz` Cookie 可覆盖
+    pub timezone: String,
+    /// 首页是否显示待办统计（总数、已完成、待完成）
+    pub home_todo_stats: bool,
+    /// 用户列表的默认排序（`字段:方向`，字段为 id、name、email，方向为 asc、desc），
+    /// 首屏列表和未指定 `sort` 的搜索使用
+    pub default_user_sort: String,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            initial_users: 12,
+            search_debounce_ms: 300,
+            max_rendered_rows: 100,
+            timezone: "UTC".to_string(),
+            home_todo_stats: false,
+            default_user_sort: "id:asc".to_string(),
+        }
+    }
+}
+
+/// HTMX 前端默认配置，渲染为 `<meta name="htmx-config">`
+///
+/// 未设置的项不输出，沿用 HTMX 自身的默认值
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct HtmxConfig {
+    /// 默认交换方式（`defaultSwapStyle`），如 `innerHTML`、`outerHTML`
+    pub default_swap_style: Option<String>,
+    /// 是否启用历史记录快照（`historyEnabled`）
+    pub history_enabled: Option<bool>,
+    /// 历史记录快照缓存的页面数（`historyCacheSize`），0 表示不缓存
+    pub history_cache_size: Option<u32>,
+    /// 请求超时（毫秒，`timeout`），0 表示不限时
+    pub timeout_ms: Option<u64>,
+    /// 删除成功时是否返回 `HX-Reswap: delete`，显式删除目标元素（不渲染到 meta 标签）
+    ///
+    /// 关闭时依赖目标元素以 `outerHTML` 交换空内容，保留元素上 `hx-swap` 的 `swap:` 延迟动画
+    pub delete_reswap: bool,
+}
+
+impl HtmxConfig {
+    /// 允许的交换方式
+    const SWAP_STYLES: [&'static str; 8] = [
+        "innerHTML",
+        "outerHTML",
+        "textContent",
+        "beforebegin",
+        "afterbegin",
+        "beforeend",
+        "afterend",
+        "none",
+    ];
+
+    /// `htmx-config` meta 标签的 JSON 内容，全部未设置时返回 `None`
+    pub fn meta_json(&self) -> Option<String> {
+        let mut config = serde_json::Map::new();
+        if let Some(style) = &self.default_swap_style {
+            config.insert("defaultSwapStyle".to_string(), style.clone().into());
+        }
+        if let Some(enabled) = self.history_enabled {
+            config.insert("historyEnabled".to_string(), enabled.into());
+        }
+        if let Some(size) = self.history_cache_size {
+            config.insert("historyCacheSize".to_string(), size.into());
+        }
+        if let Some(timeout) = self.timeout_ms {
+            config.insert("timeout".to_string(), timeout.into());
+        }
+        (!config.is_empty()).then(|| serde_json::Value::Object(config).to_string())
+    }
+}
+
+/// 人为延迟的上限（毫秒），配置和 `?__delay` 参数都不能超过
+pub const MAX_DEV_DELAY_MS: u64 = 30_000;
+
+/// 开发环境的人为延迟，用于调试加载状态和超时处理
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct DevDelayConfig {
+    /// 是否为每个请求注入随机延迟
+    pub enabled: bool,
+    /// 是否允许通过 `?__delay=毫秒` 单独指定延迟，默认关闭，避免任何人都能让连接挂起
+    pub allow_query_param: bool,
+    /// 随机延迟下限（毫秒）
+    pub min_ms: u64,
+    /// 随机延迟上限（毫秒）
+    pub max_ms: u64,
+}
+
+impl Default for DevDelayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allow_query_param: false,
+            min_ms: 200,
+            max_ms: 1000,
         }
     }
 }
 
 /// 应用配置
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct AppConfig {
     pub database: DatabaseConfig,
     pub server: ServerConfig,
     pub security: SecurityConfig,
+    pub static_assets: StaticAssetsConfig,
+    pub compression: CompressionConfig,
+    pub avatar: AvatarConfig,
+    pub cache: CacheConfig,
+    pub metrics: MetricsConfig,
+    pub request_id: RequestIdConfig,
+    pub ui: UiConfig,
+    pub htmx: HtmxConfig,
+    /// 人为延迟注入，仅开发环境生效（见 [`AppConfig::dev_delay`]）
+    pub dev_delay: DevDelayConfig,
+    /// 内置 TLS，未配置时使用明文 HTTP
+    pub tls: Option<TlsConfig>,
     pub log_level: String,
     pub environment: String,
+    /// 访问日志采样率（0.0–1.0），仅作用于非错误响应，4xx/5xx 总是记录
+    pub access_log_sample_rate: f64,
+    /// 是否压缩 HTML 响应中的多余空白，未设置时仅生产环境启用
+    pub minify_html: Option<bool>,
+    /// 开发环境下以 debug 级别记录的请求/响应头，支持 `*` 后缀通配，为空时不记录
+    pub log_headers: Vec<String>,
+    /// 分页参数越界时返回 400，默认关闭（静默修正到合法范围）
+    pub strict_pagination: bool,
+    /// 启动时是否用示例数据渲染所有模板，提前发现渲染期错误
+    pub validate_templates_on_startup: bool,
+    /// 模板自检失败时是否退出，关闭时只记录错误日志
+    ///
+    /// 只记录日志对渲染中的 panic 仅在 debug 构建有效：release 构建使用 `panic = "abort"`，
+    /// 无论是否开启，模板 panic 都会使进程在自检阶段直接退出
+    pub strict_template_validation: bool,
 }
 
 impl Default for AppConfig {
@@ -111,15 +678,31 @@ impl Default for AppConfig {
             database: DatabaseConfig::default(),
             server: ServerConfig::default(),
             security: SecurityConfig::default(),
+            static_assets: StaticAssetsConfig::default(),
+            compression: CompressionConfig::default(),
+            avatar: AvatarConfig::default(),
+            cache: CacheConfig::default(),
+            metrics: MetricsConfig::default(),
+            request_id: RequestIdConfig::default(),
+            ui: UiConfig::default(),
+            htmx: HtmxConfig::default(),
+            dev_delay: DevDelayConfig::default(),
+            tls: None,
             log_level: "info".to_string(),
             environment: "development".to_string(),
+            access_log_sample_rate: 1.0,
+            minify_html: None,
+            log_headers: vec!["HX-*".to_string()],
+            strict_pagination: false,
+            validate_templates_on_startup: true,
+            strict_template_validation: false,
         }
     }
 }
 
 impl AppConfig {
-    /// 从默认位置加载配置
-    pub fn load() -> Result<Self, ConfigError> {
+    /// 构建配置来源：第一个存在的配置文件，再叠加 `APP_` 前缀的环境变量
+    fn figment() -> Figment {
         // 配置文件搜索路径
         let config_paths = [
             PathBuf::from("./config.toml"),
@@ -140,8 +723,18 @@ impl AppConfig {
         }
 
         // 从环境变量加载（优先级最高）
-        figment = figment.merge(Env::prefixed("APP_").split("."));
+        figment.merge(Env::prefixed("APP_").split("."))
+    }
+
+    /// 从默认位置加载配置
+    #[allow(clippy::result_large_err)]
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::load_from(Self::figment())
+    }
 
+    /// 从给定的配置源解析并验证配置
+    #[allow(clippy::result_large_err)]
+    fn load_from(figment: Figment) -> Result<Self, ConfigError> {
         // 构建配置
         let config: AppConfig = figment.extract()?;
 
@@ -152,6 +745,7 @@ impl AppConfig {
     }
 
     /// 验证配置
+    #[allow(clippy::result_large_err)]
     fn validate(&self) -> Result<(), ConfigError> {
         // 环境必须是 development、staging 或 production
         if !matches!(
@@ -173,6 +767,361 @@ impl AppConfig {
             ));
         }
 
+        // 验证路径前缀：为空，或以 / 开头、不以 / 结尾且只含 URL 安全字符
+        let base_path = &self.server.base_path;
+        if !base_path.is_empty()
+            && (!base_path.starts_with('/')
+                || base_path.ends_with('/')
+                || !base_path
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '-' | '_' | '.')))
+        {
+            return Err(ConfigError::Validation(format!(
+                "base_path 必须以 / 开头、不以 / 结尾，且只能包含字母、数字、-、_、.: {}",
+                base_path
+            )));
+        }
+
+        if let Some(public_base_url) = &self.server.public_base_url {
+            let is_valid = public_base_url.parse::<axum::http::Uri>().is_ok_and(|uri| {
+                matches!(uri.scheme_str(), Some("http" | "https"))
+                    && uri.host().is_some_and(|host| !host.is_empty())
+                    && uri.query().is_none()
+            });
+            if !is_valid || public_base_url.ends_with('/') {
+                return Err(ConfigError::Validation(format!(
+                    "public_base_url 必须是不带查询串、不以 / 结尾的 http(s) 地址: {}",
+                    public_base_url
+                )));
+            }
+        }
+
+        // 验证 noindex 路由前缀
+        if let Some(prefix) =
+            self.server.noindex_prefixes.iter().find(|prefix| {
+                !prefix.starts_with('/') || (prefix.len() > 1 && prefix.ends_with('/'))
+            })
+        {
+            return Err(ConfigError::Validation(format!(
+                "noindex_prefixes 中的前缀必须以 / 开头且不以 / 结尾: {}",
+                prefix
+            )));
+        }
+
+        // 验证请求头日志模式：`*` 只能出现在末尾
+        if let Some(pattern) = self
+            .log_headers
+            .iter()
+            .find(|pattern| pattern.is_empty() || pattern.trim_end_matches('*').contains('*'))
+        {
+            return Err(ConfigError::Validation(format!(
+                "log_headers 中的模式不能为空，且 * 只能出现在末尾: {}",
+                pattern
+            )));
+        }
+
+        // Unix socket 仅在 Unix 平台可用
+        if self.server.unix_socket.is_some() && !cfg!(unix) {
+            return Err(ConfigError::Validation(
+                "unix_socket 仅支持 Unix 平台".to_string(),
+            ));
+        }
+
+        // 预加载列表要求覆盖子域名且有效期至少一年
+        let hsts = &self.security.hsts;
+        if hsts.preload && (!hsts.include_subdomains || hsts.max_age_seconds < 31_536_000) {
+            return Err(ConfigError::Validation(
+                "hsts.preload 要求 include_subdomains = true 且 max_age_seconds 不少于 31536000"
+                    .to_string(),
+            ));
+        }
+
+        // 验证 TLS 证书文件（内容在启动时解析）
+        if let Some(tls) = &self.tls {
+            if self.server.unix_socket.is_some() {
+                return Err(ConfigError::Validation(
+                    "tls 不能与 unix_socket 同时使用".to_string(),
+                ));
+            }
+            for path in [&tls.cert_path, &tls.key_path] {
+                if !PathBuf::from(path).is_file() {
+                    return Err(ConfigError::Validation(format!(
+                        "TLS 证书或私钥文件不存在: {}",
+                        path
+                    )));
+                }
+            }
+        }
+
+        // 验证访问日志采样率
+        if !(0.0..=1.0).contains(&self.access_log_sample_rate) {
+            return Err(ConfigError::Validation(
+                "access_log_sample_rate 必须在 0.0 到 1.0 之间".to_string(),
+            ));
+        }
+
+        // 携带凭据的 CORS 不允许通配来源，浏览器会静默拒绝此类请求
+        if let Some(origin) = self
+            .security
+            .cors_allow_origins
+            .iter()
+            .find(|origin| origin.contains('*'))
+        {
+            return Err(ConfigError::Validation(format!(
+                "CORS 启用了凭据（allow_credentials），不能使用通配来源: {}，请列出具体的来源",
+                origin
+            )));
+        }
+
+        // 生产环境不能回退到本地来源，避免把开发配置带到线上
+        if self.is_production() {
+            if let Some(origin) = self
+                .security
+                .cors_allow_origins
+                .iter()
+                .find(|origin| is_local_origin(origin))
+            {
+                return Err(ConfigError::Validation(format!(
+                    "生产环境的 cors_allow_origins 不能包含本地来源: {}",
+                    origin
+                )));
+            }
+        }
+
+        // 浏览器对预检缓存时间有各自的上限（Firefox 24 小时），超出部分无效
+        if self.security.cors_max_age_seconds > 86_400 {
+            return Err(ConfigError::Validation(
+                "cors_max_age_seconds 不能超过 86400".to_string(),
+            ));
+        }
+
+        // 验证 URI 长度限制
+        if self.security.max_uri_length == 0 || self.security.max_query_length == 0 {
+            return Err(ConfigError::Validation(
+                "max_uri_length 和 max_query_length 必须大于 0".to_string(),
+            ));
+        }
+
+        if self
+            .compression
+            .skip_content_types
+            .iter()
+            .any(|content_type| content_type.is_empty())
+        {
+            return Err(ConfigError::Validation(
+                "compression.skip_content_types 不能包含空字符串".to_string(),
+            ));
+        }
+
+        // 验证请求头限制
+        if self.security.max_header_bytes == 0 || self.security.max_header_count == 0 {
+            return Err(ConfigError::Validation(
+                "max_header_bytes 和 max_header_count 必须大于 0".to_string(),
+            ));
+        }
+
+        // 管理令牌过短时容易被猜中
+        if self
+            .security
+            .admin_token
+            .as_ref()
+            .is_some_and(|token| token.len() < 16)
+        {
+            return Err(ConfigError::Validation(
+                "admin_token 长度不能少于 16 个字符".to_string(),
+            ));
+        }
+
+        // 验证 ETag 算法
+        if !matches!(
+            self.static_assets.etag_algorithm.to_lowercase().as_str(),
+            "sha256" | "siphash"
+        ) {
+            return Err(ConfigError::Validation(
+                "etag_algorithm 必须是 sha256 或 siphash".to_string(),
+            ));
+        }
+
+        // 验证 favicon 路径
+        let favicon_path = &self.static_assets.favicon_path;
+        if self.static_assets.favicon_enabled
+            && (favicon_path.is_empty()
+                || favicon_path.starts_with('/')
+                || favicon_path.contains(".."))
+        {
+            return Err(ConfigError::Validation(
+                "favicon_path 必须是 static/ 下的相对路径".to_string(),
+            ));
+        }
+
+        // 验证指标导出端口不与应用端口冲突
+        if self.metrics.port == Some(self.server.port) {
+            return Err(ConfigError::Validation(
+                "metrics.port 不能与 server.port 相同".to_string(),
+            ));
+        }
+
+        // 验证缓存后端
+        match self.cache.backend.as_str() {
+            "memory" => {}
+            "redis" if !cfg!(feature = "redis") => {
+                return Err(ConfigError::Validation(
+                    "cache.backend 为 redis，但编译时未启用 redis feature".to_string(),
+                ));
+            }
+            "redis" if self.cache.redis_url.is_none() => {
+                return Err(ConfigError::Validation(
+                    "cache.backend 为 redis 时必须配置 cache.redis_url".to_string(),
+                ));
+            }
+            "redis" => {}
+            _ => {
+                return Err(ConfigError::Validation(
+                    "cache.backend 必须是 memory 或 redis".to_string(),
+                ));
+            }
+        }
+
+        // 验证缓存命名空间容量
+        for (namespace, capacity) in &self.cache.namespace_capacity {
+            if namespace.is_empty() || namespace.contains(':') {
+                return Err(ConfigError::Validation(format!(
+                    "cache.namespace_capacity 的命名空间 \"{}\" 不能为空或包含 ':'",
+                    namespace
+                )));
+            }
+            if *capacity == 0 {
+                return Err(ConfigError::Validation(format!(
+                    "cache.namespace_capacity.{} 必须大于 0",
+                    namespace
+                )));
+            }
+        }
+        for (entity, patterns) in &self.cache.dependencies {
+            if entity.is_empty() {
+                return Err(ConfigError::Validation(
+                    "cache.dependencies 的实体名不能为空".to_string(),
+                ));
+            }
+            for pattern in patterns {
+                let key = pattern.strip_suffix('*').unwrap_or(pattern);
+                if key.is_empty() || key.contains('*') {
+                    return Err(ConfigError::Validation(format!(
+                        "cache.dependencies.{} 的 \"{}\" 必须是缓存键或以 '*' 结尾的非空前缀",
+                        entity, pattern
+                    )));
+                }
+            }
+        }
+        if !(1..=MAX_CACHE_SHARDS).contains(&self.cache.shards) {
+            return Err(ConfigError::Validation(format!(
+                "cache.shards 必须在 1 到 {} 之间",
+                MAX_CACHE_SHARDS
+            )));
+        }
+
+        // 验证 Gravatar 默认样式
+        if !matches!(
+            self.avatar.gravatar_default.as_str(),
+            "mp" | "identicon" | "monsterid" | "wavatar" | "retro" | "robohash" | "blank"
+        ) {
+            return Err(ConfigError::Validation(
+                "gravatar_default 必须是 mp、identicon、monsterid、wavatar、retro、robohash 或 blank"
+                    .to_string(),
+            ));
+        }
+
+        // 验证首屏用户数
+        if !(1..=100).contains(&self.ui.initial_users) {
+            return Err(ConfigError::Validation(
+                "ui.initial_users 必须在 1–100 之间".to_string(),
+            ));
+        }
+        if self.ui.search_debounce_ms > 5000 {
+            return Err(ConfigError::Validation(
+                "ui.search_debounce_ms 不能超过 5000".to_string(),
+            ));
+        }
+        if self.ui.max_rendered_rows == 0 {
+            return Err(ConfigError::Validation(
+                "ui.max_rendered_rows 必须大于 0".to_string(),
+            ));
+        }
+        if self.ui.timezone.parse::<chrono_tz::Tz>().is_err() {
+            return Err(ConfigError::Validation(format!(
+                "ui.timezone 不是有效的 IANA 时区名: {}",
+                self.ui.timezone
+            )));
+        }
+        if crate::domain::UserSort::try_parse(&self.ui.default_user_sort).is_none() {
+            return Err(ConfigError::Validation(format!(
+                "ui.default_user_sort 必须是 id、name、email 之一，可加 :asc 或 :desc: {}",
+                self.ui.default_user_sort
+            )));
+        }
+
+        if let Some(style) = &self.htmx.default_swap_style {
+            if !HtmxConfig::SWAP_STYLES.contains(&style.as_str()) {
+                return Err(ConfigError::Validation(format!(
+                    "htmx.default_swap_style 必须是 {} 之一: {}",
+                    HtmxConfig::SWAP_STYLES.join("、"),
+                    style
+                )));
+            }
+        }
+
+        // 验证请求 ID 配置
+        if axum::http::HeaderName::from_bytes(self.request_id.header.as_bytes()).is_err() {
+            return Err(ConfigError::Validation(format!(
+                "request_id.header 不是有效的头部名称: {}",
+                self.request_id.header
+            )));
+        }
+        let request_id_format = self.request_id.format.to_lowercase();
+        if !matches!(
+            request_id_format.as_str(),
+            "uuid" | "base62" | "traceparent"
+        ) {
+            return Err(ConfigError::Validation(
+                "request_id.format 必须是 uuid、base62 或 traceparent".to_string(),
+            ));
+        }
+        if self.request_id.header.eq_ignore_ascii_case("traceparent")
+            && request_id_format != "traceparent"
+        {
+            return Err(ConfigError::Validation(
+                "request_id.header 为 traceparent 时 format 也必须是 traceparent".to_string(),
+            ));
+        }
+
+        // 验证网段配置
+        for (name, list) in [
+            ("internal_allow_list", &self.security.internal_allow_list),
+            ("trusted_proxies", &self.security.trusted_proxies),
+        ] {
+            if let Some(invalid) = list.iter().find(|s| parse_network(s).is_none()) {
+                return Err(ConfigError::Validation(format!(
+                    "{} 中的网段无效: {}",
+                    name, invalid
+                )));
+            }
+        }
+        // 未配置受信任代理时，代理转发的请求都来自代理的私有地址，放行私有网段等于对外开放
+        if self.security.trusted_proxies.is_empty() {
+            if let Some(private) = self
+                .security
+                .internal_allow_networks()
+                .iter()
+                .find(|net| overlaps_private_network(net))
+            {
+                return Err(ConfigError::Validation(format!(
+                    "internal_allow_list 包含私有网段 {} 时必须配置 trusted_proxies，\
+                     否则经反向代理转发的外部请求会被放行",
+                    private
+                )));
+            }
+        }
+
         // 验证数据库配置
         if self.database.max_connections < self.database.min_connections {
             return Err(ConfigError::Validation(
@@ -180,27 +1129,499 @@ impl AppConfig {
             ));
         }
 
+        if self.database.max_batch_rows == 0 {
+            return Err(ConfigError::Validation(
+                "max_batch_rows 必须大于 0".to_string(),
+            ));
+        }
+
+        if self.database.keepalive_interval_seconds == Some(0) {
+            return Err(ConfigError::Validation(
+                "keepalive_interval_seconds 必须大于 0".to_string(),
+            ));
+        }
+
+        if !matches!(
+            self.database.log_statements.to_lowercase().as_str(),
+            "off" | "error" | "warn" | "info" | "debug"
+        ) {
+            return Err(ConfigError::Validation(
+                "log_statements 必须是 off、error、warn、info 或 debug".to_string(),
+            ));
+        }
+
+        // 验证 SQLite pragma 配置
+        if !matches!(
+            self.database.synchronous.to_uppercase().as_str(),
+            "OFF" | "NORMAL" | "FULL" | "EXTRA"
+        ) {
+            return Err(ConfigError::Validation(
+                "synchronous 必须是 OFF、NORMAL、FULL 或 EXTRA".to_string(),
+            ));
+        }
+
+        if !matches!(
+            self.database.temp_store.to_uppercase().as_str(),
+            "DEFAULT" | "FILE" | "MEMORY"
+        ) {
+            return Err(ConfigError::Validation(
+                "temp_store 必须是 DEFAULT、FILE 或 MEMORY".to_string(),
+            ));
+        }
+
+        if self.database.migration_lock_wait_seconds > 3600 {
+            return Err(ConfigError::Validation(
+                "migration_lock_wait_seconds 不能超过 3600 秒".to_string(),
+            ));
+        }
+        if self.database.busy_timeout_seconds > 300 {
+            return Err(ConfigError::Validation(
+                "busy_timeout_seconds 不能超过 300 秒".to_string(),
+            ));
+        }
+
+        if self.database.cache_size == 0 {
+            return Err(ConfigError::Validation("cache_size 不能为 0".to_string()));
+        }
+
+        // 生产环境不允许插入示例数据
+        if self.is_production() && self.database.seed_on_startup == Some(true) {
+            return Err(ConfigError::Validation(
+                "生产环境不能启用 seed_on_startup".to_string(),
+            ));
+        }
+
+        // 生产环境不暴露查询次数
+        if self.is_production() && self.database.query_count_header == Some(true) {
+            return Err(ConfigError::Validation(
+                "生产环境不能启用 query_count_header".to_string(),
+            ));
+        }
+
+        // 验证延迟注入配置
+        if self.dev_delay.min_ms > self.dev_delay.max_ms || self.dev_delay.max_ms > MAX_DEV_DELAY_MS
+        {
+            return Err(ConfigError::Validation(format!(
+                "dev_delay 要求 min_ms 不大于 max_ms，且 max_ms 不超过 {}",
+                MAX_DEV_DELAY_MS
+            )));
+        }
+        if self.is_production() && (self.dev_delay.enabled || self.dev_delay.allow_query_param) {
+            return Err(ConfigError::Validation(
+                "生产环境不能启用 dev_delay".to_string(),
+            ));
+        }
+
         Ok(())
     }
 
     /// 是否为生产环境
-    #[allow(dead_code)]
     pub fn is_production(&self) -> bool {
         self.environment.to_lowercase() == "production"
     }
 
     /// 是否为开发环境
-    #[allow(dead_code)]
     pub fn is_development(&self) -> bool {
         self.environment.to_lowercase() == "development"
     }
+
+    /// 启动时是否插入示例数据
+    ///
+    /// 显式配置优先，否则仅开发环境插入
+    pub fn should_seed(&self) -> bool {
+        self.database
+            .seed_on_startup
+            .unwrap_or_else(|| self.is_development())
+    }
+
+    /// 是否在响应头中返回数据库查询次数
+    ///
+    /// 显式配置优先，否则仅开发环境启用
+    pub fn should_expose_query_count(&self) -> bool {
+        self.database
+            .query_count_header
+            .unwrap_or_else(|| self.is_development())
+    }
+
+    /// 当前环境实际生效的 CORS 策略
+    ///
+    /// 开发环境额外允许任意端口的 `http://localhost` 和 `http://127.0.0.1`，
+    /// 其他环境只允许 `cors_allow_origins` 中列出的来源
+    pub fn cors_policy(&self) -> CorsPolicy {
+        CorsPolicy {
+            origins: self.security.cors_allow_origins.clone(),
+            allow_local_any_port: self.is_development(),
+        }
+    }
+
+    /// 是否压缩 HTML 响应
+    ///
+    /// 显式配置优先，否则仅生产环境压缩，开发环境保留原始格式便于调试
+    pub fn should_minify_html(&self) -> bool {
+        self.minify_html.unwrap_or_else(|| self.is_production())
+    }
+
+    /// 对外访问的根地址，不以 `/` 结尾
+    ///
+    /// 优先使用 `server.public_base_url`，未设置时按 `http(s)://host:port` 加 `base_path` 推导
+    /// （启用内置 TLS 时为 https）。部署在反向代理之后或监听 `0.0.0.0` 时推导结果通常不是
+    /// 外部可访问的地址，生产环境应显式配置
+    pub fn public_base_url(&self) -> String {
+        if let Some(public_base_url) = &self.server.public_base_url {
+            return public_base_url.clone();
+        }
+        let scheme = if self.tls.is_some() { "https" } else { "http" };
+        format!(
+            "{}://{}{}",
+            scheme,
+            self.server.server_addr(),
+            self.server.base_path
+        )
+    }
+
+    /// `Strict-Transport-Security` 头的值
+    ///
+    /// 只在启用内置 TLS 时返回，避免明文 HTTP 下（如本地开发）浏览器被锁定为只能用 HTTPS 访问
+    pub fn hsts_header_value(&self) -> Option<String> {
+        let hsts = &self.security.hsts;
+        if self.tls.is_none() || !hsts.enabled {
+            return None;
+        }
+
+        let mut value = format!("max-age={}", hsts.max_age_seconds);
+        if hsts.include_subdomains {
+            value.push_str("; includeSubDomains");
+        }
+        if hsts.preload {
+            value.push_str("; preload");
+        }
+        Some(value)
+    }
+
+    /// 生效的延迟注入配置
+    ///
+    /// 仅开发环境返回 `Some`（`?__delay` 参数还需开启 `allow_query_param`），
+    /// 其他环境一律为 `None`，不挂载延迟中间件
+    pub fn dev_delay(&self) -> Option<DevDelayConfig> {
+        self.is_development().then(|| self.dev_delay.clone())
+    }
+
+    /// 是否记录请求/响应头调试日志（仅开发环境）
+    pub fn should_log_headers(&self) -> bool {
+        self.is_development() && !self.log_headers.is_empty()
+    }
+
+    /// 脱敏后的副本，用于打印生效配置
+    ///
+    /// 连接地址经 [`sanitize_log_message`] 清理（密码、令牌等），管理令牌整体替换为星号
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        let redact_url = |url: &mut Option<String>| {
+            if let Some(url) = url {
+                *url = sanitize_log_message(url);
+            }
+        };
+        redact_url(&mut config.database.url);
+        redact_url(&mut config.database.read_url);
+        redact_url(&mut config.cache.redis_url);
+        if config.security.admin_token.is_some() {
+            config.security.admin_token = Some("********".to_string());
+        }
+        config
+    }
+}
+
+/// 加载失败时判断目标环境
+///
+/// 先直接读取环境变量 `APP_ENVIRONMENT`：配置文件有语法错误时 figment 整体无法解析，
+/// 不能依赖它得到环境。未设置时再尝试从配置中单独读取 `environment`，
+/// 仍读取不到时按默认的开发环境处理
+fn environment_on_load_failure() -> String {
+    if let Ok(environment) = std::env::var("APP_ENVIRONMENT") {
+        return environment;
+    }
+    AppConfig::figment()
+        .extract_inner::<String>("environment")
+        .unwrap_or_else(|_| AppConfig::default().environment)
+}
+
+/// 加载配置
+///
+/// 验证失败时总是退出：配置已读取但取值不合法，回退到默认配置只会掩盖错误。
+/// 读取或解析失败时，生产环境退出，避免带着默认配置悄悄运行；其他环境回退到默认配置
+fn load_or_exit() -> AppConfig {
+    match AppConfig::load() {
+        Ok(config) => config,
+        Err(e @ ConfigError::Validation(_)) => {
+            eprintln!("错误: {}\n提示: {}", e, e.guidance());
+            std::process::exit(1);
+        }
+        Err(e) if environment_on_load_failure().eq_ignore_ascii_case("production") => {
+            eprintln!(
+                "错误: {}\n提示: {}\n生产环境拒绝使用默认配置，退出.",
+                e,
+                e.guidance()
+            );
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("警告: {}\n提示: {}\n使用默认配置.", e, e.guidance());
+            AppConfig::default()
+        }
+    }
 }
 
+/// 启动时由 [`init_config`] 加载的配置，`CONFIG` 优先使用它，避免重复加载
+static STARTUP_CONFIG: OnceLock<AppConfig> = OnceLock::new();
+
 // 提供一个全局配置实例的访问方式
 lazy_static::lazy_static! {
-    pub static ref CONFIG: AppConfig = AppConfig::load()
-        .unwrap_or_else(|e| {
-            eprintln!("警告: 无法加载配置: {}. 使用默认配置.", e);
-            AppConfig::default()
-        });
+    pub static ref CONFIG: AppConfig = STARTUP_CONFIG.get().cloned().unwrap_or_else(load_or_exit);
+}
+
+/// 打印合并后的生效配置（JSON，已脱敏）并退出，对应命令行参数 `--print-config`
+///
+/// 与正常启动不同，加载或验证失败时不回退到默认配置，而是输出错误并以非零状态退出
+pub fn print_config_and_exit() -> ! {
+    match AppConfig::load() {
+        Ok(config) => match serde_json::to_string_pretty(&config.redacted()) {
+            Ok(json) => {
+                println!("{}", json);
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("错误: 无法序列化配置: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Err(e) => {
+            eprintln!("错误: {}\n提示: {}", e, e.guidance());
+            std::process::exit(1);
+        }
+    }
+}
+
+/// 显式初始化全局配置
+///
+/// 应在 `main` 启动任何服务之前调用，使配置错误在启动阶段立即暴露：
+/// 在访问 `CONFIG` 之前加载配置，按错误类型输出说明和修正建议（见 [`ConfigError::guidance`]），
+/// 加载结果随后作为 `CONFIG` 的值
+pub fn init_config() -> &'static AppConfig {
+    if STARTUP_CONFIG.get().is_none() {
+        let _ = STARTUP_CONFIG.set(load_or_exit());
+    }
+    &CONFIG
+}
+
+/// CORS 来源策略
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorsPolicy {
+    /// 明确列出的来源
+    pub origins: Vec<String>,
+    /// 是否允许任意端口的本地来源
+    pub allow_local_any_port: bool,
+}
+
+impl CorsPolicy {
+    /// 是否允许该来源
+    pub fn allows(&self, origin: &str) -> bool {
+        self.origins.iter().any(|allowed| allowed == origin)
+            || (self.allow_local_any_port && is_local_origin(origin))
+    }
+}
+
+/// 是否为本地开发来源（`http://localhost[:port]` 或 `http://127.0.0.1[:port]`）
+fn is_local_origin(origin: &str) -> bool {
+    let Some(authority) = origin.strip_prefix("http://") else {
+        return false;
+    };
+    let (host, port) = authority.split_once(':').unwrap_or((authority, ""));
+    matches!(host, "localhost" | "127.0.0.1") && port.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn private_allow_list_requires_trusted_proxies() {
+        let mut config = AppConfig::default();
+        assert!(config.validate().is_ok());
+
+        config
+            .security
+            .internal_allow_list
+            .push("172.16.0.0/12".to_string());
+        assert!(matches!(config.validate(), Err(ConfigError::Validation(_))));
+
+        config.security.trusted_proxies = vec!["172.18.0.0/16".to_string()];
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn cors_credentials_reject_wildcard_origins() {
+        let mut config = AppConfig::default();
+        for origin in ["*", "https://*.example.com"] {
+            config.security.cors_allow_origins = vec![origin.to_string()];
+            let Err(ConfigError::Validation(message)) = config.validate() else {
+                panic!("通配来源 {} 应被拒绝", origin);
+            };
+            assert!(message.contains("allow_credentials"), "{}", message);
+            assert!(message.contains(origin), "{}", message);
+        }
+
+        config.security.cors_allow_origins = vec!["https://app.example.com".to_string()];
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn cors_effective_origins_per_environment() {
+        let listed = "https://app.example.com";
+        let mut config = AppConfig::default();
+        config.security.cors_allow_origins = vec![listed.to_string()];
+
+        for (environment, local_allowed) in [
+            ("development", true),
+            ("staging", false),
+            ("production", false),
+        ] {
+            config.environment = environment.to_string();
+            assert!(config.validate().is_ok(), "{}", environment);
+            let policy = config.cors_policy();
+            assert_eq!(policy.origins, vec![listed.to_string()], "{}", environment);
+            assert!(policy.allows(listed), "{}", environment);
+            assert!(
+                !policy.allows("https://evil.example.com"),
+                "{}",
+                environment
+            );
+            for local in [
+                "http://localhost:5173",
+                "http://127.0.0.1:8080",
+                "http://localhost",
+            ] {
+                assert_eq!(
+                    policy.allows(local),
+                    local_allowed,
+                    "{} {}",
+                    environment,
+                    local
+                );
+            }
+        }
+        // 本地来源只放行 http，且主机须完全一致
+        config.environment = "development".to_string();
+        let policy = config.cors_policy();
+        assert!(!policy.allows("https://localhost:5173"));
+        assert!(!policy.allows("http://localhost.evil.com"));
+    }
+
+    #[test]
+    fn production_rejects_local_cors_origins() {
+        let mut config = AppConfig {
+            environment: "production".to_string(),
+            ..AppConfig::default()
+        };
+        config.security.cors_allow_origins = vec!["http://localhost:3000".to_string()];
+        assert!(matches!(config.validate(), Err(ConfigError::Validation(_))));
+
+        config.environment = "staging".to_string();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn import_timeout_defaults_to_120_seconds_and_zero_disables_it() {
+        let mut server = ServerConfig::default();
+        assert_eq!(server.import_timeout(), Some(Duration::from_secs(120)));
+        server.import_timeout_seconds = 0;
+        assert_eq!(server.import_timeout(), None);
+    }
+
+    #[test]
+    fn initial_users_must_be_between_1_and_100() {
+        let mut config = AppConfig::default();
+        for (count, valid) in [(0, false), (1, true), (100, true), (101, false)] {
+            config.ui.initial_users = count;
+            assert_eq!(config.validate().is_ok(), valid, "{}", count);
+        }
+    }
+
+    #[test]
+    fn default_user_sort_must_be_allow_listed() {
+        let mut config = AppConfig::default();
+        config.ui.default_user_sort = "email:desc".to_string();
+        assert!(config.validate().is_ok());
+
+        config.ui.default_user_sort = "created_at:desc".to_string();
+        assert!(matches!(config.validate(), Err(ConfigError::Validation(_))));
+    }
+
+    #[test]
+    fn metrics_exporter_listens_on_loopback_by_default() {
+        assert!(MetricsConfig::default().host.is_loopback());
+    }
+
+    #[test]
+    fn dev_delay_only_in_development() {
+        let mut config = AppConfig::default();
+        config.dev_delay.enabled = true;
+        config.dev_delay.allow_query_param = true;
+        assert!(config.dev_delay().is_some());
+
+        config.environment = "staging".to_string();
+        assert!(config.dev_delay().is_none());
+
+        config.environment = "production".to_string();
+        assert!(config.dev_delay().is_none());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn cache_dependencies_extend_builtin_entities() {
+        let config = CacheConfig {
+            dependencies: HashMap::from([
+                ("tags".to_string(), vec!["tags".to_string()]),
+                (
+                    "users".to_string(),
+                    vec!["users".to_string(), "stats:users".to_string()],
+                ),
+            ]),
+            ..CacheConfig::default()
+        };
+
+        let dependencies = config.effective_dependencies();
+        assert_eq!(dependencies["tags"], vec!["tags"]);
+        assert_eq!(dependencies["todos"], vec!["todos", "search:todos:*"]);
+        assert_eq!(
+            dependencies["users"],
+            vec!["users", "initial_users:*", "search:users:*", "stats:users"]
+        );
+    }
+
+    #[test]
+    fn loading_error_message_and_guidance() {
+        let error = AppConfig::load_from(Figment::from(Toml::string(
+            "[server]\nport = \"not-a-port\"",
+        )))
+        .unwrap_err();
+
+        assert!(matches!(error, ConfigError::Loading(_)));
+        let message = error.to_string();
+        assert!(message.starts_with("配置加载错误: "), "{}", message);
+        assert!(message.contains("port"), "{}", message);
+        assert!(error.guidance().contains("TOML 语法"));
+    }
+
+    #[test]
+    fn validation_error_message_and_guidance() {
+        let error = AppConfig::load_from(Figment::from(Toml::string("environment = \"prod\"")))
+            .unwrap_err();
+
+        assert!(matches!(error, ConfigError::Validation(_)));
+        assert_eq!(
+            error.to_string(),
+            "配置验证错误: 环境必须是 development、staging 或 production"
+        );
+        assert!(error.guidance().contains("取值不合法"));
+    }
 }
@@ -0,0 +1,19 @@
+//! HTML 辅助函数
+//!
+//! 手工拼接 HTML 片段（头像、提示消息等）时使用，模板渲染由 askama 负责转义
+
+/// 转义 HTML 特殊字符
+pub fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,114 @@
+//! 多语言消息模块
+//!
+//! 根据 `Accept-Language` 选择界面消息的语言，无法识别时回退到中文
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::convert::Infallible;
+
+/// 支持的语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    Zh,
+    En,
+}
+
+impl Locale {
+    /// 解析 `Accept-Language`，按 q 值选择第一个支持的语言
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let Some(value) = headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok())
+        else {
+            return Self::default();
+        };
+
+        let mut candidates: Vec<(f32, Locale)> = value
+            .split(',')
+            .filter_map(|item| {
+                let mut parts = item.trim().split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .and_then(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                Some((quality, Self::from_tag(tag)?))
+            })
+            .filter(|(quality, _)| *quality > 0.0)
+            .collect();
+
+        // 稳定排序，q 值相同时保留请求头中的顺序
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+        candidates
+            .first()
+            .map(|(_, locale)| *locale)
+            .unwrap_or_default()
+    }
+
+    /// 按主语言标签匹配（`en-US` → `En`）
+    fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split('-').next()?;
+        if primary.eq_ignore_ascii_case("zh") {
+            Some(Self::Zh)
+        } else if primary.eq_ignore_ascii_case("en") {
+            Some(Self::En)
+        } else {
+            None
+        }
+    }
+
+    /// `Content-Language` 使用的语言标签
+    pub fn tag(self) -> &'static str {
+        match self {
+            Self::Zh => "zh-CN",
+            Self::En => "en",
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Locale
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(&parts.headers))
+    }
+}
+
+/// 界面消息目录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    /// 读取页面数据失败
+    LoadDataFailed,
+}
+
+impl Message {
+    /// 取指定语言的消息文本
+    pub fn text(self, locale: Locale) -> &'static str {
+        match (self, locale) {
+            (Self::LoadDataFailed, Locale::Zh) => "获取数据失败，请稍后重试",
+            (Self::LoadDataFailed, Locale::En) => "Failed to load data, please try again later",
+        }
+    }
+
+    /// 构建带 `Content-Language` 的纯文本错误响应
+    pub fn into_response(self, status: StatusCode, locale: Locale) -> Response {
+        (
+            status,
+            [(
+                header::CONTENT_LANGUAGE,
+                HeaderValue::from_static(locale.tag()),
+            )],
+            self.text(locale),
+        )
+            .into_response()
+    }
+}
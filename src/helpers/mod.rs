@@ -1,6 +1,15 @@
 // 公共辅助函数和工具模块
+pub mod avatar;
 pub mod cache;
+pub mod compression;
 pub mod config;
+pub mod html;
+pub mod i18n;
 pub mod monitoring;
 pub mod pagination;
+#[cfg(feature = "redis")]
+pub mod redis_cache;
+pub mod request_id;
 pub mod security;
+pub mod timeout;
+pub mod timezone;
@@ -2,15 +2,29 @@
 //!
 //! 提供健康检查、性能指标收集和API文档功能
 
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+use axum::{
+    body::HttpBody,
+    extract::{MatchedPath, Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Router,
+};
 use metrics::{counter, gauge, histogram, increment_counter};
-use metrics_exporter_prometheus::PrometheusBuilder;
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
 use serde::Serialize;
-use sqlx::{Error as SqlxError, SqlitePool};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tracing::Span;
 
-use crate::helpers::config::AppConfig;
+use crate::db::Db;
+use crate::helpers::config::{AppConfig, MetricsConfig};
+use crate::helpers::request_id::RequestId;
+use crate::helpers::security::{ip_allow_list_guard, redact_header_value, IpAllowList};
+use crate::routes::registry::RouteRegistry;
+use crate::services::shutdown::ShutdownCoordinator;
 
 /// 健康检查响应
 #[derive(Serialize)]
@@ -21,22 +35,45 @@ pub struct HealthCheckResponse {
     pub database: String,
 }
 
+/// 就绪检查响应
+#[derive(Serialize)]
+pub struct ReadinessResponse {
+    pub status: String,
+    /// 缓存预热是否已完成
+    pub cache_warmed: bool,
+    /// 缓存自动刷新任务是否正常（最近一轮刷新失败，或异常退出后、重启并成功刷新一轮前为 false）
+    pub cache_refresher_healthy: bool,
+    /// 连接池当前连接数
+    pub pool_connections: u32,
+    /// 连接池最小连接数
+    pub min_connections: u32,
+}
+
 /// 应用状态，包含启动时间和数据库连接池
 #[derive(Clone)]
 pub struct AppState {
     pub start_time: Instant,
-    pub pool: SqlitePool,
-    #[allow(dead_code)]
+    /// 健康检查探测主库（`write`），缓存预热读取只读副本（`read`）
+    pub db: Db,
     pub config: Arc<AppConfig>,
+    /// Prometheus 指标句柄，用于渲染 `/metrics`
+    pub metrics: PrometheusHandle,
+    /// 缓存预热是否已完成至少一次
+    warmed_up: Arc<AtomicBool>,
+    /// 缓存自动刷新任务是否正常
+    cache_refresher_healthy: Arc<AtomicBool>,
 }
 
 impl AppState {
     /// 创建新的应用状态
-    pub fn new(pool: SqlitePool, config: Arc<AppConfig>) -> Self {
+    pub fn new(db: Db, config: Arc<AppConfig>, metrics: PrometheusHandle) -> Self {
         Self {
             start_time: Instant::now(),
-            pool,
+            db,
             config,
+            metrics,
+            warmed_up: Arc::new(AtomicBool::new(false)),
+            cache_refresher_healthy: Arc::new(AtomicBool::new(true)),
         }
     }
 
@@ -44,28 +81,98 @@ impl AppState {
     pub fn uptime(&self) -> u64 {
         self.start_time.elapsed().as_secs()
     }
+
+    /// 标记缓存预热已完成，由预热任务调用
+    pub fn mark_warmed_up(&self) {
+        self.warmed_up.store(true, Ordering::Release);
+    }
+
+    /// 缓存预热是否已完成
+    pub fn is_warmed_up(&self) -> bool {
+        self.warmed_up.load(Ordering::Acquire)
+    }
+
+    /// 设置缓存自动刷新任务的健康状态，由刷新任务及其守护逻辑调用
+    pub fn set_cache_refresher_healthy(&self, healthy: bool) {
+        self.cache_refresher_healthy
+            .store(healthy, Ordering::Release);
+    }
+
+    /// 缓存自动刷新任务是否正常
+    pub fn is_cache_refresher_healthy(&self) -> bool {
+        self.cache_refresher_healthy.load(Ordering::Acquire)
+    }
+
+    /// 关闭前输出最终的指标快照
+    ///
+    /// 抓取式部署在进程退出前可能错过最后一次抓取，这里更新运行时间后渲染一次快照并以 debug 级别记录；
+    /// 指标未对外暴露（无独立导出器且关闭了 `/metrics`）时不做任何处理
+    pub fn flush_metrics(&self) {
+        if !self.config.metrics.is_exposed() {
+            return;
+        }
+
+        gauge!("app_uptime_seconds", self.uptime() as f64);
+        let snapshot = self.metrics.render();
+        tracing::info!("📈 已输出最终指标快照（{} 字节）", snapshot.len());
+        tracing::debug!("最终指标快照:\n{}", snapshot);
+    }
 }
 
 /// 初始化指标收集器
-pub fn init_metrics() {
-    // 设置 Prometheus 指标收集器
-    let builder = PrometheusBuilder::new();
-    builder
-        .install()
-        .expect("Failed to install Prometheus metrics exporter");
+pub fn init_metrics(config: &MetricsConfig, shutdown: &ShutdownCoordinator) -> PrometheusHandle {
+    // 设置 Prometheus 指标收集器，配置了独立端口时同时启动导出器
+    let (recorder, exporter) = match config.port {
+        Some(port) => {
+            let addr = SocketAddr::new(config.host, port);
+            match PrometheusBuilder::new().with_http_listener(addr).build() {
+                Ok((recorder, exporter)) => {
+                    tracing::info!("📈 Prometheus 导出器监听 http://{}", addr);
+                    (recorder, Some(exporter))
+                }
+                Err(e) => {
+                    // 端口被占用等情况不影响应用启动，只是无法通过独立端口抓取
+                    tracing::warn!("⚠️  Prometheus 导出器启动失败 ({}): {}", addr, e);
+                    (PrometheusBuilder::new().build_recorder(), None)
+                }
+            }
+        }
+        None => (PrometheusBuilder::new().build_recorder(), None),
+    };
+
+    let handle = recorder.handle();
+    metrics::set_boxed_recorder(Box::new(recorder))
+        .expect("Failed to install Prometheus metrics recorder");
+
+    if let Some(exporter) = exporter {
+        shutdown.spawn("metrics-exporter", |token| async move {
+            tokio::select! {
+                result = exporter => {
+                    if let Err(e) = result {
+                        tracing::error!("❌ Prometheus 导出器异常退出: {}", e);
+                    }
+                }
+                _ = token.cancelled() => {}
+            }
+        });
+    }
 
     // 初始化HTTP请求指标
     counter!("http_requests_total", 0);
     gauge!("app_uptime_seconds", 0.0);
     histogram!("http_request_duration_seconds", 0.0);
+    histogram!("http_request_size_bytes", 0.0);
+    histogram!("http_response_size_bytes", 0.0);
     counter!("http_requests_errors_total", 0);
+    counter!("http_requests_uri_too_long_total", 0);
+    counter!("http_requests_ip_denied_total", 0);
+    counter!("http_requests_timed_out_total", 0);
 
     // 初始化数据库指标
     counter!("db_queries_total", 0);
     histogram!("db_query_duration_seconds", 0.0);
     counter!("db_queries_errors_total", 0);
-    gauge!("db_connections_active", 0.0);
-    gauge!("db_connections_idle", 0.0);
+    counter!("db_connections_recycled_total", 0);
 
     // 初始化缓存指标
     counter!("cache_hits_total", 0);
@@ -73,11 +180,15 @@ pub fn init_metrics() {
     counter!("cache_sets_total", 0);
     counter!("cache_invalidations_total", 0);
     gauge!("cache_size_items", 0.0);
+    counter!("cache_namespace_evictions_total", 0);
+    counter!("cache_refresh_task_restarts_total", 0);
 
     // 初始化业务指标
     gauge!("todos_count_total", 0.0);
     gauge!("todos_count_completed", 0.0);
     gauge!("users_count_total", 0.0);
+
+    handle
 }
 
 /// 健康检查处理器
@@ -89,7 +200,7 @@ pub async fn health_check(State(state): State<AppState>) -> impl IntoResponse {
     gauge!("app_uptime_seconds", state.uptime() as f64);
 
     // 检查数据库连接
-    let db_status = match sqlx::query("SELECT 1").execute(&state.pool).await {
+    let db_status = match sqlx::query("SELECT 1").execute(&state.db.write).await {
         Ok(_) => "ok",
         Err(e) => {
             tracing::error!("数据库健康检查失败: {}", e);
@@ -109,14 +220,101 @@ pub async fn health_check(State(state): State<AppState>) -> impl IntoResponse {
     (StatusCode::OK, axum::Json(response)).into_response()
 }
 
+/// 就绪检查处理器
+///
+/// 缓存预热完成（`readiness_requires_warmup` 关闭时跳过）且连接池达到最小连接数后返回 200，
+/// 否则返回 503，供负载均衡器在实例预热期间暂缓分发流量。
+///
+/// 缓存自动刷新任务异常时状态为 `degraded` 但仍返回 200：缓存过期后请求会直接查库，实例仍可服务
+pub async fn readiness_check(State(state): State<AppState>) -> impl IntoResponse {
+    let cache_warmed = state.is_warmed_up();
+    let cache_refresher_healthy = state.is_cache_refresher_healthy();
+    let pool_connections = state.db.write.size();
+    let min_connections = state.db.write.options().get_min_connections();
+
+    let ready = (cache_warmed || !state.config.server.readiness_requires_warmup)
+        && pool_connections >= min_connections;
+
+    let response = ReadinessResponse {
+        status: match (ready, cache_refresher_healthy) {
+            (false, _) => "warming_up",
+            (true, true) => "ready",
+            (true, false) => "degraded",
+        }
+        .to_string(),
+        cache_warmed,
+        cache_refresher_healthy,
+        pool_connections,
+        min_connections,
+    };
+
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, axum::Json(response)).into_response()
+}
+
+/// 未匹配路由的指标标签
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// 获取用于指标标签的路由模式
+///
+/// 使用 axum 匹配到的路由模式（如 `/block/users/:id/detail`），避免原始路径中的 id 导致标签基数爆炸；
+/// 未匹配任何路由的请求（如 404）统一标记为 `unmatched`
+fn route_label<B>(req: &axum::http::Request<B>) -> String {
+    req.extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string())
+}
+
+/// 创建请求的链路追踪 span（`TraceLayer::make_span_with`）
+///
+/// 与指标一样按路由模式而非原始路径标记，`otel.name` 为 `方法 路由模式`（如 `GET /api/todos/:id`），
+/// 同一路由的请求在追踪系统中归为一组。`status` 和 `latency_ms` 在响应后由
+/// [`record_request_span_response`] 写入
+pub fn make_request_span<B>(req: &axum::http::Request<B>) -> Span {
+    let route = route_label(req);
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .map(RequestId::as_str)
+        .unwrap_or_default();
+    tracing::info_span!(
+        "request",
+        otel.name = %format_args!("{} {}", req.method(), route),
+        method = %req.method(),
+        route = %route,
+        request_id = %request_id,
+        status = tracing::field::Empty,
+        latency_ms = tracing::field::Empty,
+    )
+}
+
+/// 在请求 span 上记录响应状态码和耗时（`TraceLayer::on_response`）
+pub fn record_request_span_response<B>(
+    response: &axum::http::Response<B>,
+    latency: Duration,
+    span: &Span,
+) {
+    let status = response.status().as_u16();
+    let latency_ms = latency.as_millis() as u64;
+    span.record("status", status);
+    span.record("latency_ms", latency_ms);
+    tracing::debug!("请求处理完成");
+}
+
 /// 指标收集中间件
 pub async fn metrics_middleware(
     req: axum::http::Request<axum::body::Body>,
     next: axum::middleware::Next,
 ) -> impl IntoResponse {
     let start = Instant::now();
-    let path = req.uri().path().to_string();
     let method = req.method().to_string();
+    let route = route_label(&req);
+    let request_size = req.body().size_hint().exact();
 
     // 处理请求
     let response = next.run(req).await;
@@ -127,76 +325,313 @@ pub async fn metrics_middleware(
 
     // 根据状态码分类记录请求
     if status.starts_with('2') {
-        increment_counter!("http_requests_total", "status" => status.clone(), "method" => method.clone(), "path" => path.clone());
+        increment_counter!("http_requests_total", "status" => status.clone(), "method" => method.clone(), "route" => route.clone());
     } else {
-        increment_counter!("http_requests_errors_total", "status" => status.clone(), "method" => method.clone(), "path" => path.clone());
+        increment_counter!("http_requests_errors_total", "status" => status.clone(), "method" => method.clone(), "route" => route.clone());
     }
 
-    // 使用histogram记录请求时间分布
+    // 记录请求和响应体大小（仅在大小已知时，流式响应跳过）
+    if let Some(size) = request_size {
+        histogram!("http_request_size_bytes", size as f64,
+            "method" => method.clone(),
+            "route" => route.clone()
+        );
+    }
+    if let Some(size) = response.body().size_hint().exact() {
+        histogram!("http_response_size_bytes", size as f64,
+            "method" => method.clone(),
+            "route" => route.clone()
+        );
+    }
+
+    // 使用histogram记录请求时间分布，按路由模式而非原始路径标记
     histogram!("http_request_duration_seconds", duration.as_secs_f64(),
         "status" => status,
         "method" => method,
-        "path" => path
+        "route" => route
+    );
+
+    response
+}
+
+/// 访问日志中间件
+///
+/// 按采样率记录成功请求，4xx/5xx 请求总是记录，避免高负载下日志量失控
+pub async fn access_log_middleware(
+    State(sample_rate): State<f64>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let start = Instant::now();
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+
+    let response = next.run(req).await;
+
+    let status = response.status();
+    let is_error = status.is_client_error() || status.is_server_error();
+    if is_error || sample_rate >= 1.0 || rand::random::<f64>() < sample_rate {
+        tracing::info!(
+            target: "htmx_rs_template::access_log",
+            "{} {} {} {}ms",
+            method,
+            path,
+            status.as_u16(),
+            start.elapsed().as_millis()
+        );
+    }
+
+    response
+}
+
+/// 请求头日志的 target，可单独调整级别（如 `htmx_rs_template::header_log=debug`）
+const HEADER_LOG_TARGET: &str = "htmx_rs_template::header_log";
+
+/// 请求/响应头调试日志中间件
+///
+/// 仅开发环境启用（见 [`AppConfig::should_log_headers`]），以 debug 级别记录匹配
+/// `log_headers` 的请求头和响应头，便于排查 `HX-*` 头的往返；敏感头始终脱敏。
+/// 日志级别高于 debug 时直接放行，不做任何处理
+pub async fn header_log_middleware(
+    State(patterns): State<Option<Arc<[String]>>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(patterns) = patterns else {
+        return next.run(req).await;
+    };
+    if !tracing::enabled!(target: HEADER_LOG_TARGET, tracing::Level::DEBUG) {
+        return next.run(req).await;
+    }
+
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let request_headers = format_logged_headers(req.headers(), &patterns);
+
+    let response = next.run(req).await;
+
+    let response_headers = format_logged_headers(response.headers(), &patterns);
+    tracing::debug!(
+        target: HEADER_LOG_TARGET,
+        "{} {} {} 请求头: [{}] 响应头: [{}]",
+        method,
+        path,
+        response.status().as_u16(),
+        request_headers,
+        response_headers
     );
 
     response
 }
 
+/// 按允许列表筛选并格式化头部，敏感头的值替换为星号
+fn format_logged_headers(headers: &HeaderMap, patterns: &[String]) -> String {
+    headers
+        .iter()
+        .filter(|(name, _)| {
+            patterns
+                .iter()
+                .any(|pattern| header_matches(name.as_str(), pattern))
+        })
+        .map(|(name, value)| {
+            format!(
+                "{}: {}",
+                name,
+                redact_header_value(name, value.to_str().unwrap_or("<binary>"))
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// 头部名是否匹配模式（不区分大小写，支持 `*` 后缀通配）
+fn header_matches(name: &str, pattern: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name
+            .get(..prefix.len())
+            .is_some_and(|head| head.eq_ignore_ascii_case(prefix)),
+        None => name.eq_ignore_ascii_case(pattern),
+    }
+}
+
 /// 创建监控路由
-pub fn create_monitoring_routes(state: AppState) -> Router {
+///
+/// 健康检查供负载均衡和编排系统探测，不限制来源；`/metrics` 只允许 `metrics_allow_list` 内的地址访问
+pub fn create_monitoring_routes(
+    state: AppState,
+    metrics_allow_list: IpAllowList,
+    registry: &mut RouteRegistry,
+) -> Router {
     use axum::routing::get;
 
     // 创建路由
-    Router::new()
-        .route("/health", get(health_check))
-        .route("/metrics", get(metrics_handler))
-        .with_state(state)
+    let mut router = Router::new()
+        .route(registry.add("GET", "/health"), get(health_check))
+        .route(registry.add("GET", "/health/ready"), get(readiness_check));
+
+    // 使用独立端口抓取时可关闭应用内的 /metrics
+    if state.config.metrics.app_route_enabled {
+        router = router.route(
+            registry.add("GET", "/metrics"),
+            get(metrics_handler).route_layer(axum::middleware::from_fn_with_state(
+                metrics_allow_list,
+                ip_allow_list_guard,
+            )),
+        );
+    }
+
+    router.with_state(state)
 }
 
 /// 指标处理器 - 暴露Prometheus指标
-pub async fn metrics_handler() -> impl IntoResponse {
-    // 为了简化，我们返回一个简单的文本响应
-    // 注意：在实际生产环境中，需要正确配置metrics_exporter_prometheus
-    // 来支持通过HTTP端点暴露指标
-    let metrics_text = "# 性能指标暴露端点\n# 请确保正确配置了metrics_exporter_prometheus库\n";
+pub async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
     (
         StatusCode::OK,
-        axum::response::Response::builder()
-            .header("Content-Type", "text/plain; charset=utf-8")
-            .body(axum::body::Body::from(metrics_text))
-            .unwrap(),
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        state.metrics.render(),
     )
-        .into_response()
+}
+
+/// 查询次数响应头
+const DB_QUERIES_HEADER: &str = "x-db-queries";
+
+/// 请求级数据库查询计数器
+///
+/// 由 [`db_query_count_middleware`] 为每个请求创建并放入请求扩展，
+/// [`track_db_query`] 通过 task-local 找到当前请求的计数器，调用方无需传递
+#[derive(Debug, Clone, Default)]
+pub struct DbQueryCounter(Arc<AtomicUsize>);
+
+impl DbQueryCounter {
+    /// 本次请求已执行的查询次数
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn increment(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+tokio::task_local! {
+    static DB_QUERY_COUNTER: DbQueryCounter;
+}
+
+/// 数据库查询计数中间件
+///
+/// 启用时在响应头 `X-DB-Queries` 中返回本次请求经 [`track_db_query`] 执行的查询次数，
+/// 便于发现 N+1 查询或绕过缓存的处理器
+pub async fn db_query_count_middleware(
+    State(enabled): State<bool>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    if !enabled {
+        return next.run(req).await;
+    }
+
+    let counter = DbQueryCounter::default();
+    req.extensions_mut().insert(counter.clone());
+
+    let mut response = DB_QUERY_COUNTER.scope(counter.clone(), next.run(req)).await;
+    response
+        .headers_mut()
+        .insert(DB_QUERIES_HEADER, counter.get().into());
+    response
 }
 
 /// 数据库查询监控帮助函数
+///
+/// 记录查询次数和耗时指标，并累加当前请求的查询计数
 pub async fn track_db_query<T, F>(query_name: &str, f: F) -> std::result::Result<T, sqlx::Error>
 where
     F: std::future::Future<Output = std::result::Result<T, sqlx::Error>>,
 {
     // 增加查询计数
-    increment_counter!("db_queries_total", "query" => query_name.to_string());
+    count_db_query(query_name);
 
     // 记录查询时间
     let start = Instant::now();
+    let result = f.await;
+    record_db_query_duration(query_name, start, result.is_ok());
+    result
+}
 
-    match f.await {
-        Ok(result) => {
-            // 成功时记录指标
-            histogram!("db_query_duration_seconds", start.elapsed().as_secs_f64(),
-                "query" => query_name.to_string(),
-                "status" => "success"
-            );
-            Ok(result)
-        }
-        Err(e) => {
-            // 失败时记录指标
-            increment_counter!("db_queries_errors_total", "query" => query_name.to_string());
-            histogram!("db_query_duration_seconds", start.elapsed().as_secs_f64(),
-                "query" => query_name.to_string(),
-                "status" => "error"
-            );
-            Err(e)
+/// 记录一次数据库查询，并累加当前请求的查询计数
+///
+/// 需要在处理请求的任务中调用，派生任务中无法访问请求的计数器
+pub fn count_db_query(query_name: &str) {
+    increment_counter!("db_queries_total", "query" => query_name.to_string());
+    let _ = DB_QUERY_COUNTER.try_with(DbQueryCounter::increment);
+}
+
+/// 记录查询耗时，失败时同时累加错误计数
+pub fn record_db_query_duration(query_name: &str, start: Instant, success: bool) {
+    if !success {
+        increment_counter!("db_queries_errors_total", "query" => query_name.to_string());
+    }
+    histogram!("db_query_duration_seconds", start.elapsed().as_secs_f64(),
+        "query" => query_name.to_string(),
+        "status" => if success { "success" } else { "error" }
+    );
+}
+
+/// 测试共用的全局指标记录器，首次调用时安装，之后返回同一个句柄
+///
+/// 记录器是进程级的，并行测试共享同一份数据，断言时应使用各自独有的标签值
+#[cfg(test)]
+pub fn test_metrics_handle() -> PrometheusHandle {
+    static HANDLE: std::sync::OnceLock<PrometheusHandle> = std::sync::OnceLock::new();
+    HANDLE
+        .get_or_init(|| {
+            let recorder = PrometheusBuilder::new().build_recorder();
+            let handle = recorder.handle();
+            metrics::set_boxed_recorder(Box::new(recorder))
+                .expect("Failed to install test metrics recorder");
+            handle
+        })
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn metrics_label_routes_by_pattern_and_unmatched() {
+        let handle = test_metrics_handle();
+        let router = Router::new()
+            .route("/metrics-probe/:id", get(|| async { "ok" }))
+            .layer(middleware::from_fn(metrics_middleware));
+
+        for uri in [
+            "/metrics-probe/7",
+            "/metrics-probe/8",
+            "/metrics-probe-missing",
+        ] {
+            let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            router.clone().oneshot(req).await.unwrap();
         }
+
+        let rendered = handle.render();
+        let ok = rendered
+            .lines()
+            .find(|line| {
+                line.starts_with("http_requests_total{")
+                    && line.contains("route=\"/metrics-probe/:id\"")
+            })
+            .expect("缺少按路由模式标记的请求计数");
+        assert!(ok.ends_with(" 2"), "{}", ok);
+        assert!(!rendered.contains("/metrics-probe/7"));
+        assert!(rendered.lines().any(|line| {
+            line.starts_with("http_requests_errors_total{")
+                && line.contains("status=\"404\"")
+                && line.contains("route=\"unmatched\"")
+        }));
     }
 }
@@ -1,5 +1,15 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Json, Response};
 use serde::{Deserialize, Serialize};
 
+use crate::helpers::config::CONFIG;
+use crate::routes::htmx::{flash_alert, HxHeaders};
+
+/// 默认每页数量
+const DEFAULT_PER_PAGE: i64 = 12;
+/// 每页数量上限
+const MAX_PER_PAGE: i64 = 100;
+
 /// 分页查询参数结构体
 #[derive(Debug, Deserialize)]
 pub struct PageQuery {
@@ -34,12 +44,86 @@ impl PageQuery {
 
     /// 获取处理后的每页数量，确保在合理范围内
     pub fn get_per_page(&self) -> i64 {
-        self.per_page.unwrap_or(12).clamp(1, 100)
+        self.per_page
+            .unwrap_or(DEFAULT_PER_PAGE)
+            .clamp(1, MAX_PER_PAGE)
     }
 
-    /// 计算偏移量
+    /// 计算偏移量（极大的页码不会溢出）
     pub fn get_offset(&self) -> i64 {
-        (self.get_page() - 1) * self.get_per_page()
+        (self.get_page() - 1).saturating_mul(self.get_per_page())
+    }
+
+    /// 检查分页参数，应在读取 `get_page`/`get_per_page` 之前调用
+    ///
+    /// 默认总是通过，越界值由上面的方法静默修正；开启 `strict_pagination` 后，
+    /// 越界的 `page`（< 1）或 `per_page`（不在 1–100）返回 [`InvalidPagination`]（400）
+    pub fn validate(&self) -> Result<(), InvalidPagination> {
+        self.validate_with(CONFIG.strict_pagination)
+    }
+
+    /// 按给定模式检查分页参数，规则同 [`PageQuery::validate`]
+    fn validate_with(&self, strict: bool) -> Result<(), InvalidPagination> {
+        if !strict {
+            return Ok(());
+        }
+
+        if let Some(page) = self.page.filter(|&p| p < 1) {
+            return Err(InvalidPagination {
+                field: "page",
+                value: page,
+                message: "page 必须大于等于 1".to_string(),
+            });
+        }
+        if let Some(per_page) = self.per_page.filter(|p| !(1..=MAX_PER_PAGE).contains(p)) {
+            return Err(InvalidPagination {
+                field: "per_page",
+                value: per_page,
+                message: format!("per_page 必须在 1 到 {} 之间", MAX_PER_PAGE),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// 严格分页模式下越界的分页参数
+#[derive(Debug, Clone)]
+pub struct InvalidPagination {
+    pub field: &'static str,
+    pub value: i64,
+    pub message: String,
+}
+
+impl InvalidPagination {
+    /// 按请求类型构建 400 响应
+    ///
+    /// HTMX 默认不换入 4xx 响应，HTMX 请求返回换入 `#flash-messages` 的提示片段，其他客户端返回 JSON
+    pub fn into_response_for(self, hx: &HxHeaders) -> Response {
+        if hx.request {
+            flash_alert(
+                StatusCode::BAD_REQUEST,
+                "warning",
+                "exclamation-triangle",
+                &self.message,
+            )
+        } else {
+            self.into_response()
+        }
+    }
+}
+
+impl IntoResponse for InvalidPagination {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "invalid_pagination",
+                "message": self.message,
+                "field": self.field,
+                "value": self.value,
+            })),
+        )
+            .into_response()
     }
 }
 
@@ -79,8 +163,79 @@ pub fn create_pagination(page: i64, per_page: i64, total: i64) -> Pagination {
 /// # 返回值
 /// (start_item, end_item) - 开始和结束的项目索引
 pub fn calculate_display_range(page: i64, per_page: i64, current_count: usize) -> (i64, i64) {
-    let start_item = (page - 1) * per_page + 1;
-    let end_item = start_item - 1 + current_count as i64;
+    let start_item = (page - 1).saturating_mul(per_page).saturating_add(1);
+    let end_item = (start_item - 1).saturating_add(current_count as i64);
 
     (start_item, end_item)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(page: Option<i64>, per_page: Option<i64>) -> PageQuery {
+        PageQuery { page, per_page }
+    }
+
+    #[test]
+    fn lenient_mode_clamps_out_of_range_values() {
+        for (page, per_page, expected) in [
+            (Some(0), Some(0), (1, 1)),
+            (Some(-3), Some(500), (1, MAX_PER_PAGE)),
+            (None, None, (1, DEFAULT_PER_PAGE)),
+            (Some(4), Some(20), (4, 20)),
+        ] {
+            let q = query(page, per_page);
+            assert!(q.validate_with(false).is_ok());
+            assert_eq!((q.get_page(), q.get_per_page()), expected);
+        }
+        assert_eq!(query(Some(i64::MAX), Some(100)).get_offset(), i64::MAX);
+    }
+
+    #[test]
+    fn strict_mode_rejects_out_of_range_values() {
+        for (page, per_page, field, value) in [
+            (Some(0), None, "page", 0),
+            (Some(-1), Some(500), "page", -1),
+            (None, Some(0), "per_page", 0),
+            (
+                Some(2),
+                Some(MAX_PER_PAGE + 1),
+                "per_page",
+                MAX_PER_PAGE + 1,
+            ),
+        ] {
+            let err = query(page, per_page).validate_with(true).unwrap_err();
+            assert_eq!((err.field, err.value), (field, value));
+            assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+        }
+        assert!(query(None, None).validate_with(true).is_ok());
+        assert!(query(Some(1), Some(MAX_PER_PAGE))
+            .validate_with(true)
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn invalid_pagination_returns_flash_alert_for_htmx() {
+        let err = query(Some(0), None).validate_with(true).unwrap_err();
+        let hx = HxHeaders {
+            request: true,
+            ..HxHeaders::default()
+        };
+
+        let response = err.clone().into_response_for(&hx);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers()["hx-retarget"], "#flash-messages");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("alert-warning"));
+
+        let response = err.into_response_for(&HxHeaders::default());
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.headers()[axum::http::header::CONTENT_TYPE],
+            "application/json"
+        );
+    }
+}
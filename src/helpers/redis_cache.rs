@@ -0,0 +1,198 @@
+//! Redis 缓存后端
+//!
+//! 仅在启用 `redis` feature 时编译。数据经 [`CacheCodec`] 序列化后以 `SET EX` 写入，
+//! 失效即删除键；Redis 不可用时记录警告并按未命中处理，不影响请求
+
+use deadpool_redis::redis::AsyncCommands;
+use deadpool_redis::{Config, CreatePoolError, Pool, Runtime};
+use metrics::increment_counter;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use super::cache::{metric_namespace, split_patterns, Cache, CacheValue};
+
+/// 缓存数据的序列化方式
+pub trait CacheCodec: Send + Sync + 'static {
+    /// 序列化错误
+    type Error: std::fmt::Display;
+
+    fn encode<T: CacheValue>(value: &T) -> Result<Vec<u8>, Self::Error>;
+
+    fn decode<T: CacheValue>(bytes: &[u8]) -> Result<T, Self::Error>;
+}
+
+/// JSON 序列化（默认）
+pub struct JsonCodec;
+
+impl CacheCodec for JsonCodec {
+    type Error = serde_json::Error;
+
+    fn encode<T: CacheValue>(value: &T) -> Result<Vec<u8>, Self::Error> {
+        serde_json::to_vec(value)
+    }
+
+    fn decode<T: CacheValue>(bytes: &[u8]) -> Result<T, Self::Error> {
+        serde_json::from_slice(bytes)
+    }
+}
+
+/// Redis 缓存
+pub struct RedisCache<C: CacheCodec = JsonCodec> {
+    pool: Pool,
+    key_prefix: String,
+    default_duration: Duration,
+    _codec: PhantomData<C>,
+}
+
+impl<C: CacheCodec> RedisCache<C> {
+    /// 创建 Redis 缓存，连接在首次使用时建立
+    pub fn new(
+        url: &str,
+        key_prefix: &str,
+        default_duration: Duration,
+    ) -> Result<Self, CreatePoolError> {
+        let pool = Config::from_url(url).create_pool(Some(Runtime::Tokio1))?;
+
+        Ok(Self {
+            pool,
+            key_prefix: key_prefix.to_string(),
+            default_duration,
+            _codec: PhantomData,
+        })
+    }
+
+    fn prefixed(&self, key: &str) -> String {
+        format!("{}{}", self.key_prefix, key)
+    }
+}
+
+impl<C: CacheCodec> Cache for RedisCache<C> {
+    async fn get<T: CacheValue>(&self, key: &str) -> Option<T> {
+        let mut conn = match self.pool.get().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                tracing::warn!("⚠️  获取 Redis 连接失败: {}", e);
+                increment_counter!("cache_misses_total", "namespace" => metric_namespace(key), "reason" => "backend_error");
+                return None;
+            }
+        };
+
+        let bytes: Option<Vec<u8>> = match conn.get(self.prefixed(key)).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::warn!("⚠️  读取 Redis 缓存失败: {}", e);
+                increment_counter!("cache_misses_total", "namespace" => metric_namespace(key), "reason" => "backend_error");
+                return None;
+            }
+        };
+
+        let Some(bytes) = bytes else {
+            increment_counter!("cache_misses_total", "namespace" => metric_namespace(key), "reason" => "not_found");
+            return None;
+        };
+
+        match C::decode(&bytes) {
+            Ok(value) => {
+                increment_counter!("cache_hits_total", "namespace" => metric_namespace(key));
+                Some(value)
+            }
+            Err(e) => {
+                // 数据结构变更后旧缓存无法解析，按未命中处理，写入新值时会覆盖
+                tracing::warn!("⚠️  解析 Redis 缓存失败 {}: {}", key, e);
+                increment_counter!("cache_misses_total", "namespace" => metric_namespace(key), "reason" => "decode_error");
+                None
+            }
+        }
+    }
+
+    async fn set<T: CacheValue>(&self, key: &str, data: T, duration: Option<Duration>) {
+        let bytes = match C::encode(&data) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::warn!("⚠️  序列化缓存数据失败 {}: {}", key, e);
+                return;
+            }
+        };
+        // SET EX 要求至少 1 秒
+        let seconds = duration.unwrap_or(self.default_duration).as_secs().max(1);
+
+        let result = match self.pool.get().await {
+            Ok(mut conn) => conn
+                .set_ex::<_, _, ()>(self.prefixed(key), bytes, seconds as usize)
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+
+        match result {
+            Ok(()) => increment_counter!("cache_sets_total", "namespace" => metric_namespace(key)),
+            Err(e) => tracing::warn!("⚠️  写入 Redis 缓存失败 {}: {}", key, e),
+        }
+    }
+
+    async fn invalidate(&self, key: &str) {
+        let result = match self.pool.get().await {
+            Ok(mut conn) => conn
+                .del::<_, ()>(self.prefixed(key))
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+
+        match result {
+            Ok(()) => {
+                increment_counter!("cache_invalidations_total", "namespace" => metric_namespace(key))
+            }
+            Err(e) => tracing::warn!("⚠️  删除 Redis 缓存失败 {}: {}", key, e),
+        }
+    }
+
+    /// 前缀通过 `SCAN MATCH` 查找，与精确键一起用一条 `DEL` 删除；
+    /// 扫描和删除之间新写入的键不会被删除
+    async fn invalidate_patterns(&self, patterns: &[String]) {
+        let (keys, prefixes) = split_patterns(patterns);
+
+        let result = async {
+            let mut conn = self.pool.get().await.map_err(|e| e.to_string())?;
+            let mut targets: Vec<String> = keys.iter().map(|key| self.prefixed(key)).collect();
+            for prefix in &prefixes {
+                let pattern = format!("{}*", escape_glob(&self.prefixed(prefix)));
+                let mut iter = conn
+                    .scan_match::<_, String>(pattern)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                while let Some(key) = iter.next_item().await {
+                    targets.push(key);
+                }
+            }
+            if !targets.is_empty() {
+                conn.del::<_, ()>(targets)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            Ok::<_, String>(())
+        }
+        .await;
+
+        match result {
+            Ok(()) => {
+                for pattern in patterns {
+                    increment_counter!("cache_invalidations_total", "namespace" => metric_namespace(pattern));
+                }
+            }
+            Err(e) => tracing::warn!("⚠️  批量删除 Redis 缓存失败 {:?}: {}", patterns, e),
+        }
+    }
+}
+
+/// 转义 Redis glob 模式中的特殊字符
+fn escape_glob(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -0,0 +1,248 @@
+//! 请求 ID 模块
+//!
+//! 为每个请求分配 ID 并写入响应头，便于把日志与上游网关、链路追踪系统关联。
+//! 请求已携带合法 ID 时沿用，否则按配置格式生成
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use rand::RngCore;
+use std::sync::Arc;
+
+use crate::helpers::config::RequestIdConfig;
+use crate::helpers::security::generate_token;
+
+/// 沿用的请求 ID 最大长度
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// base62 格式的长度（约 131 位随机数，与 UUID v4 的 122 位相当）
+const BASE62_REQUEST_ID_LENGTH: usize = 22;
+
+/// 当前请求的 ID，存放在请求扩展中
+#[derive(Debug, Clone)]
+pub struct RequestId(pub Arc<str>);
+
+impl RequestId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// 请求 ID 生成格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestIdFormat {
+    /// UUID v4，如 `1b4e28ba-2fa1-4d2e-883f-0016d3cca427`
+    Uuid,
+    /// 22 位 base62
+    Base62,
+    /// W3C Trace Context，如 `00-<trace-id>-<parent-id>-01`
+    TraceParent,
+}
+
+impl RequestIdFormat {
+    /// 解析配置中的格式名，未知格式（已在配置验证中拒绝）回退到 UUID
+    pub fn parse(format: &str) -> Self {
+        match format.to_lowercase().as_str() {
+            "base62" => Self::Base62,
+            "traceparent" => Self::TraceParent,
+            _ => Self::Uuid,
+        }
+    }
+
+    /// 生成新的请求 ID
+    pub fn generate(self) -> String {
+        match self {
+            Self::Uuid => {
+                let mut bytes = [0u8; 16];
+                rand::thread_rng().fill_bytes(&mut bytes);
+                // 版本号 4，变体 RFC 4122
+                bytes[6] = (bytes[6] & 0x0f) | 0x40;
+                bytes[8] = (bytes[8] & 0x3f) | 0x80;
+                let hex = to_hex(&bytes);
+                format!(
+                    "{}-{}-{}-{}-{}",
+                    &hex[0..8],
+                    &hex[8..12],
+                    &hex[12..16],
+                    &hex[16..20],
+                    &hex[20..32]
+                )
+            }
+            Self::Base62 => generate_token(BASE62_REQUEST_ID_LENGTH),
+            Self::TraceParent => {
+                let mut trace_id = [0u8; 16];
+                let mut parent_id = [0u8; 8];
+                rand::thread_rng().fill_bytes(&mut trace_id);
+                rand::thread_rng().fill_bytes(&mut parent_id);
+                format!("00-{}-{}-01", to_hex(&trace_id), to_hex(&parent_id))
+            }
+        }
+    }
+}
+
+/// 请求 ID 中间件的状态
+#[derive(Clone)]
+pub struct RequestIdState {
+    pub header: HeaderName,
+    pub format: RequestIdFormat,
+}
+
+impl RequestIdState {
+    /// 从配置创建，头部名称已在配置验证中校验
+    pub fn from_config(config: &RequestIdConfig) -> Self {
+        Self {
+            header: HeaderName::from_bytes(config.header.to_lowercase().as_bytes())
+                .unwrap_or_else(|_| HeaderName::from_static("x-request-id")),
+            format: RequestIdFormat::parse(&config.format),
+        }
+    }
+}
+
+/// 请求 ID 中间件
+///
+/// 请求头中的 ID 通过 [`is_valid_request_id`] 校验后沿用，否则生成新 ID；
+/// ID 放入请求扩展（[`RequestId`]）并写入同名响应头
+pub async fn request_id_middleware(
+    State(state): State<RequestIdState>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let incoming = req
+        .headers()
+        .get(&state.header)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| is_valid_request_id(id, state.format));
+
+    let id = match incoming {
+        Some(id) => id.to_string(),
+        None => state.format.generate(),
+    };
+    let request_id = RequestId(Arc::from(id.as_str()));
+    req.extensions_mut().insert(request_id);
+
+    let mut response = next.run(req).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(state.header, value);
+    }
+    response
+}
+
+/// 请求中携带的 ID 是否可以沿用
+///
+/// 只接受长度不超过 128 的字母、数字、`-`、`_`、`.`，避免换行等字符注入日志；
+/// traceparent 格式还要求符合 `00-<32 位十六进制>-<16 位十六进制>-<2 位十六进制>`
+pub fn is_valid_request_id(id: &str, format: RequestIdFormat) -> bool {
+    if id.is_empty()
+        || id.len() > MAX_REQUEST_ID_LENGTH
+        || !id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+    {
+        return false;
+    }
+
+    if format != RequestIdFormat::TraceParent {
+        return true;
+    }
+
+    let parts: Vec<&str> = id.split('-').collect();
+    let is_hex = |s: &str, len: usize| {
+        s.len() == len && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    };
+    parts.len() == 4
+        && is_hex(parts[0], 2)
+        && parts[0] != "ff"
+        && is_hex(parts[1], 32)
+        && parts[1].bytes().any(|b| b != b'0')
+        && is_hex(parts[2], 16)
+        && parts[2].bytes().any(|b| b != b'0')
+        && is_hex(parts[3], 2)
+}
+
+/// 小写十六进制编码
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Extension, Router};
+    use tower::ServiceExt;
+
+    fn router(format: RequestIdFormat) -> Router {
+        let state = RequestIdState {
+            header: HeaderName::from_static("x-request-id"),
+            format,
+        };
+        Router::new()
+            .route(
+                "/",
+                get(|Extension(id): Extension<RequestId>| async move { id.as_str().to_string() }),
+            )
+            .layer(middleware::from_fn_with_state(state, request_id_middleware))
+    }
+
+    /// 返回响应头中的 ID 和处理器看到的 ID
+    async fn request_ids(format: RequestIdFormat, incoming: Option<&str>) -> (String, String) {
+        let mut req = Request::builder().uri("/");
+        if let Some(id) = incoming {
+            req = req.header("x-request-id", id);
+        }
+        let response = router(format)
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let echoed = response.headers()["x-request-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (echoed, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn reuses_valid_incoming_id() {
+        let (echoed, seen) = request_ids(RequestIdFormat::Uuid, Some("gateway-42.a_b")).await;
+        assert_eq!(echoed, "gateway-42.a_b");
+        assert_eq!(seen, echoed);
+
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let (echoed, _) = request_ids(RequestIdFormat::TraceParent, Some(traceparent)).await;
+        assert_eq!(echoed, traceparent);
+    }
+
+    #[tokio::test]
+    async fn replaces_invalid_or_oversized_id() {
+        let oversized = "a".repeat(MAX_REQUEST_ID_LENGTH + 1);
+        for incoming in ["bad id", "id\"<script>", "", oversized.as_str()] {
+            let (echoed, seen) = request_ids(RequestIdFormat::Base62, Some(incoming)).await;
+            assert_ne!(echoed, incoming);
+            assert_eq!(echoed.len(), BASE62_REQUEST_ID_LENGTH, "{}", echoed);
+            assert_eq!(seen, echoed);
+        }
+
+        // traceparent 格式要求结构合法
+        let (echoed, _) = request_ids(RequestIdFormat::TraceParent, Some("gateway-42")).await;
+        assert!(is_valid_request_id(&echoed, RequestIdFormat::TraceParent));
+        assert_ne!(echoed, "gateway-42");
+    }
+
+    #[tokio::test]
+    async fn generates_id_in_configured_format() {
+        let (uuid, _) = request_ids(RequestIdFormat::Uuid, None).await;
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(&uuid[14..15], "4");
+
+        let (traceparent, _) = request_ids(RequestIdFormat::TraceParent, None).await;
+        assert!(is_valid_request_id(
+            &traceparent,
+            RequestIdFormat::TraceParent
+        ));
+    }
+}
@@ -0,0 +1,207 @@
+//! 请求超时模块
+//!
+//! 全局超时由 [`request_timeout_middleware`] 统一施加；个别路由（导入、流式导出等）
+//! 在构建路由时挂上 [`route_timeout_middleware`] 覆盖全局值，优先级：路由覆盖 > 全局默认。
+//!
+//! 超时只计算到处理器返回响应（即响应头发出）为止，流式响应体的传输时间不受限制。
+//!
+//! 开发环境可用 [`dev_delay_middleware`] 注入人为延迟，观察加载指示器和超时提示
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use metrics::increment_counter;
+use rand::Rng;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::helpers::config::{DevDelayConfig, MAX_DEV_DELAY_MS};
+use crate::helpers::security::accepts_json;
+use crate::routes::htmx::{flash_alert, HxHeaders};
+
+/// 路由级超时覆盖
+#[derive(Debug, Clone, Copy)]
+pub enum RouteTimeout {
+    /// 使用该时长替代全局超时
+    After(Duration),
+    /// 不限时（SSE、流式响应等）
+    Disabled,
+}
+
+/// 全局超时中间件放入请求扩展的标记，路由覆盖生效时置位
+#[derive(Clone)]
+struct TimeoutOverridden(Arc<AtomicBool>);
+
+/// 全局请求超时中间件
+///
+/// 状态为 `None` 时不限时。到达全局超时时，如果匹配的路由已通过
+/// [`route_timeout_middleware`] 接管，则继续等待，由路由自己的超时决定
+pub async fn request_timeout_middleware(
+    State(timeout): State<Option<Duration>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let Some(timeout) = timeout else {
+        return next.run(req).await;
+    };
+
+    let overridden = Arc::new(AtomicBool::new(false));
+    req.extensions_mut()
+        .insert(TimeoutOverridden(overridden.clone()));
+    let path = req.uri().path().to_string();
+    let headers = req.headers().clone();
+
+    let response = next.run(req);
+    tokio::pin!(response);
+
+    tokio::select! {
+        response = &mut response => response,
+        () = tokio::time::sleep(timeout) => {
+            if overridden.load(Ordering::Acquire) {
+                response.await
+            } else {
+                timed_out_response(&headers, &path, timeout)
+            }
+        }
+    }
+}
+
+/// 路由级超时中间件，挂在单个路由上覆盖全局超时
+pub async fn route_timeout_middleware(
+    State(timeout): State<RouteTimeout>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if let Some(TimeoutOverridden(overridden)) = req.extensions().get() {
+        overridden.store(true, Ordering::Release);
+    }
+
+    match timeout {
+        RouteTimeout::Disabled => next.run(req).await,
+        RouteTimeout::After(timeout) => {
+            let path = req.uri().path().to_string();
+            let headers = req.headers().clone();
+            tokio::time::timeout(timeout, next.run(req))
+                .await
+                .unwrap_or_else(|_| timed_out_response(&headers, &path, timeout))
+        }
+    }
+}
+
+/// 指定单个请求延迟的查询参数
+const DEV_DELAY_PARAM: &str = "__delay";
+
+/// 人为延迟中间件（仅开发环境）
+///
+/// 状态为 `None` 时直接放行，非开发环境构建路由时传入 `None`（见 [`crate::helpers::config::AppConfig::dev_delay`]）。
+/// 开启 `allow_query_param` 且请求带 `?__delay=毫秒` 时按该值延迟（不超过 [`MAX_DEV_DELAY_MS`]），
+/// 否则在 `enabled` 时于 `min_ms..=max_ms` 间随机延迟。延迟发生在处理器之前，计入请求超时
+pub async fn dev_delay_middleware(
+    State(config): State<Option<DevDelayConfig>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(config) = config else {
+        return next.run(req).await;
+    };
+
+    let requested = req
+        .uri()
+        .query()
+        .filter(|_| config.allow_query_param)
+        .and_then(|query| {
+            query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| *key == DEV_DELAY_PARAM)
+                .and_then(|(_, value)| value.parse::<u64>().ok())
+        });
+    let delay_ms = match requested {
+        Some(ms) => ms.min(MAX_DEV_DELAY_MS),
+        None if config.enabled => rand::thread_rng().gen_range(config.min_ms..=config.max_ms),
+        None => 0,
+    };
+
+    if delay_ms > 0 {
+        tracing::debug!("🐢 注入延迟 {}ms: {}", delay_ms, req.uri().path());
+        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+    }
+    next.run(req).await
+}
+
+/// 超时响应（503），按请求类型返回提示片段、JSON 或纯文本
+fn timed_out_response(headers: &HeaderMap, path: &str, timeout: Duration) -> Response {
+    increment_counter!("http_requests_timed_out_total");
+    tracing::warn!("⏱️  请求处理超时（{:?}）: {}", timeout, path);
+
+    let message = "请求处理超时，请稍后重试";
+    if HxHeaders::from_headers(headers).request {
+        flash_alert(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "warning",
+            "hourglass-split",
+            message,
+        )
+    } else if accepts_json(headers) {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({
+                "error": "timeout",
+                "message": message,
+            })),
+        )
+            .into_response()
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, message).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::config::AppConfig;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use std::time::Instant;
+    use tower::ServiceExt;
+
+    /// 请求 `/?__delay=200` 的耗时
+    async fn elapsed_with(config: &AppConfig) -> Duration {
+        let router =
+            Router::new()
+                .route("/", get(|| async { "ok" }))
+                .layer(middleware::from_fn_with_state(
+                    config.dev_delay(),
+                    dev_delay_middleware,
+                ));
+        let req = Request::builder()
+            .uri("/?__delay=200")
+            .body(Body::empty())
+            .unwrap();
+        let start = Instant::now();
+        router.oneshot(req).await.unwrap();
+        start.elapsed()
+    }
+
+    #[tokio::test]
+    async fn delay_applied_only_in_development() {
+        let mut config = AppConfig::default();
+        config.dev_delay.allow_query_param = true;
+        assert!(elapsed_with(&config).await >= Duration::from_millis(200));
+
+        for environment in ["staging", "production"] {
+            config.environment = environment.to_string();
+            assert!(elapsed_with(&config).await < Duration::from_millis(200));
+        }
+    }
+
+    #[tokio::test]
+    async fn query_param_requires_opt_in() {
+        let config = AppConfig::default();
+        assert!(config.dev_delay().is_some());
+        assert!(elapsed_with(&config).await < Duration::from_millis(200));
+    }
+}
@@ -0,0 +1,123 @@
+//! 时间显示时区
+//!
+//! 数据库中的时间戳统一按 UTC 存储（SQLite `CURRENT_TIMESTAMP`），展示时转换到显示时区。
+//! 显示时区默认取 `ui.timezone`，请求带有 `tz` Cookie（IANA 时区名，由布局中的脚本按浏览器时区写入）时优先使用
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap},
+};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::convert::Infallible;
+
+use crate::helpers::config::CONFIG;
+
+/// 指定显示时区的 Cookie 名称
+pub const TIMEZONE_COOKIE: &str = "tz";
+
+/// 展示格式，如 `2024-05-01 08:30 CST`
+const DISPLAY_FORMAT: &str = "%Y-%m-%d %H:%M %Z";
+
+/// 请求使用的显示时区
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayTimezone(pub Tz);
+
+impl Default for DisplayTimezone {
+    /// 配置的默认时区（`ui.timezone`），配置在加载时已校验
+    fn default() -> Self {
+        Self(CONFIG.ui.timezone.parse().unwrap_or(Tz::UTC))
+    }
+}
+
+impl DisplayTimezone {
+    /// 读取 `tz` Cookie，缺失或不是有效的 IANA 时区名时使用配置的默认时区
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|cookies| cookies.split(';'))
+            .find_map(|pair| {
+                let (name, value) = pair.trim().split_once('=')?;
+                (name == TIMEZONE_COOKIE).then_some(value)
+            })
+            // 前端用 encodeURIComponent 写入时 `/` 会被编码
+            .and_then(|value| value.replace("%2F", "/").replace("%2f", "/").parse().ok())
+            .map(Self)
+            .unwrap_or_default()
+    }
+
+    /// 把 UTC 时间转换到显示时区
+    pub fn convert(self, utc: DateTime<Utc>) -> DateTime<Tz> {
+        utc.with_timezone(&self.0)
+    }
+
+    /// 格式化数据库中的 UTC 时间戳（`YYYY-MM-DD HH:MM:SS` 或 RFC 3339）
+    ///
+    /// 无法解析时原样返回，不影响页面渲染
+    pub fn format(self, stored: &str) -> String {
+        let utc = NaiveDateTime::parse_from_str(stored, "%Y-%m-%d %H:%M:%S")
+            .map(|naive| Utc.from_utc_datetime(&naive))
+            .or_else(|_| DateTime::parse_from_rfc3339(stored).map(|dt| dt.with_timezone(&Utc)));
+
+        match utc {
+            Ok(utc) => self.convert(utc).format(DISPLAY_FORMAT).to_string(),
+            Err(_) => stored.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for DisplayTimezone
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(&parts.headers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn format_converts_utc_to_display_zone() {
+        let stored = "2024-05-01 00:30:00";
+        assert_eq!(
+            DisplayTimezone(chrono_tz::Asia::Shanghai).format(stored),
+            "2024-05-01 08:30 CST"
+        );
+        assert_eq!(
+            DisplayTimezone(chrono_tz::America::New_York).format(stored),
+            "2024-04-30 20:30 EDT"
+        );
+        assert_eq!(
+            DisplayTimezone(chrono_tz::Asia::Shanghai).format("2024-05-01T00:30:00Z"),
+            "2024-05-01 08:30 CST"
+        );
+    }
+
+    #[test]
+    fn format_returns_unparseable_input_unchanged() {
+        assert_eq!(DisplayTimezone(Tz::UTC).format("yesterday"), "yesterday");
+    }
+
+    #[test]
+    fn cookie_overrides_default_zone() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("theme=dark; tz=Europe%2FBerlin"),
+        );
+        assert_eq!(
+            DisplayTimezone::from_headers(&headers),
+            DisplayTimezone(chrono_tz::Europe::Berlin)
+        );
+    }
+}
@@ -1,24 +1,49 @@
 mod db;
+mod domain;
 mod helpers;
 mod routes;
 mod services;
 
-use axum::{middleware, routing::get, Extension, Router};
-use helpers::config::CONFIG;
+use axum::{extract::ConnectInfo, http::HeaderValue, middleware, routing::get, Extension, Router};
+use axum_server::tls_rustls::RustlsConfig;
+use helpers::cache::{init_cache_backend, stop_cache_cleanup};
+use helpers::compression::CompressionPredicate;
+use helpers::config::{init_config, print_config_and_exit, AppConfig, TlsConfig};
 use helpers::monitoring::{create_monitoring_routes, init_metrics, AppState};
-use helpers::security::sanitize_log_message;
+use helpers::request_id::{request_id_middleware, RequestIdState};
+use helpers::security::{
+    admin_auth_guard, audit_middleware, header_size_guard, ip_allow_list_guard,
+    rate_limit_middleware, sanitize_log_message, security_headers_middleware,
+    set_sanitizer_fast_path_max_length, uri_length_guard, AuditState, HeaderLimits, IpAllowList,
+    RateLimiter, UriLengthLimits, UNIX_SOCKET_PEER,
+};
+use helpers::timeout::{request_timeout_middleware, route_timeout_middleware, RouteTimeout};
+use routes::registry::RouteRegistry;
+use services::audit::{run_audit_writer, AuditLogger};
 use services::cache_warmup::{start_cache_refresh_task, warmup_all_caches};
+use services::db_keepalive::start_db_keepalive_task;
+use services::pool_metrics::start_pool_metrics_task;
+use services::shutdown::ShutdownCoordinator;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::signal;
 use tower::ServiceBuilder;
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tower_http::{
+    compression::CompressionLayer,
+    cors::{AllowOrigin, CorsLayer},
+    trace::TraceLayer,
+};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
 async fn main() {
-    // 加载配置
-    let config = &CONFIG;
+    // `--print-config`：打印合并后的生效配置并退出
+    if std::env::args().skip(1).any(|arg| arg == "--print-config") {
+        print_config_and_exit();
+    }
+
+    // 加载配置（生产环境加载失败时直接退出）
+    let config = init_config();
 
     // 初始化日志
     tracing_subscriber::registry()
@@ -34,10 +59,33 @@ async fn main() {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    // 模板自检：用示例数据渲染所有模板
+    if config.validate_templates_on_startup {
+        let failures = services::template_check::validate_templates();
+        for failure in &failures {
+            tracing::error!("❌ 模板渲染失败: {}: {}", failure.template, failure.message);
+        }
+        if !failures.is_empty() && config.strict_template_validation {
+            tracing::error!(
+                "❌ {} 个模板渲染失败，已开启 strict_template_validation，退出",
+                failures.len()
+            );
+            std::process::exit(1);
+        }
+    }
+
+    set_sanitizer_fast_path_max_length(config.security.log_sanitizer_fast_path_max_length);
+
+    // 初始化缓存后端
+    if let Err(e) = init_cache_backend(&config.cache) {
+        tracing::error!("❌ 无法初始化缓存后端: {}", sanitize_log_message(&e));
+        std::process::exit(1);
+    }
+
     // 创建数据库连接池
     tracing::info!("🔧 正在连接数据库...");
-    let pool = match db::create_pool().await {
-        Ok(pool) => pool,
+    let db = match db::create_pool(&config.database).await {
+        Ok(db) => db,
         Err(e) => {
             tracing::error!(
                 "❌ 无法创建数据库连接池: {}",
@@ -46,9 +94,10 @@ async fn main() {
             std::process::exit(1);
         }
     };
+    let pool = db.write.clone();
 
     // 初始化数据库表和运行迁移
-    if let Err(e) = db::run_migrations(&pool).await {
+    if let Err(e) = db::run_migrations(&pool, &config.database).await {
         tracing::error!(
             "❌ 数据库迁移失败: {}",
             sanitize_log_message(&e.to_string())
@@ -56,112 +105,487 @@ async fn main() {
         std::process::exit(1);
     }
 
-    // 插入示例数据
-    if let Err(e) = db::seed_data(&pool).await {
-        tracing::warn!(
-            "⚠️  示例数据插入失败: {}",
-            sanitize_log_message(&e.to_string())
-        );
+    db::detect_returning_support(&pool, config.database.use_returning).await;
+
+    // 插入示例数据（仅在配置允许时，生产环境以空库启动）
+    if config.should_seed() {
+        tracing::info!("🌱 正在插入示例数据...");
+        if let Err(e) = db::seed_data(&pool).await {
+            tracing::warn!(
+                "⚠️  示例数据插入失败: {}",
+                sanitize_log_message(&e.to_string())
+            );
+        }
+    } else {
+        tracing::info!("⏭️  跳过示例数据插入（seed_on_startup 未启用）");
     }
 
     tracing::info!("✅ 数据库初始化完成");
 
-    // 执行缓存预热
-    tracing::info!("🔥 开始缓存预热...");
-    if let Err(e) = warmup_all_caches(&pool).await {
-        tracing::warn!(
-            "⚠️  缓存预热部分失败: {}",
-            sanitize_log_message(&e.to_string())
-        );
-    }
+    // 创建关闭协调器，所有后台任务都在此登记
+    let shutdown = Arc::new(ShutdownCoordinator::new());
+
+    // 初始化监控指标
+    let metrics_handle = init_metrics(&config.metrics, &shutdown);
+
+    // 创建应用状态
+    let app_state = AppState::new(db.clone(), Arc::new((*config).clone()), metrics_handle);
+
+    // 后台执行缓存预热，完成后 /health/ready 才报告就绪
+    let warmup_state = app_state.clone();
+    shutdown.spawn("cache-warmup", move |token| async move {
+        tracing::info!("🔥 开始缓存预热...");
+        tokio::select! {
+            result = warmup_all_caches(&warmup_state.db.read) => {
+                if let Err(e) = result {
+                    tracing::warn!(
+                        "⚠️  缓存预热部分失败: {}",
+                        sanitize_log_message(&e.to_string())
+                    );
+                }
+                warmup_state.mark_warmed_up();
+                tracing::info!("✅ 缓存预热完成，实例已就绪");
+            }
+            _ = token.cancelled() => {}
+        }
+    });
 
     // 启动定期缓存刷新任务（非阻塞）
+    let refresh_state = app_state.clone();
+    shutdown.spawn("cache-refresh", move |token| {
+        start_cache_refresh_task(refresh_state, token)
+    });
+
+    // 空闲连接保活（默认关闭，SQLite 连接不会被动断开）
+    // 保活数量取连接池实际的 min_connections（由 connect_pool 设置），与就绪检查一致
+    if let Some(seconds) = config.database.keepalive_interval_seconds {
+        let mut keepalive_pools = vec![("db-keepalive", db.write.clone())];
+        if config.database.read_url.is_some() {
+            keepalive_pools.push(("db-keepalive-read", db.read.clone()));
+        }
+        for (name, pool) in keepalive_pools {
+            let min_connections = pool.options().get_min_connections();
+            shutdown.spawn(name, move |token| {
+                start_db_keepalive_task(pool, Duration::from_secs(seconds), min_connections, token)
+            });
+        }
+    }
+
+    // 连接池指标：配置了只读副本时主库和副本分别上报
+    let mut metric_pools = vec![("write", db.write.clone())];
+    if config.database.read_url.is_some() {
+        metric_pools.push(("read", db.read.clone()));
+    }
+    shutdown.spawn("pool-metrics", move |token| {
+        start_pool_metrics_task(metric_pools, token)
+    });
+
+    // 缓存清理线程在关闭时停止
+    shutdown.spawn("cache-cleanup", |token| async move {
+        token.cancelled().await;
+        if let Err(e) = tokio::task::spawn_blocking(stop_cache_cleanup).await {
+            tracing::warn!("⚠️  停止缓存清理线程失败: {}", e);
+        }
+    });
+
+    // 审计记录由后台任务异步写入，关闭时写完剩余记录
+    let (audit_logger, audit_receiver) = AuditLogger::new();
     let pool_clone = pool.clone();
-    tokio::spawn(async move {
-        start_cache_refresh_task(pool_clone).await;
+    shutdown.spawn("audit-writer", move |token| {
+        run_audit_writer(pool_clone, audit_receiver, token)
     });
+    let admin_token: Option<Arc<str>> = config.security.admin_token.as_deref().map(Arc::from);
+    let audit_state = AuditState {
+        enabled: config.security.audit_log_enabled,
+        logger: audit_logger,
+        admin_token: admin_token.clone(),
+    };
 
-    // 初始化监控指标
-    init_metrics();
+    let app = build_router(config, db, app_state.clone(), audit_state);
 
-    // 创建应用状态
-    let app_state = AppState::new(pool.clone(), Arc::new((*config).clone()));
+    tracing::info!("📱 Navigate pages without refresh!");
+    tracing::info!("💾 SQLite database: app.db");
+    tracing::info!("🌐 环境: {}", config.environment);
+
+    // 启动服务器，支持优雅关闭；配置了 Unix socket 时替代 TCP 监听
+    match (config.server.unix_socket.as_deref(), config.tls.as_ref()) {
+        #[cfg(unix)]
+        (Some(socket_path), _) => {
+            serve_unix_socket(
+                socket_path,
+                app,
+                Duration::from_secs(config.server.graceful_shutdown_timeout_seconds),
+            )
+            .await
+        }
+        (_, Some(tls)) => {
+            serve_tls(
+                &config.server.server_addr(),
+                tls,
+                app,
+                shutdown.clone(),
+                Duration::from_secs(config.server.graceful_shutdown_timeout_seconds),
+            )
+            .await
+        }
+        _ => serve_tcp(&config.server.server_addr(), app).await,
+    }
 
-    // 创建监控路由
-    let monitoring_routes = create_monitoring_routes(app_state.clone());
+    // 服务已返回、处理中的请求都已完成，再按顺序停止后台任务，超时后强制中止
+    shutdown
+        .shutdown(Duration::from_secs(
+            config.server.graceful_shutdown_timeout_seconds,
+        ))
+        .await;
+    tracing::info!("✅ 所有后台任务已停止");
 
-    // 配置中间件
-    let cors_origins: Vec<_> = config
-        .security
-        .cors_allow_origins
-        .iter()
-        .filter_map(|origin| origin.parse().ok())
-        .collect();
+    // 后台任务的最后一批指标也已记录，输出最终快照
+    app_state.flush_metrics();
+}
 
-    let middleware_stack = ServiceBuilder::new()
-        // 跟踪请求
-        .layer(middleware::from_fn(helpers::monitoring::metrics_middleware))
-        .layer(TraceLayer::new_for_http())
-        // CORS 配置
+/// 组装应用路由
+///
+/// 路由分为两部分，各自使用独立的中间件栈，合并后再套上公共的外层中间件：
+/// - 公开路由（官网、`/app`、`/block`、`/api`、静态资源）：限流、审计、CORS、HTML 压缩、br 压缩等完整中间件
+/// - 内部路由（`/health`、`/metrics`、`/admin`）：供探针、抓取器和运维使用，不经过限流、CORS
+///   和面向浏览器的处理；`/metrics`、`/admin` 限内部网段访问，管理接口另外要求管理令牌
+///
+/// 外层（两部分共用）只包含请求 ID、URI 长度限制、请求指标、访问日志和链路追踪。
+/// 新增路由时按调用方归入对应部分：浏览器访问的放公开路由，运维系统访问的放内部路由
+fn build_router(
+    config: &AppConfig,
+    db: db::Db,
+    app_state: AppState,
+    audit_state: AuditState,
+) -> Router {
+    let import_timeout = config
+        .server
+        .import_timeout()
+        .map_or(RouteTimeout::Disabled, RouteTimeout::After);
+
+    // 配置中间件（通配来源已在配置验证中拒绝，与 allow_credentials 兼容）
+    let cors_policy = config.cors_policy();
+    tracing::info!(
+        "🌐 CORS 来源: {:?}{}",
+        cors_policy.origins,
+        if cors_policy.allow_local_any_port {
+            "，以及任意端口的本地来源"
+        } else {
+            ""
+        }
+    );
+    let cors_origins = AllowOrigin::predicate(move |origin: &HeaderValue, _| {
+        origin
+            .to_str()
+            .is_ok_and(|origin| cors_policy.allows(origin))
+    });
+
+    let mut cors_layer = CorsLayer::new()
+        .allow_origin(cors_origins)
+        .allow_methods([
+            axum::http::Method::GET,
+            axum::http::Method::POST,
+            axum::http::Method::PUT,
+            axum::http::Method::DELETE,
+        ])
+        .allow_headers([axum::http::header::CONTENT_TYPE, axum::http::header::ACCEPT])
+        .allow_credentials(true);
+    // 缓存预检结果，减少 JSON 接口的 OPTIONS 往返
+    if config.security.cors_max_age_seconds > 0 {
+        cors_layer = cors_layer.max_age(Duration::from_secs(config.security.cors_max_age_seconds));
+    }
+
+    let uri_limits = UriLengthLimits {
+        max_uri_length: config.security.max_uri_length,
+        max_query_length: config.security.max_query_length,
+    };
+    let header_limits = HeaderLimits {
+        max_header_bytes: config.security.max_header_bytes,
+        max_header_count: config.security.max_header_count,
+    };
+    let noindex_prefixes = Arc::<[String]>::from(config.server.noindex_prefixes.clone());
+    let admin_token = audit_state.admin_token.clone();
+
+    // 公开路由的中间件栈
+    let public_stack = ServiceBuilder::new()
+        // CORS 配置：在限流之外，429 响应也带 CORS 头，跨域客户端才能读到状态和 Retry-After
+        .layer(cors_layer)
+        // 写操作限流
+        .layer(middleware::from_fn_with_state(
+            Arc::new(RateLimiter::new(config.security.rate_limit_per_minute)),
+            rate_limit_middleware,
+        ))
+        // 审计写操作
+        .layer(middleware::from_fn_with_state(
+            audit_state,
+            audit_middleware,
+        ))
+        // 片段和接口地址禁止搜索引擎索引
+        .layer(middleware::from_fn_with_state(
+            noindex_prefixes.clone(),
+            routes::robots_tag_middleware,
+        ))
+        // 返回本次请求的数据库查询次数（默认仅开发环境）
+        .layer(middleware::from_fn_with_state(
+            config.should_expose_query_count(),
+            helpers::monitoring::db_query_count_middleware,
+        ))
+        // br 压缩，跳过小响应和已压缩的类型；须在 HTML 空白压缩之外，拿到处理后的响应体
         .layer(
-            CorsLayer::new()
-                .allow_origin(cors_origins)
-                .allow_methods([
-                    axum::http::Method::GET,
-                    axum::http::Method::POST,
-                    axum::http::Method::PUT,
-                    axum::http::Method::DELETE,
-                ])
-                .allow_headers([axum::http::header::CONTENT_TYPE, axum::http::header::ACCEPT])
-                .allow_credentials(true),
-        )
-        // 数据库连接池
-        .layer(Extension(pool));
-
-    // 注意：tower-http 0.6版本的compression API已更改，如需添加压缩功能，
-    // 请使用以下方式导入和配置：
-    // use tower_http::compression::CompressionLayer;
-    // .layer(CompressionLayer::new())
-
-    let app = Router::new()
+            CompressionLayer::new()
+                .compress_when(CompressionPredicate::from_config(&config.compression)),
+        )
+        // 压缩 HTML 响应（默认仅生产环境）
+        .layer(middleware::from_fn_with_state(
+            config.should_minify_html(),
+            routes::minify_html_middleware,
+        ))
+        // 人为延迟（仅开发环境），调试加载状态和超时
+        .layer(middleware::from_fn_with_state(
+            config.dev_delay(),
+            helpers::timeout::dev_delay_middleware,
+        ))
+        // 数据库连接池：处理器按操作选择 `Db::write`（主库）或 `Db::read`（副本）
+        .layer(Extension(db.clone()));
+
+    // 登记添加的路由，供开发环境的 /admin/routes 列出
+    let mut registry = RouteRegistry::default();
+
+    let public_routes = Router::new()
         // 官网首页
-        .route("/", get(routes::official::index))
+        .route(registry.add("GET", "/"), get(routes::official::index))
         // /app 开头 - 返回完整 HTML 页面
-        .route("/app", get(routes::pages::index))
-        .route("/app/todos", get(routes::pages::todos_page))
-        .route("/app/users", get(routes::pages::users_page))
+        .route(registry.add("GET", "/app"), get(routes::pages::index))
+        .route(
+            registry.add("GET", "/app/todos"),
+            get(routes::pages::todos_page),
+        )
+        .route(
+            registry.add("GET", "/app/users"),
+            get(routes::pages::users_page),
+        )
         // /block 开头 - 返回 HTML 片段
-        .route("/block/home", get(routes::pages::page_home))
-        .route("/block/todos", get(routes::pages::page_todos))
-        .route("/block/users", get(routes::pages::page_users))
-        .route("/block/todos/create-form", get(routes::todos::create_form))
-        .route("/block/users/search", get(routes::users::search))
-        .route("/block/users/:id/detail", get(routes::users::detail))
-        .route("/block/modal/example", get(routes::modal::example))
+        .route(
+            registry.add("GET", "/block/home"),
+            get(routes::pages::page_home),
+        )
+        .route(
+            registry.add("GET", "/block/todos"),
+            get(routes::pages::page_todos),
+        )
+        .route(
+            registry.add("GET", "/block/users"),
+            get(routes::pages::page_users),
+        )
+        .route(
+            registry.add("GET", "/block/todos/create-form"),
+            get(routes::todos::create_form),
+        )
+        .route(
+            registry.add("GET", "/block/todos/skeleton"),
+            get(routes::todos::skeleton),
+        )
+        .route(
+            registry.add("GET", "/block/todos/search"),
+            get(routes::todos::search),
+        )
+        .route(
+            registry.add("GET", "/block/users/skeleton"),
+            get(routes::users::skeleton),
+        )
+        .route(
+            registry.add("GET", "/block/users/search"),
+            get(routes::users::search),
+        )
+        .route(
+            registry.add("GET", "/block/users/:id/detail"),
+            get(routes::users::detail),
+        )
+        .route(
+            registry.add("GET", "/block/users/details"),
+            get(routes::users::details),
+        )
+        .route(
+            registry.add("GET", "/block/modal/example"),
+            get(routes::modal::example),
+        )
         // /api 开头 - 返回 JSON 或执行操作后返回 HTML 片段
-        .route("/api/todos", axum::routing::post(routes::todos::create))
         .route(
-            "/api/todos/:id",
+            registry.add("POST", "/api/todos"),
+            axum::routing::post(routes::todos::create),
+        )
+        .route(
+            registry.add("GET", "/api/todos/stats"),
+            get(routes::todos::stats),
+        )
+        .route(
+            registry.add("DELETE", "/api/todos/:id"),
             axum::routing::delete(routes::todos::delete),
         )
         .route(
-            "/api/todos/:id/toggle",
+            registry.add("PUT", "/api/todos/:id/toggle"),
             axum::routing::put(routes::todos::toggle),
         )
+        // 导入需要逐行校验写入，按 `server.import_timeout_seconds` 放宽超时
+        .route(
+            registry.add("POST", "/api/todos/import"),
+            axum::routing::post(routes::todos::import).layer(middleware::from_fn_with_state(
+                import_timeout,
+                route_timeout_middleware,
+            )),
+        )
+        .route(
+            registry.add("POST", "/api/users/import"),
+            axum::routing::post(routes::users::import).layer(middleware::from_fn_with_state(
+                import_timeout,
+                route_timeout_middleware,
+            )),
+        )
         // 静态文件（嵌入式）
-        .route("/static/*path", get(routes::static_assets::static_handler))
-        // 监控路由
-        .merge(monitoring_routes)
-        // 应用中间件栈
-        .layer(middleware_stack);
+        .route(
+            registry.add("GET", "/static/*path"),
+            get(routes::static_assets::static_handler),
+        )
+        .route(
+            registry.add("GET", "/favicon.ico"),
+            get(routes::static_assets::favicon_handler),
+        )
+        .layer(public_stack);
+
+    // 监控指标和管理接口只允许内部网段访问
+    let internal_allow_list = IpAllowList::new(
+        config.security.internal_allow_networks(),
+        config.security.trusted_proxy_networks(),
+    );
+
+    // 管理接口，需要内部网段 + 管理令牌
+    let admin_routes = Router::new()
+        .route(
+            registry.add("GET", "/admin/audit"),
+            get(routes::admin::audit),
+        )
+        // 导出全部用户的姓名和邮箱；流式响应，不限时
+        .route(
+            registry.add("GET", "/admin/users/export"),
+            get(routes::users::export).layer(middleware::from_fn_with_state(
+                RouteTimeout::Disabled,
+                route_timeout_middleware,
+            )),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            admin_token,
+            admin_auth_guard,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            internal_allow_list.clone(),
+            ip_allow_list_guard,
+        ));
+
+    // 内部路由：健康检查、监控指标和管理接口
+    let mut internal_routes =
+        create_monitoring_routes(app_state, internal_allow_list.clone(), &mut registry)
+            .merge(admin_routes);
 
+    // 开发环境列出已注册的路由，只限内部网段（不要求管理令牌），须在所有路由登记之后添加
+    if config.is_development() {
+        registry.add("GET", "/admin/routes");
+        internal_routes = internal_routes.route(
+            "/admin/routes",
+            get(routes::admin::routes)
+                .layer(Extension(Arc::new(registry)))
+                .route_layer(middleware::from_fn_with_state(
+                    internal_allow_list,
+                    ip_allow_list_guard,
+                )),
+        );
+    }
+
+    let internal_routes = internal_routes.layer(
+        ServiceBuilder::new()
+            .layer(middleware::from_fn_with_state(
+                noindex_prefixes,
+                routes::robots_tag_middleware,
+            ))
+            .layer(Extension(db)),
+    );
+
+    let app = public_routes.merge(internal_routes);
+
+    // 部署在子路径下时整体挂载到路径前缀
+    let base_path = &config.server.base_path;
+    let app = if base_path.is_empty() {
+        app
+    } else {
+        tracing::info!("📁 路径前缀: {}", base_path);
+        Router::new()
+            .nest(base_path, app)
+            // 反向代理通常把子路径根地址规范化为带斜杠的形式
+            .route(&format!("{}/", base_path), get(routes::official::index))
+    };
+
+    // 两部分共用的外层中间件
+    let edge_stack = ServiceBuilder::new()
+        // 最外层：分配请求 ID，被拒绝的请求也能关联日志
+        .layer(middleware::from_fn_with_state(
+            RequestIdState::from_config(&config.request_id),
+            request_id_middleware,
+        ))
+        // 拒绝超长 URI 和过大的请求头，保护所有处理器
+        .layer(middleware::from_fn_with_state(uri_limits, uri_length_guard))
+        .layer(middleware::from_fn_with_state(
+            header_limits,
+            header_size_guard,
+        ))
+        // 安全响应头（HSTS 仅在启用 TLS 时发送）
+        .layer(middleware::from_fn_with_state(
+            config
+                .hsts_header_value()
+                .and_then(|value| HeaderValue::from_str(&value).ok()),
+            security_headers_middleware,
+        ))
+        // 跟踪请求
+        .layer(middleware::from_fn(helpers::monitoring::metrics_middleware))
+        // 访问日志（按配置采样）
+        .layer(middleware::from_fn_with_state(
+            config.access_log_sample_rate,
+            helpers::monitoring::access_log_middleware,
+        ))
+        // 记录 HX-* 等请求/响应头（仅开发环境，debug 级别）
+        .layer(middleware::from_fn_with_state(
+            config
+                .should_log_headers()
+                .then(|| Arc::<[String]>::from(config.log_headers.clone())),
+            helpers::monitoring::header_log_middleware,
+        ))
+        // 链路追踪：span 按路由模式命名，响应后记录状态码和耗时
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(helpers::monitoring::make_request_span)
+                .on_response(helpers::monitoring::record_request_span_response),
+        )
+        // 全局请求超时（路由可单独覆盖）
+        .layer(middleware::from_fn_with_state(
+            config.server.request_timeout(),
+            request_timeout_middleware,
+        ));
+    let app = app.layer(edge_stack);
+
+    // 405 的 Allow 头在路由匹配后才生成，逐路由的中间件看不到，
+    // 因此把整个应用作为回退服务，在最外层补充 405 响应体
+    Router::new()
+        .fallback_service(app)
+        .layer(middleware::from_fn(routes::method_not_allowed_middleware))
+}
+
+/// 通过 TCP 监听提供服务（默认方式）
+async fn serve_tcp(addr: &str, app: Router) {
     // 绑定地址
-    let listener = match tokio::net::TcpListener::bind(config.server.server_addr()).await {
+    let listener = match tokio::net::TcpListener::bind(addr).await {
         Ok(listener) => listener,
         Err(e) => {
             tracing::error!(
                 "❌ 无法绑定到地址 {}: {}",
-                config.server.server_addr(),
+                addr,
                 sanitize_log_message(&e.to_string())
             );
             std::process::exit(1);
@@ -172,15 +596,87 @@ async fn main() {
         "🚀 SPA Server listening on http://{}",
         listener.local_addr().unwrap()
     );
-    tracing::info!("📱 Navigate pages without refresh!");
-    tracing::info!("💾 SQLite database: app.db");
-    tracing::info!("🌐 环境: {}", config.environment);
 
-    // 启动服务器，支持优雅关闭
-    match axum::serve(listener, app.into_make_service())
-        .with_graceful_shutdown(shutdown_signal(
-            config.server.graceful_shutdown_timeout_seconds,
-        ))
+    match axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await
+    {
+        Ok(_) => tracing::info!("✅ 服务器已正常关闭"),
+        Err(e) => tracing::error!("❌ 服务器错误: {}", sanitize_log_message(&e.to_string())),
+    }
+}
+
+/// 通过内置 TLS 提供 HTTPS 服务
+///
+/// 证书和私钥在启动时解析，失败则退出；Unix 平台上收到 SIGHUP 时重新加载证书，
+/// 新连接使用新证书，已建立的连接不受影响。加载失败时保留旧证书继续服务
+async fn serve_tls(
+    addr: &str,
+    tls: &TlsConfig,
+    app: Router,
+    shutdown: Arc<ShutdownCoordinator>,
+    graceful_timeout: Duration,
+) {
+    // 进程内只需安装一次加密后端，重复安装会返回错误，忽略即可
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    let rustls_config = match RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path).await {
+        Ok(rustls_config) => rustls_config,
+        Err(e) => {
+            tracing::error!(
+                "❌ 无法加载 TLS 证书 ({}, {}): {}",
+                tls.cert_path,
+                tls.key_path,
+                sanitize_log_message(&e.to_string())
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let listener = match std::net::TcpListener::bind(addr)
+        .and_then(|listener| listener.set_nonblocking(true).map(|()| listener))
+    {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!(
+                "❌ 无法绑定到地址 {}: {}",
+                addr,
+                sanitize_log_message(&e.to_string())
+            );
+            std::process::exit(1);
+        }
+    };
+
+    tracing::info!(
+        "🔒 SPA Server listening on https://{}",
+        listener.local_addr().unwrap()
+    );
+
+    #[cfg(unix)]
+    {
+        let rustls_config = rustls_config.clone();
+        let tls = tls.clone();
+        shutdown.spawn("tls-reload", move |token| {
+            reload_tls_on_sighup(rustls_config, tls, token)
+        });
+    }
+
+    // 收到关闭信号后停止接收新连接，等待已有连接处理完
+    let handle = axum_server::Handle::new();
+    tokio::spawn({
+        let handle = handle.clone();
+        async move {
+            shutdown_signal().await;
+            handle.graceful_shutdown(Some(graceful_timeout));
+        }
+    });
+
+    match axum_server::from_tcp_rustls(listener, rustls_config)
+        .handle(handle)
+        .serve(app.into_make_service_with_connect_info::<std::net::SocketAddr>())
         .await
     {
         Ok(_) => tracing::info!("✅ 服务器已正常关闭"),
@@ -188,8 +684,131 @@ async fn main() {
     }
 }
 
+/// 收到 SIGHUP 时重新加载 TLS 证书，直到收到关闭信号
+#[cfg(unix)]
+async fn reload_tls_on_sighup(
+    rustls_config: RustlsConfig,
+    tls: TlsConfig,
+    token: tokio_util::sync::CancellationToken,
+) {
+    let mut hangup = match signal::unix::signal(signal::unix::SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            tracing::warn!("⚠️  无法监听 SIGHUP，TLS 证书不会自动重新加载: {}", e);
+            return;
+        }
+    };
+
+    loop {
+        tokio::select! {
+            _ = hangup.recv() => {}
+            () = token.cancelled() => return,
+        }
+
+        match rustls_config
+            .reload_from_pem_file(&tls.cert_path, &tls.key_path)
+            .await
+        {
+            Ok(()) => tracing::info!("🔒 已重新加载 TLS 证书: {}", tls.cert_path),
+            Err(e) => tracing::error!(
+                "❌ 重新加载 TLS 证书失败，继续使用旧证书: {}",
+                sanitize_log_message(&e.to_string())
+            ),
+        }
+    }
+}
+
+/// 通过 Unix domain socket 提供服务
+///
+/// 适用于与反向代理同机部署的场景。socket 文件在启动时清理残留、关闭时删除。
+/// 此模式下没有对端 IP，对端统一记为 [`UNIX_SOCKET_PEER`]，代理传递的请求头
+/// 只在 `trusted_proxies` 包含该地址时才被采信。关闭时最多等待 `graceful_timeout`
+#[cfg(unix)]
+async fn serve_unix_socket(socket_path: &str, app: Router, graceful_timeout: Duration) {
+    use hyper_util::rt::{TokioExecutor, TokioIo};
+    use hyper_util::server::conn::auto::Builder;
+    use hyper_util::server::graceful::GracefulShutdown;
+    use hyper_util::service::TowerToHyperService;
+
+    // 清理上次异常退出残留的 socket 文件
+    if std::path::Path::new(socket_path).exists() {
+        if let Err(e) = std::fs::remove_file(socket_path) {
+            tracing::error!("❌ 无法删除残留的 socket 文件 {}: {}", socket_path, e);
+            std::process::exit(1);
+        }
+    }
+
+    let listener = match tokio::net::UnixListener::bind(socket_path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!(
+                "❌ 无法绑定到 Unix socket {}: {}",
+                socket_path,
+                sanitize_log_message(&e.to_string())
+            );
+            std::process::exit(1);
+        }
+    };
+
+    tracing::info!("🚀 SPA Server listening on unix:{}", socket_path);
+
+    let app = app.layer(Extension(ConnectInfo(UNIX_SOCKET_PEER)));
+    let builder = Builder::new(TokioExecutor::new());
+    let graceful = GracefulShutdown::new();
+    let signal = shutdown_signal();
+    tokio::pin!(signal);
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let stream = match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        tracing::warn!("⚠️  接受 Unix socket 连接失败: {}", e);
+                        continue;
+                    }
+                };
+
+                let service = TowerToHyperService::new(app.clone());
+                let connection = builder
+                    .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                    .into_owned();
+                let connection = graceful.watch(connection);
+
+                tokio::spawn(async move {
+                    if let Err(e) = connection.await {
+                        tracing::debug!("Unix socket 连接异常结束: {}", e);
+                    }
+                });
+            }
+            () = &mut signal => break,
+        }
+    }
+
+    // 停止接收新连接，等待已有连接处理完毕，超时后不再等待
+    drop(listener);
+    if tokio::time::timeout(graceful_timeout, graceful.shutdown())
+        .await
+        .is_err()
+    {
+        tracing::warn!(
+            "⚠️  等待 Unix socket 连接关闭超时（{:?}），剩余连接将被中断",
+            graceful_timeout
+        );
+    }
+
+    if let Err(e) = std::fs::remove_file(socket_path) {
+        tracing::warn!("⚠️  删除 socket 文件失败 {}: {}", socket_path, e);
+    }
+    tracing::info!("✅ 服务器已正常关闭");
+}
+
 /// 处理优雅关闭信号
-async fn shutdown_signal(timeout_seconds: u64) {
+///
+/// 收到信号后返回，HTTP 服务随之停止接收新连接并处理完已有请求。这里不通知后台任务：
+/// 处理中的请求仍可能依赖它们（如审计写入），由 `main` 在服务返回后调用
+/// [`ShutdownCoordinator::shutdown`] 统一停止
+async fn shutdown_signal() {
     // 等待中断信号
     let ctrl_c = async {
         signal::ctrl_c().await.expect("无法捕获中断信号");
@@ -210,12 +829,265 @@ async fn shutdown_signal(timeout_seconds: u64) {
 
     // 等待任一信号
     tokio::select! {
-        () = ctrl_c => tracing::info!("收到 CTRL+C 信号，正在关闭服务器..."),
-        () = terminate => tracing::info!("收到终止信号，正在关闭服务器..."),
+        () = ctrl_c => {},
+        () = terminate => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        extract::{ConnectInfo, Request},
+        http::{request::Builder, StatusCode},
+        response::Response,
+    };
+    use metrics_exporter_prometheus::PrometheusBuilder;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::net::SocketAddr;
+    use tower::ServiceExt;
+
+    const ADMIN_TOKEN: &str = "test-admin-token-0123456789";
+
+    /// 按给定配置组装完整路由，数据库为已迁移的单连接内存库
+    async fn test_router(config: &AppConfig) -> Router {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        db::run_migrations(&pool, &config.database).await.unwrap();
+        sqlx::query("INSERT INTO users (name, email) VALUES ('张三', 'zhangsan@example.com')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let db = db::Db {
+            write: pool.clone(),
+            read: pool,
+        };
+
+        let app_state = AppState::new(
+            db.clone(),
+            Arc::new(config.clone()),
+            PrometheusBuilder::new().build_recorder().handle(),
+        );
+        let (logger, _receiver) = AuditLogger::new();
+        let audit_state = AuditState {
+            enabled: false,
+            logger,
+            admin_token: config.security.admin_token.as_deref().map(Arc::from),
+        };
+        build_router(config, db, app_state, audit_state)
+    }
+
+    /// 来自本机的请求（通过内部网段检查）
+    fn local(builder: Builder) -> Request {
+        let mut req = builder.body(Body::empty()).unwrap();
+        req.extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40000))));
+        req
+    }
+
+    async fn body_text(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn user_export_requires_admin_token_and_internal_network() {
+        let mut config = AppConfig::default();
+        config.security.admin_token = Some(ADMIN_TOKEN.to_string());
+        let app = test_router(&config).await;
+
+        let export = || Request::get("/admin/users/export");
+        let response = app.clone().oneshot(local(export())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let authorized = || export().header("authorization", format!("Bearer {}", ADMIN_TOKEN));
+        let mut external = authorized().body(Body::empty()).unwrap();
+        external
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([203, 0, 113, 7], 40000))));
+        let response = app.clone().oneshot(external).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app.clone().oneshot(local(authorized())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(body_text(response).await.contains("zhangsan@example.com"));
+
+        // 旧的公开地址已不存在
+        let response = app
+            .oneshot(local(Request::get("/api/users/export")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn unsupported_method_returns_405_with_allow_header() {
+        let app = test_router(&AppConfig::default()).await;
+
+        let response = app
+            .clone()
+            .oneshot(local(
+                Request::patch("/api/todos").header("accept", "application/json"),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        let allow = response.headers()["allow"].to_str().unwrap().to_string();
+        assert!(allow.contains("POST"), "{}", allow);
+        let body: serde_json::Value = serde_json::from_str(&body_text(response).await).unwrap();
+        assert_eq!(body["error"], "method_not_allowed");
+        assert!(body["allow"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|m| m == "POST"));
+
+        let response = app
+            .clone()
+            .oneshot(local(
+                Request::patch("/api/todos").header("hx-request", "true"),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert!(response.headers().contains_key("allow"));
+        assert!(body_text(response).await.contains("alert-danger"));
+
+        // 请求里的示例：/api/todos 只注册了 POST
+        let response = app
+            .oneshot(local(Request::get("/api/todos")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers()["allow"], "POST");
+        assert!(body_text(response).await.contains("POST"));
+    }
+
+    #[tokio::test]
+    async fn skeleton_fragments_are_publicly_cacheable() {
+        let app = test_router(&AppConfig::default()).await;
+        for (path, marker) in [
+            ("/block/todos/skeleton", "待办页面骨架屏"),
+            ("/block/users/skeleton", "用户页面骨架屏"),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(local(Request::get(path)))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{}", path);
+            assert_eq!(
+                response.headers()["cache-control"],
+                "public, max-age=3600",
+                "{}",
+                path
+            );
+            let html = body_text(response).await;
+            assert!(html.contains("placeholder-glow"), "{}", path);
+            assert!(html.contains(marker), "{}", path);
+        }
+    }
+
+    #[tokio::test]
+    async fn preflight_advertises_configured_max_age() {
+        let preflight = || {
+            local(
+                Request::options("/api/todos")
+                    .header("origin", "http://localhost:5173")
+                    .header("access-control-request-method", "POST"),
+            )
+        };
+
+        let mut config = AppConfig::default();
+        let app = test_router(&config).await;
+        let response = app.oneshot(preflight()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["access-control-allow-origin"],
+            "http://localhost:5173"
+        );
+        assert_eq!(response.headers()["access-control-max-age"], "600");
+
+        // 设为 0 时不缓存预检结果
+        config.security.cors_max_age_seconds = 0;
+        let app = test_router(&config).await;
+        let response = app.oneshot(preflight()).await.unwrap();
+        assert!(!response.headers().contains_key("access-control-max-age"));
+    }
+
+    #[tokio::test]
+    async fn rate_limited_cross_origin_response_carries_cors_headers() {
+        let mut config = AppConfig::default();
+        config.security.rate_limit_per_minute = 1;
+        let app = test_router(&config).await;
+        let create = || {
+            local(
+                Request::post("/api/todos")
+                    .header("origin", "http://localhost:5173")
+                    .header("accept", "application/json"),
+            )
+        };
+
+        let response = app.clone().oneshot(create()).await.unwrap();
+        assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let response = app.oneshot(create()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(
+            response.headers()["access-control-allow-origin"],
+            "http://localhost:5173"
+        );
+        assert!(response.headers().contains_key("retry-after"));
     }
 
-    // 等待指定的超时时间后强制关闭
-    let timeout = Duration::from_secs(timeout_seconds);
-    tokio::time::sleep(timeout).await;
-    tracing::info!("超时 {} 秒，强制关闭服务器", timeout_seconds);
+    #[tokio::test]
+    async fn hsts_is_sent_only_with_tls() {
+        let mut config = AppConfig::default();
+        let app = test_router(&config).await;
+        let response = app.oneshot(local(Request::get("/health"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key("strict-transport-security"));
+
+        config.tls = Some(TlsConfig {
+            cert_path: "cert.pem".to_string(),
+            key_path: "key.pem".to_string(),
+        });
+        config.security.hsts.include_subdomains = true;
+        let app = test_router(&config).await;
+        let response = app.oneshot(local(Request::get("/health"))).await.unwrap();
+        assert_eq!(
+            response.headers()["strict-transport-security"],
+            config.hsts_header_value().unwrap().as_str()
+        );
+        assert!(config
+            .hsts_header_value()
+            .unwrap()
+            .ends_with("; includeSubDomains"));
+
+        config.security.hsts.enabled = false;
+        assert_eq!(config.hsts_header_value(), None);
+    }
+
+    #[tokio::test]
+    async fn robots_tag_is_sent_on_block_routes_but_not_on_the_landing_page() {
+        let app = test_router(&AppConfig::default()).await;
+
+        let response = app
+            .clone()
+            .oneshot(local(Request::get("/block/todos")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-robots-tag"], "noindex");
+
+        let response = app.oneshot(local(Request::get("/"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key("x-robots-tag"));
+    }
 }
@@ -0,0 +1,115 @@
+//! 管理接口
+//!
+//! 仅供运维使用，由 `admin_auth_guard` 保护；开发环境的 `/admin/routes` 只限内部网段，不要求管理令牌
+
+use axum::extract::{Extension, Query};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use std::sync::Arc;
+
+use super::htmx::CachePolicy;
+use super::registry::RouteRegistry;
+use crate::db::Db;
+use crate::helpers::config::CONFIG;
+use crate::helpers::pagination::{create_pagination, PageQuery};
+use crate::services::audit::list_entries;
+
+/// 分页查看审计日志
+pub async fn audit(
+    Extension(Db { read: pool, .. }): Extension<Db>,
+    Query(params): Query<PageQuery>,
+) -> Response {
+    if let Err(e) = params.validate() {
+        return e.into_response();
+    }
+
+    let page = params.get_page();
+    let per_page = params.get_per_page();
+
+    match list_entries(&pool, per_page, params.get_offset()).await {
+        Ok((entries, total)) => CachePolicy::NoStore.apply(Json(serde_json::json!({
+            "entries": entries,
+            "pagination": create_pagination(page, per_page, total),
+        }))),
+        Err(e) => {
+            tracing::error!("❌ 查询审计日志失败: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "查询审计日志失败").into_response()
+        }
+    }
+}
+
+/// 列出已注册的路由（仅开发环境挂载）
+///
+/// 路径不含 `base_path`，实际访问地址为 `base_path` 加路径
+pub async fn routes(Extension(registry): Extension<Arc<RouteRegistry>>) -> Response {
+    CachePolicy::NoStore.apply(Json(serde_json::json!({
+        "base_path": CONFIG.server.base_path,
+        "routes": registry.sorted(),
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::audit::{run_audit_writer, AuditEntry, AuditLogger};
+    use tokio_util::sync::CancellationToken;
+
+    #[tokio::test]
+    async fn audit_pages_through_written_entries_newest_first() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        crate::db::run_migrations(&pool, &crate::helpers::config::DatabaseConfig::default())
+            .await
+            .unwrap();
+
+        // 经审计写入任务落库：关闭时写完通道中剩余的记录
+        let (logger, receiver) = AuditLogger::new();
+        for id in 1..=5 {
+            logger.record(AuditEntry {
+                principal: Some("admin".to_string()),
+                method: "DELETE".to_string(),
+                route: "/api/todos/:id".to_string(),
+                target_id: Some(id.to_string()),
+                status: 204,
+            });
+        }
+        let token = CancellationToken::new();
+        token.cancel();
+        run_audit_writer(pool.clone(), receiver, token).await;
+
+        let response = audit(
+            Extension(Db {
+                write: pool.clone(),
+                read: pool,
+            }),
+            Query(PageQuery {
+                page: Some(2),
+                per_page: Some(2),
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let entries = body["entries"].as_array().unwrap();
+        let targets: Vec<_> = entries
+            .iter()
+            .map(|entry| entry["target_id"].as_str().unwrap())
+            .collect();
+        assert_eq!(targets, ["3", "2"]);
+        assert_eq!(entries[0]["method"], "DELETE");
+        assert_eq!(entries[0]["route"], "/api/todos/:id");
+        assert_eq!(entries[0]["status"], 204);
+        assert_eq!(entries[0]["principal"], "admin");
+        assert_eq!(body["pagination"]["total"], 5);
+        assert_eq!(body["pagination"]["total_pages"], 3);
+        assert_eq!(body["pagination"]["current_page"], 2);
+    }
+}
@@ -0,0 +1,142 @@
+//! 模板上下文
+//!
+//! 模板需要的配置值（路径前缀、环境、功能开关等）统一从这里读取，不直接访问 `CONFIG`。
+//! 上下文只包含可以出现在页面中的值，令牌、连接地址等敏感配置不会进入模板
+
+use crate::helpers::config::AppConfig;
+
+/// 模板可见的功能开关
+#[derive(Debug, Clone)]
+pub struct TemplateFeatures {
+    /// 首页是否显示待办统计（`ui.home_todo_stats`）
+    pub home_todo_stats: bool,
+}
+
+/// 所有模板共用的上下文，启动时由配置构建一次
+///
+/// 模板中通过 `crate::routes::template_context()` 访问，例如
+/// `{% if crate::routes::template_context().features.home_todo_stats %}`。
+/// 新增字段前确认该值可以公开，不要加入令牌、连接地址、文件路径等配置
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    /// 运行环境（`environment`）
+    pub environment: String,
+    /// 是否为生产环境
+    pub production: bool,
+    /// 应用的路径前缀（`server.base_path`），根路径部署时为空
+    pub base_path: String,
+    /// 实时搜索的防抖延迟（毫秒，`ui.search_debounce_ms`）
+    pub search_debounce_ms: u64,
+    /// `<meta name="htmx-config">` 的内容（`htmx` 配置段），未配置任何项时为 `None`
+    pub htmx_config_json: Option<String>,
+    pub features: TemplateFeatures,
+}
+
+impl TemplateContext {
+    /// 从配置中挑选可公开的值
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            environment: config.environment.clone(),
+            production: config.is_production(),
+            base_path: config.server.base_path.clone(),
+            search_debounce_ms: config.ui.search_debounce_ms,
+            htmx_config_json: config.htmx.meta_json(),
+            features: TemplateFeatures {
+                home_todo_stats: config.ui.home_todo_stats,
+            },
+        }
+    }
+
+    /// 环境横幅显示的环境名，生产环境不显示
+    pub fn environment_banner(&self) -> Option<&str> {
+        (!self.production).then_some(self.environment.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::config::HtmxConfig;
+    use askama::Template;
+
+    /// 布局中引入的环境横幅
+    #[derive(Template)]
+    #[template(path = "blocks/environment_banner.html")]
+    struct EnvironmentBanner<'a> {
+        context: &'a TemplateContext,
+    }
+
+    /// 布局中引入的 htmx-config meta 标签
+    #[derive(Template)]
+    #[template(path = "blocks/htmx_config.html")]
+    struct HtmxConfigMeta<'a> {
+        htmx_config: &'a str,
+    }
+
+    #[test]
+    fn htmx_config_meta_reflects_config() {
+        let config = AppConfig {
+            htmx: HtmxConfig {
+                default_swap_style: Some("outerHTML".to_string()),
+                history_cache_size: Some(0),
+                timeout_ms: Some(5000),
+                ..HtmxConfig::default()
+            },
+            ..AppConfig::default()
+        };
+        let context = TemplateContext::from_config(&config);
+        let json = context.htmx_config_json.as_deref().unwrap();
+
+        let html = HtmxConfigMeta { htmx_config: json }.render().unwrap();
+        let content = html
+            .split("content=\"")
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .unwrap()
+            .replace("&quot;", "\"");
+        let meta: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(
+            meta,
+            serde_json::json!({
+                "defaultSwapStyle": "outerHTML",
+                "historyCacheSize": 0,
+                "timeout": 5000,
+            })
+        );
+
+        // 未配置任何项时不输出 meta 标签
+        assert!(TemplateContext::from_config(&AppConfig::default())
+            .htmx_config_json
+            .is_none());
+    }
+
+    #[test]
+    fn environment_banner_only_outside_production() {
+        for (environment, shown) in [
+            ("development", true),
+            ("staging", true),
+            ("production", false),
+        ] {
+            let config = AppConfig {
+                environment: environment.to_string(),
+                ..AppConfig::default()
+            };
+            let context = TemplateContext::from_config(&config);
+            assert_eq!(
+                context.environment_banner(),
+                shown.then_some(environment),
+                "{}",
+                environment
+            );
+
+            let html = EnvironmentBanner { context: &context }.render().unwrap();
+            assert_eq!(
+                html.contains(&format!("当前环境：{}", environment)),
+                shown,
+                "{}",
+                environment
+            );
+            assert_eq!(html.contains("role=\"status\""), shown, "{}", environment);
+        }
+    }
+}
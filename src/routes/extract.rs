@@ -0,0 +1,183 @@
+//! 请求体提取器模块
+//!
+//! 在反序列化前显式检查 Content-Type，类型不匹配时返回统一的 415 响应，
+//! 而不是 axum 默认的拒绝信息。
+//!
+//! 带请求体的处理器都应使用这里的提取器：待办创建用 [`StrictForm`]，用户和待办导入用
+//! [`CsvBody`]。待办切换、删除只有路径参数，用户详情用查询参数，都不读取请求体，无需检查
+
+use axum::{
+    async_trait,
+    extract::{FromRequest, Request},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Form, Json,
+};
+use serde::de::DeserializeOwned;
+
+use super::htmx::{flash_alert, HxHeaders};
+
+/// 表单的 Content-Type
+const FORM_URLENCODED: &str = "application/x-www-form-urlencoded";
+
+/// CSV 的 Content-Type
+const TEXT_CSV: &str = "text/csv";
+
+/// 严格表单提取器
+///
+/// 仅接受 `application/x-www-form-urlencoded` 请求体，其他类型返回 415
+pub struct StrictForm<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for StrictForm<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !content_type_matches(req.headers(), FORM_URLENCODED) {
+            return Err(unsupported_media_type(req.headers(), FORM_URLENCODED));
+        }
+
+        let Form(value) = Form::<T>::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        Ok(Self(value))
+    }
+}
+
+/// CSV 请求体提取器
+///
+/// 仅接受 `text/csv` 请求体，其他类型返回 415
+pub struct CsvBody(pub String);
+
+#[async_trait]
+impl<S> FromRequest<S> for CsvBody
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !content_type_matches(req.headers(), TEXT_CSV) {
+            return Err(unsupported_media_type(req.headers(), TEXT_CSV));
+        }
+
+        let body = String::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        Ok(Self(body))
+    }
+}
+
+/// 检查请求的 Content-Type 是否为期望的类型（忽略 charset 等参数）
+fn content_type_matches(headers: &HeaderMap, expected: &str) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .is_some_and(|essence| essence.trim().eq_ignore_ascii_case(expected))
+}
+
+/// 构建 415 响应
+///
+/// HTMX 请求返回换入 `#flash-messages` 的提示片段，其他客户端返回 JSON
+fn unsupported_media_type(headers: &HeaderMap, expected: &str) -> Response {
+    let message = format!("不支持的请求类型，请使用 {}", expected);
+
+    if HxHeaders::from_headers(headers).request {
+        flash_alert(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "danger",
+            "exclamation-octagon",
+            &message,
+        )
+    } else {
+        (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Json(serde_json::json!({
+                "error": "unsupported_media_type",
+                "message": message,
+                "expected": expected,
+            })),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::post, Router};
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    #[derive(Deserialize)]
+    struct TitleForm {
+        title: String,
+    }
+
+    fn router() -> Router {
+        Router::new().route(
+            "/api/todos",
+            post(|StrictForm(form): StrictForm<TitleForm>| async move { form.title }),
+        )
+    }
+
+    async fn send(content_type: Option<&str>, htmx: bool) -> Response {
+        let mut req = Request::builder().method("POST").uri("/api/todos");
+        if let Some(content_type) = content_type {
+            req = req.header(header::CONTENT_TYPE, content_type);
+        }
+        if htmx {
+            req = req.header("hx-request", "true");
+        }
+        router()
+            .oneshot(req.body(Body::from("title=x")).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn body_text(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn strict_form_accepts_urlencoded_with_charset() {
+        let response = send(
+            Some("application/x-www-form-urlencoded; charset=UTF-8"),
+            false,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_text(response).await, "x");
+    }
+
+    #[tokio::test]
+    async fn strict_form_rejects_json_and_missing_content_type_with_json() {
+        for content_type in [Some("application/json"), None] {
+            let response = send(content_type, false).await;
+            assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+            let body: serde_json::Value = serde_json::from_str(&body_text(response).await).unwrap();
+            assert_eq!(body["error"], "unsupported_media_type");
+            assert_eq!(body["expected"], FORM_URLENCODED);
+        }
+    }
+
+    #[tokio::test]
+    async fn strict_form_rejects_htmx_requests_with_flash_alert() {
+        for content_type in [Some("application/json"), None] {
+            let response = send(content_type, true).await;
+            assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+            assert_eq!(response.headers()["hx-retarget"], "#flash-messages");
+            let body = body_text(response).await;
+            assert!(body.contains("alert-danger"));
+            assert!(body.contains(FORM_URLENCODED));
+        }
+    }
+}
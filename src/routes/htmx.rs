@@ -0,0 +1,241 @@
+//! HTMX 辅助模块
+//!
+//! 提供 HTMX 请求头提取器，以及根据请求类型调整响应的辅助函数
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::StatusCode,
+    http::{header, request::Parts, HeaderMap, HeaderName, HeaderValue},
+    response::{Html, IntoResponse, Response},
+};
+use std::convert::Infallible;
+
+use crate::helpers::config::CONFIG;
+use crate::helpers::html::escape_html;
+
+/// HTMX 请求头名称
+pub const HX_REQUEST: HeaderName = HeaderName::from_static("hx-request");
+pub const HX_BOOSTED: HeaderName = HeaderName::from_static("hx-boosted");
+pub const HX_TARGET: HeaderName = HeaderName::from_static("hx-target");
+pub const HX_TRIGGER: HeaderName = HeaderName::from_static("hx-trigger");
+
+/// HTMX 响应头名称
+pub const HX_RETARGET: HeaderName = HeaderName::from_static("hx-retarget");
+pub const HX_RESWAP: HeaderName = HeaderName::from_static("hx-reswap");
+
+/// 主内容区域的选择器，boosted 导航的片段会被重定向到这里
+const MAIN_CONTENT_SELECTOR: &str = "#main-content";
+
+/// 页面顶部提示消息容器的选择器
+const FLASH_MESSAGES_SELECTOR: &str = "#flash-messages";
+
+/// 需要在 Vary 中声明的请求头，避免缓存混用完整页面和片段
+const VARY_HTMX: &str = "HX-Request, HX-Boosted";
+
+/// HTMX 请求头提取器
+///
+/// 从请求中读取 `HX-Request`、`HX-Boosted`、`HX-Target`、`HX-Trigger`，缺失时取默认值，永不拒绝请求
+#[derive(Debug, Clone, Default)]
+pub struct HxHeaders {
+    /// 是否由 HTMX 发起
+    pub request: bool,
+    /// 是否由 hx-boost 发起
+    pub boosted: bool,
+    /// 目标元素的 id
+    pub target: Option<String>,
+    /// 触发元素的 id
+    pub trigger: Option<String>,
+}
+
+impl HxHeaders {
+    /// 从请求头构建
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        Self {
+            request: header_is_true(headers, &HX_REQUEST),
+            boosted: header_is_true(headers, &HX_BOOSTED),
+            target: header_string(headers, &HX_TARGET),
+            trigger: header_string(headers, &HX_TRIGGER),
+        }
+    }
+
+    /// 是否为 hx-boost 触发的页面导航
+    pub fn is_boosted_navigation(&self) -> bool {
+        self.request && self.boosted
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for HxHeaders
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(&parts.headers))
+    }
+}
+
+/// 读取值为 "true" 的布尔请求头
+fn header_is_true(headers: &HeaderMap, name: &HeaderName) -> bool {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("true"))
+}
+
+/// 读取字符串请求头
+fn header_string(headers: &HeaderMap, name: &HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+/// 完整页面响应
+///
+/// 声明 `Vary`，避免代理把完整页面返回给 HTMX 请求
+pub fn full_page(page: impl IntoResponse) -> Response {
+    let mut response = page.into_response();
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static(VARY_HTMX));
+    response
+}
+
+/// boosted 导航的主内容片段响应
+///
+/// hx-boost 默认替换整个 body，这里通过 `HX-Retarget`/`HX-Reswap` 把片段换入主内容区域，
+/// 从而省略 base 布局
+pub fn boosted_fragment(fragment: impl IntoResponse) -> Response {
+    let mut response = fragment.into_response();
+    let headers = response.headers_mut();
+    headers.insert(HX_RETARGET, HeaderValue::from_static(MAIN_CONTENT_SELECTOR));
+    headers.insert(HX_RESWAP, HeaderValue::from_static("innerHTML"));
+    headers.append(header::VARY, HeaderValue::from_static(VARY_HTMX));
+    response
+}
+
+/// 页面顶部提示消息响应
+///
+/// 错误响应默认不会被 HTMX 换入，这里把提示片段重定向到 `#flash-messages`，
+/// 布局中的 `htmx:beforeSwap` 处理器负责放行。`message` 会做 HTML 转义，可以包含用户输入
+pub fn flash_alert(status: StatusCode, level: &str, icon: &str, message: &str) -> Response {
+    let mut response = (
+        status,
+        Html(format!(
+            "<div class=\"alert alert-{} alert-dismissible fade show\" role=\"alert\">\
+             <i class=\"bi bi-{} me-2\"></i>{}\
+             <button type=\"button\" class=\"btn-close\" data-bs-dismiss=\"alert\"></button>\
+             </div>",
+            level,
+            icon,
+            escape_html(message)
+        )),
+    )
+        .into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        HX_RETARGET,
+        HeaderValue::from_static(FLASH_MESSAGES_SELECTOR),
+    );
+    headers.insert(HX_RESWAP, HeaderValue::from_static("innerHTML"));
+    response
+}
+
+/// 删除成功的响应
+///
+/// - 非 HTMX 请求：`204 No Content`，不带响应体
+/// - HTMX 请求：`200`，响应体只包含 OOB 片段（如更新后的统计），主内容为空。
+///   开启 `htmx.delete_reswap` 时带 `HX-Reswap: delete`，由 HTMX 显式删除目标元素；
+///   关闭时依赖目标元素以 `outerHTML` 交换空内容
+///
+/// HTMX 不交换 204 响应，因此 HTMX 请求不能返回 204，否则目标元素和 OOB 片段都不会更新
+pub fn deleted(hx: &HxHeaders, oob: impl Into<String>) -> Response {
+    deleted_with(hx, oob, CONFIG.htmx.delete_reswap)
+}
+
+/// 按给定的 `delete_reswap` 开关构建删除成功的响应
+fn deleted_with(hx: &HxHeaders, oob: impl Into<String>, delete_reswap: bool) -> Response {
+    if !hx.request {
+        return StatusCode::NO_CONTENT.into_response();
+    }
+
+    let mut response = Html(oob.into()).into_response();
+    if delete_reswap {
+        response
+            .headers_mut()
+            .insert(HX_RESWAP, HeaderValue::from_static("delete"));
+    }
+    response
+}
+
+/// 按路由声明的响应缓存策略，与插件共用 `htmx-core` 中的定义
+pub use htmx_core::CachePolicy;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn body_text(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn from_headers_reads_every_htmx_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(HX_REQUEST, HeaderValue::from_static("true"));
+        headers.insert(HX_BOOSTED, HeaderValue::from_static("TRUE"));
+        headers.insert(HX_TARGET, HeaderValue::from_static("search-results"));
+        headers.insert(HX_TRIGGER, HeaderValue::from_static("search-input"));
+
+        let hx = HxHeaders::from_headers(&headers);
+        assert!(hx.request && hx.boosted && hx.is_boosted_navigation());
+        assert_eq!(hx.target.as_deref(), Some("search-results"));
+        assert_eq!(hx.trigger.as_deref(), Some("search-input"));
+
+        // 缺失或为空的头取默认值
+        headers.insert(HX_TARGET, HeaderValue::from_static(""));
+        headers.remove(HX_TRIGGER);
+        headers.insert(HX_BOOSTED, HeaderValue::from_static("false"));
+        let hx = HxHeaders::from_headers(&headers);
+        assert!(hx.request && !hx.is_boosted_navigation());
+        assert_eq!((hx.target, hx.trigger), (None, None));
+    }
+
+    #[tokio::test]
+    async fn deleted_is_no_content_without_htmx() {
+        for delete_reswap in [false, true] {
+            let response = deleted_with(&HxHeaders::default(), "<div></div>", delete_reswap);
+            assert_eq!(response.status(), StatusCode::NO_CONTENT);
+            assert!(!response.headers().contains_key(HX_RESWAP));
+            assert!(body_text(response).await.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn deleted_returns_oob_fragment_to_htmx() {
+        let hx = HxHeaders {
+            request: true,
+            ..HxHeaders::default()
+        };
+        let oob = "<div id=\"todo-stats\" hx-swap-oob=\"true\"></div>";
+
+        // 显式删除目标元素
+        let response = deleted_with(&hx, oob, true);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[HX_RESWAP], "delete");
+        assert_eq!(body_text(response).await, oob);
+
+        // 目标元素以 outerHTML 交换空的主内容
+        let response = deleted_with(&hx, oob, false);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key(HX_RESWAP));
+        assert_eq!(body_text(response).await, oob);
+    }
+}
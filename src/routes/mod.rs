@@ -3,9 +3,675 @@
 //! 包含所有路由处理逻辑的模块声明
 
 // 模块声明，不包含业务逻辑
+pub mod admin;
+pub mod context;
+pub mod extract;
+pub mod htmx;
 pub mod modal;
 pub mod official;
 pub mod pages;
+pub mod registry;
 pub mod static_assets;
 pub mod todos;
 pub mod users;
+
+use askama::Template;
+use axum::{
+    body::{Body, HttpBody},
+    extract::{Request, State},
+    http::StatusCode,
+    http::{header, HeaderName, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::sync::{Arc, Once};
+
+use crate::helpers::config::CONFIG;
+use crate::helpers::security::accepts_json;
+use context::TemplateContext;
+use htmx::HxHeaders;
+
+/// 保留原始空白的元素
+const RAW_TEXT_ELEMENTS: [&str; 4] = ["pre", "textarea", "script", "style"];
+
+lazy_static::lazy_static! {
+    /// 模板上下文，启动后不变，只构建一次
+    static ref TEMPLATE_CONTEXT: TemplateContext = TemplateContext::from_config(&CONFIG);
+}
+
+/// 只记录一次压缩效果
+static MINIFY_LOGGED: Once = Once::new();
+
+/// 参与 HTML 压缩的响应体上限，更大或长度未知（流式）的响应原样返回，避免整体缓冲
+const MAX_MINIFY_BYTES: u64 = 1024 * 1024;
+
+/// 所有模板共用的上下文（环境、路径前缀、功能开关等，不含敏感配置）
+///
+/// 以下 `base_path()` 等函数是常用字段的简写
+pub fn template_context() -> &'static TemplateContext {
+    &TEMPLATE_CONTEXT
+}
+
+/// 应用的路径前缀（`server.base_path`），根路径部署时为空
+///
+/// 模板中的绝对链接通过 `{{ crate::routes::base_path() }}/...` 拼接
+pub fn base_path() -> &'static str {
+    &template_context().base_path
+}
+
+/// 实时搜索的防抖延迟（毫秒，`ui.search_debounce_ms`）
+///
+/// 模板中的 `hx-trigger` 通过 `delay:{{ crate::routes::search_debounce_ms() }}ms` 引用
+pub fn search_debounce_ms() -> u64 {
+    template_context().search_debounce_ms
+}
+
+/// 首页是否显示待办统计（`ui.home_todo_stats`）
+pub fn home_todo_stats_enabled() -> bool {
+    template_context().features.home_todo_stats
+}
+
+/// `<meta name="htmx-config">` 的内容（`htmx` 配置段），未配置任何项时为 `None`
+pub fn htmx_config_json() -> Option<&'static str> {
+    template_context().htmx_config_json.as_deref()
+}
+
+/// 为应用内的绝对路径加上路径前缀
+pub fn url(path: &str) -> String {
+    format!("{}{}", base_path(), path)
+}
+
+/// 为应用内的绝对路径加上对外根地址（`server.public_base_url`），生成完整链接
+///
+/// 用于 canonical 链接、站点地图等需要完整 URL 的场景，路径不含 `base_path`
+pub fn absolute_url(path: &str) -> String {
+    format!("{}{}", CONFIG.public_base_url(), path)
+}
+
+/// canonical 链接地址，仅在显式配置了 `server.public_base_url` 时返回
+///
+/// 推导出的地址在反向代理之后通常不是外部地址，此时不输出 canonical 标签，
+/// 以免搜索引擎把页面归并到内部地址
+pub fn canonical_url(path: &str) -> Option<String> {
+    CONFIG
+        .server
+        .public_base_url
+        .is_some()
+        .then(|| absolute_url(path))
+}
+
+/// HTML 片段响应
+///
+/// `/block/*` 处理器返回的片段统一经此渲染，显式声明 `text/html; charset=utf-8`，
+/// 不依赖模板扩展名推断；渲染失败时记录错误并返回 500
+pub struct HtmlFragment<T: Template>(pub T);
+
+impl<T: Template> IntoResponse for HtmlFragment<T> {
+    fn into_response(self) -> Response {
+        match self.0.render() {
+            Ok(html) => (
+                [(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("text/html; charset=utf-8"),
+                )],
+                html,
+            )
+                .into_response(),
+            Err(e) => {
+                tracing::error!("渲染 HTML 片段失败: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
+/// 单个请求要写入的行数超出 `database.max_batch_rows`
+#[derive(Debug, Clone, Copy)]
+pub struct BatchTooLarge {
+    pub rows: usize,
+    pub limit: usize,
+}
+
+impl BatchTooLarge {
+    /// 检查本次要写入的行数，应在开启写事务之前调用
+    pub fn check(rows: usize) -> Result<(), Self> {
+        let limit = CONFIG.database.max_batch_rows;
+        if rows > limit {
+            return Err(Self { rows, limit });
+        }
+        Ok(())
+    }
+}
+
+impl IntoResponse for BatchTooLarge {
+    fn into_response(self) -> Response {
+        tracing::warn!(
+            "⚠️  批量写入被拒绝: {} 行，超出上限 {}",
+            self.rows,
+            self.limit
+        );
+        (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(serde_json::json!({
+                "error": "batch_too_large",
+                "message": format!("单次最多写入 {} 行，本次 {} 行，请拆分后重试", self.limit, self.rows),
+                "limit": self.limit,
+                "rows": self.rows,
+            })),
+        )
+            .into_response()
+    }
+}
+
+/// 空状态操作按钮
+pub struct EmptyStateAction {
+    /// 按钮文字
+    pub label: String,
+    /// 点击后请求的片段地址
+    pub hx_get: String,
+    /// 片段替换的目标选择器
+    pub hx_target: String,
+}
+
+/// 通用空状态组件
+///
+/// 列表为空时代替空白片段显示，由各调用方提供符合上下文的文字
+#[derive(Template)]
+#[template(path = "components/empty_state/base.html")]
+pub struct EmptyStateTemplate {
+    /// Bootstrap Icons 图标名（不含 `bi-` 前缀）
+    pub icon: String,
+    pub title: String,
+    pub hint: String,
+    pub action: Option<EmptyStateAction>,
+}
+
+impl EmptyStateTemplate {
+    /// 创建不带操作按钮的空状态
+    pub fn new(icon: &str, title: &str, hint: impl Into<String>) -> Self {
+        Self {
+            icon: icon.to_string(),
+            title: title.to_string(),
+            hint: hint.into(),
+            action: None,
+        }
+    }
+
+    /// 附加操作按钮
+    pub fn with_action(mut self, label: &str, hx_get: &str, hx_target: &str) -> Self {
+        self.action = Some(EmptyStateAction {
+            label: label.to_string(),
+            hx_get: hx_get.to_string(),
+            hx_target: hx_target.to_string(),
+        });
+        self
+    }
+
+    /// 待办列表为空
+    pub fn no_todos() -> Self {
+        Self::new("inbox", "暂无任务", "添加第一个任务，开始管理你的待办事项").with_action(
+            "添加新任务",
+            &url("/block/todos/create-form"),
+            "#create-form",
+        )
+    }
+}
+
+/// 405 响应中间件
+///
+/// axum 已根据路由注册的方法生成准确的 `Allow` 头，但响应体为空；
+/// 这里按客户端类型补充说明：HTMX 请求返回提示片段，JSON 客户端返回 JSON，其他返回纯文本。
+///
+/// 路由未注册 OPTIONS 时，不带 CORS 预检头的 `OPTIONS` 探测（CORS 预检已由 `CorsLayer` 应答）
+/// 返回 `204` 和 `Allow` 头，而不是 405
+pub async fn method_not_allowed_middleware(req: Request, next: Next) -> Response {
+    let method = req.method().clone();
+    let is_htmx = HxHeaders::from_headers(req.headers()).request;
+    let wants_json = accepts_json(req.headers());
+
+    let response = next.run(req).await;
+    if response.status() != StatusCode::METHOD_NOT_ALLOWED {
+        return response;
+    }
+
+    let allow = response
+        .headers()
+        .get(header::ALLOW)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    if method == axum::http::Method::OPTIONS {
+        let allow = if allow.is_empty() {
+            "OPTIONS".to_string()
+        } else {
+            format!("{},OPTIONS", allow)
+        };
+        let (mut parts, _) = response.into_parts();
+        parts.status = StatusCode::NO_CONTENT;
+        if let Ok(value) = HeaderValue::from_str(&allow) {
+            parts.headers.insert(header::ALLOW, value);
+        }
+        parts.headers.remove(header::CONTENT_LENGTH);
+        parts.headers.remove(header::CONTENT_TYPE);
+        return Response::from_parts(parts, Body::empty());
+    }
+    let message = format!("该地址不支持 {} 请求，可用方法: {}", method, allow);
+
+    let mut rebuilt = if is_htmx {
+        htmx::flash_alert(
+            StatusCode::METHOD_NOT_ALLOWED,
+            "danger",
+            "x-octagon",
+            &message,
+        )
+    } else if wants_json {
+        (
+            StatusCode::METHOD_NOT_ALLOWED,
+            Json(serde_json::json!({
+                "error": "method_not_allowed",
+                "message": message,
+                "allow": allow.split(',').map(str::trim).filter(|m| !m.is_empty()).collect::<Vec<_>>(),
+            })),
+        )
+            .into_response()
+    } else {
+        (StatusCode::METHOD_NOT_ALLOWED, message).into_response()
+    };
+
+    // 保留 Allow、CORS 等原有响应头，只替换响应体相关的头
+    let (parts, _) = response.into_parts();
+    for (name, value) in parts.headers.iter() {
+        if name != header::CONTENT_LENGTH && name != header::CONTENT_TYPE {
+            rebuilt.headers_mut().append(name, value.clone());
+        }
+    }
+    rebuilt
+}
+
+/// 搜索引擎索引控制中间件
+///
+/// 路径匹配 `noindex_prefixes` 中任一前缀时添加 `X-Robots-Tag: noindex`，
+/// 避免片段和接口地址被收录；官网等页面不受影响。
+/// 挂载在 `base_path` 下时 nest 已去掉前缀，这里拿到的就是相对路径
+pub async fn robots_tag_middleware(
+    State(prefixes): State<Arc<[String]>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let noindex = is_noindex_path(req.uri().path(), &prefixes);
+    let mut response = next.run(req).await;
+
+    if noindex {
+        response.headers_mut().insert(
+            HeaderName::from_static("x-robots-tag"),
+            HeaderValue::from_static("noindex"),
+        );
+    }
+    response
+}
+
+/// 路径是否落在任一 noindex 前缀下（按路径段匹配，`/apply` 不匹配 `/app`）
+fn is_noindex_path(path: &str, prefixes: &[String]) -> bool {
+    prefixes.iter().any(|prefix| {
+        path.strip_prefix(prefix.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/') || prefix == "/")
+    })
+}
+
+/// HTML 压缩中间件
+///
+/// 启用时（`minify_html`，默认仅生产环境）压缩 `text/html` 响应中的多余空白，
+/// 已编码（如 gzip）、长度未知或超过 [`MAX_MINIFY_BYTES`] 的响应原样返回。
+/// 读取响应体失败时原响应已无法恢复，返回 500
+pub async fn minify_html_middleware(
+    State(enabled): State<bool>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let response = next.run(req).await;
+    let too_large = response
+        .body()
+        .size_hint()
+        .exact()
+        .is_none_or(|len| len > MAX_MINIFY_BYTES);
+    if !enabled || !is_plain_html(&response) || too_large {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_MINIFY_BYTES as usize).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("读取 HTML 响应失败: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let Ok(html) = std::str::from_utf8(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    let minified = minify_html(html);
+    MINIFY_LOGGED.call_once(|| {
+        tracing::info!(
+            "🗜️  HTML 压缩已启用: {} → {} 字节（减少 {:.1}%）",
+            bytes.len(),
+            minified.len(),
+            (1.0 - minified.len() as f64 / bytes.len().max(1) as f64) * 100.0
+        );
+    });
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(minified))
+}
+
+/// 是否为未编码的 HTML 响应
+fn is_plain_html(response: &Response) -> bool {
+    let headers = response.headers();
+    !headers.contains_key(header::CONTENT_ENCODING)
+        && headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/html"))
+}
+
+/// 压缩 HTML 中的多余空白
+///
+/// 文本和标签内的连续空白合并为一个空格，属性值、注释以及
+/// `<pre>`/`<textarea>`/`<script>`/`<style>` 的内容原样保留
+pub fn minify_html(html: &str) -> String {
+    let bytes = html.as_bytes();
+    let mut out = String::with_capacity(html.len());
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let c = bytes[pos];
+
+        if c.is_ascii_whitespace() {
+            // 合并连续空白
+            while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            out.push(' ');
+            continue;
+        }
+
+        if c != b'<' {
+            let next = html[pos..]
+                .find(|ch: char| ch == '<' || ch.is_ascii_whitespace())
+                .map_or(html.len(), |i| pos + i);
+            out.push_str(&html[pos..next]);
+            pos = next;
+            continue;
+        }
+
+        // 注释原样保留
+        if html[pos..].starts_with("<!--") {
+            let end = html[pos..].find("-->").map_or(html.len(), |i| pos + i + 3);
+            out.push_str(&html[pos..end]);
+            pos = end;
+            continue;
+        }
+
+        // 标签：合并空白，保留引号内的属性值
+        let tag_start = pos;
+        pos += 1;
+        let mut quote = None;
+        while pos < bytes.len() {
+            let b = bytes[pos];
+            match quote {
+                Some(q) if b == q => quote = None,
+                Some(_) => {}
+                None if b == b'"' || b == b'\'' => quote = Some(b),
+                None if b == b'>' => {
+                    pos += 1;
+                    break;
+                }
+                None => {}
+            }
+            pos += 1;
+        }
+        let tag = &html[tag_start..pos];
+        out.push_str(&collapse_tag_whitespace(tag));
+
+        // 原始文本元素的内容原样保留到结束标签
+        if let Some(name) = raw_text_element(tag) {
+            let closing = format!("</{}", name);
+            let end =
+                find_ignore_ascii_case(&html[pos..], &closing).map_or(html.len(), |i| pos + i);
+            out.push_str(&html[pos..end]);
+            pos = end;
+        }
+    }
+
+    out.trim().to_string()
+}
+
+/// 忽略 ASCII 大小写查找子串，返回字节偏移
+fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/// 合并标签内引号外的连续空白
+fn collapse_tag_whitespace(tag: &str) -> String {
+    let mut out = String::with_capacity(tag.len());
+    let mut quote = None;
+    let mut in_whitespace = false;
+
+    for ch in tag.chars() {
+        match quote {
+            Some(q) => {
+                out.push(ch);
+                if ch == q {
+                    quote = None;
+                }
+            }
+            None if ch.is_ascii_whitespace() => {
+                if !in_whitespace {
+                    out.push(' ');
+                    in_whitespace = true;
+                }
+                continue;
+            }
+            None => {
+                if ch == '"' || ch == '\'' {
+                    quote = Some(ch);
+                }
+                out.push(ch);
+            }
+        }
+        in_whitespace = false;
+    }
+
+    out
+}
+
+/// 若标签是原始文本元素的开始标签，返回元素名
+fn raw_text_element(tag: &str) -> Option<&'static str> {
+    let name = tag
+        .strip_prefix('<')?
+        .split(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+        .next()?;
+    RAW_TEXT_ELEMENTS
+        .iter()
+        .copied()
+        .find(|element| element.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minify_preserves_raw_text_elements_in_any_case() {
+        let html = "<div>\n  <PRE>  a\n   b </Pre>\n  <p>  x  </p>\n</div>";
+        assert_eq!(
+            minify_html(html),
+            "<div> <PRE>  a\n   b </Pre> <p> x </p> </div>"
+        );
+    }
+
+    #[derive(Template)]
+    #[template(source = "<p>{{ value }}</p>", ext = "html")]
+    struct ValueTemplate<T: std::fmt::Display> {
+        value: T,
+    }
+
+    /// 渲染时总是失败的值
+    struct Unrenderable;
+
+    impl std::fmt::Display for Unrenderable {
+        fn fmt(&self, _: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            Err(std::fmt::Error)
+        }
+    }
+
+    #[tokio::test]
+    async fn html_fragment_declares_utf8_html() {
+        let response = HtmlFragment(ValueTemplate { value: "片段" }).into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/html; charset=utf-8"
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], "<p>片段</p>".as_bytes());
+
+        let response = HtmlFragment(ValueTemplate {
+            value: Unrenderable,
+        })
+        .into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    fn prefixes(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn noindex_matches_whole_path_segments() {
+        let prefixes = prefixes(&["/app", "/api"]);
+        assert!(is_noindex_path("/app", &prefixes));
+        assert!(is_noindex_path("/app/todos", &prefixes));
+        assert!(!is_noindex_path("/apply", &prefixes));
+        assert!(!is_noindex_path("/", &prefixes));
+        assert!(is_noindex_path("/", &self::prefixes(&["/"])));
+    }
+
+    #[tokio::test]
+    async fn robots_tag_uses_path_relative_to_base_path() {
+        use axum::{body::Body, middleware, routing::get, Router};
+        use tower::ServiceExt;
+
+        let prefixes = Arc::<[String]>::from(prefixes(&["/app"]));
+        let routes = Router::new()
+            .route("/app/x", get(|| async { "ok" }))
+            .route("/apply", get(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(
+                prefixes,
+                robots_tag_middleware,
+            ));
+        let router = Router::new().nest("/app", routes);
+
+        let robots_tag = |uri: &'static str| {
+            let router = router.clone();
+            async move {
+                let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+                let response = router.oneshot(req).await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                response.headers().get("x-robots-tag").cloned()
+            }
+        };
+        // base_path 与 noindex 前缀同名时也只去掉一次
+        assert_eq!(robots_tag("/app/app/x").await.unwrap(), "noindex");
+        assert!(robots_tag("/app/apply").await.is_none());
+    }
+
+    /// 声明了长度但读取时出错的响应体
+    struct FailingBody;
+
+    impl HttpBody for FailingBody {
+        type Data = axum::body::Bytes;
+        type Error = std::io::Error;
+
+        fn poll_frame(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Option<Result<hyper::body::Frame<Self::Data>, Self::Error>>> {
+            std::task::Poll::Ready(Some(Err(std::io::Error::other("连接中断"))))
+        }
+
+        fn size_hint(&self) -> hyper::body::SizeHint {
+            hyper::body::SizeHint::with_exact(16)
+        }
+    }
+
+    async fn minified(content_type: &'static str, body: Body) -> Response {
+        use axum::{middleware, routing::get, Router};
+        use tower::ServiceExt;
+
+        // 处理器需要可克隆，响应体只取用一次
+        let body = Arc::new(std::sync::Mutex::new(Some(body)));
+        let router = Router::new()
+            .route(
+                "/",
+                get(move || async move {
+                    let body = body.lock().unwrap().take().unwrap();
+                    ([(header::CONTENT_TYPE, content_type)], body)
+                }),
+            )
+            .layer(middleware::from_fn_with_state(true, minify_html_middleware));
+        router
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn body_bytes(response: Response) -> axum::body::Bytes {
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn minify_middleware_compresses_html_only() {
+        let html = "<div>\n  <p>  x  </p>\n</div>";
+        let response = minified("text/html; charset=utf-8", Body::from(html)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(&body_bytes(response).await[..], b"<div> <p> x </p> </div>");
+
+        let json = "{\n  \"a\":  1\n}";
+        let response = minified("application/json", Body::from(json)).await;
+        assert_eq!(
+            response.headers()[header::CONTENT_LENGTH],
+            json.len().to_string()
+        );
+        assert_eq!(&body_bytes(response).await[..], json.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn minify_middleware_passes_oversized_html_through() {
+        let html = format!("<p>  {}  </p>", "x".repeat(MAX_MINIFY_BYTES as usize));
+        let response = minified("text/html", Body::from(html.clone())).await;
+        assert_eq!(
+            response.headers()[header::CONTENT_LENGTH],
+            html.len().to_string()
+        );
+        assert_eq!(&body_bytes(response).await[..], html.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn minify_middleware_returns_500_when_body_read_fails() {
+        let response = minified("text/html", Body::new(FailingBody)).await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!response.headers().contains_key(header::CONTENT_TYPE));
+        assert!(body_bytes(response).await.is_empty());
+    }
+}
@@ -1,10 +1,12 @@
 use askama::Template;
 use askama_axum::IntoResponse;
 
+use super::HtmlFragment;
+
 #[derive(Template)]
 #[template(path = "components/modal/base.html")]
 pub struct ModalExampleTemplate;
 
 pub async fn example() -> impl IntoResponse {
-    ModalExampleTemplate
+    HtmlFragment(ModalExampleTemplate)
 }
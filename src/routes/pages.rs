@@ -4,26 +4,49 @@
 
 use askama::Template;
 use askama_axum::IntoResponse;
-use axum::{http::StatusCode, Extension};
+use axum::{http::StatusCode, response::Response, Extension};
 use futures::future;
 use sqlx::SqlitePool;
 
+use crate::db::Db;
 // 导入缓存模块
-use crate::helpers::cache::{get_from_cache, invalidate_cache, set_to_cache};
+use crate::helpers::cache::{get_from_cache, set_to_cache};
+use crate::helpers::config::CONFIG;
+use crate::helpers::i18n::{Locale, Message};
+use crate::helpers::monitoring::track_db_query;
 
 // 导入其他模块的类型
-use super::todos::Todo;
-use super::users::User;
+use super::htmx::{boosted_fragment, full_page, CachePolicy, HxHeaders};
+use super::todos::{Todo, TodoStats};
+use super::users::{configured_user_sort, User};
+use super::{home_todo_stats_enabled, HtmlFragment};
 
 // 定义缓存键常量，避免硬编码
 pub const CACHE_KEY_TODOS: &str = "todos";
 pub const CACHE_KEY_USERS: &str = "users";
-pub const INITIAL_USERS_CACHE_KEY: &str = "initial_users";
+
+/// 首屏用户列表的缓存键，包含数量，修改 `ui.initial_users` 后不会读到按旧数量缓存的列表
+pub fn initial_users_cache_key(count: u32) -> String {
+    format!("initial_users:{}", count)
+}
+
+/// 按默认排序（`ui.default_user_sort`）获取首屏展示的前 `count` 个用户，页面和缓存预热共用
+pub async fn get_initial_users(pool: &SqlitePool, count: u32) -> Result<Vec<User>, sqlx::Error> {
+    let sql = format!(
+        "SELECT id, name, email FROM users ORDER BY {} LIMIT ?",
+        configured_user_sort().order_by()
+    );
+    track_db_query(
+        "users.initial",
+        sqlx::query_as::<_, User>(&sql).bind(count).fetch_all(pool),
+    )
+    .await
+}
 
 // 获取待办事项（带缓存）
 async fn get_todos_with_cache(pool: &SqlitePool) -> Result<(Vec<Todo>, usize, usize), sqlx::Error> {
     // 尝试从缓存获取
-    if let Some((todos, completed_count, pending_count)) = get_from_cache(CACHE_KEY_TODOS) {
+    if let Some((todos, completed_count, pending_count)) = get_from_cache(CACHE_KEY_TODOS).await {
         return Ok((todos, completed_count, pending_count));
     }
 
@@ -39,7 +62,8 @@ async fn get_todos_with_cache(pool: &SqlitePool) -> Result<(Vec<Todo>, usize, us
         CACHE_KEY_TODOS,
         (todos.clone(), stats.completed_count, stats.pending_count),
         Some(std::time::Duration::from_secs(900)),
-    );
+    )
+    .await;
 
     Ok((todos, stats.completed_count, stats.pending_count))
 }
@@ -47,7 +71,7 @@ async fn get_todos_with_cache(pool: &SqlitePool) -> Result<(Vec<Todo>, usize, us
 // 获取用户列表（带缓存）
 async fn get_users_with_cache(pool: &SqlitePool) -> Result<Vec<User>, sqlx::Error> {
     // 尝试从缓存获取
-    if let Some(users) = get_from_cache(CACHE_KEY_USERS) {
+    if let Some(users) = get_from_cache(CACHE_KEY_USERS).await {
         return Ok(users);
     }
 
@@ -59,7 +83,8 @@ async fn get_users_with_cache(pool: &SqlitePool) -> Result<Vec<User>, sqlx::Erro
         CACHE_KEY_USERS,
         users.clone(),
         Some(std::time::Duration::from_secs(600)),
-    );
+    )
+    .await;
 
     Ok(users)
 }
@@ -67,7 +92,12 @@ async fn get_users_with_cache(pool: &SqlitePool) -> Result<Vec<User>, sqlx::Erro
 // 完整页面模板（首次加载）
 #[derive(Template)]
 #[template(path = "modules/home/index.html")]
-pub struct IndexTemplate;
+pub struct IndexTemplate {
+    /// 是否显示待办统计区域（`ui.home_todo_stats`）
+    pub show_todo_stats: bool,
+    /// 开启 `ui.home_todo_stats` 时内联渲染的待办统计
+    pub todo_stats: Option<TodoStats>,
+}
 
 // 完整页面模板（包含 base.html，用于直接访问）
 #[derive(Template)]
@@ -87,7 +117,12 @@ pub struct UsersFullPageTemplate {
 // SPA 页面内容片段（不包含 base.html）
 #[derive(Template)]
 #[template(path = "modules/home/main.html")]
-pub struct HomePageTemplate;
+pub struct HomePageTemplate {
+    /// 是否显示待办统计区域（`ui.home_todo_stats`）
+    pub show_todo_stats: bool,
+    /// 开启 `ui.home_todo_stats` 时渲染的待办统计
+    pub todo_stats: Option<TodoStats>,
+}
 
 #[derive(Template)]
 #[template(path = "modules/todos/main.html")]
@@ -103,104 +138,329 @@ pub struct UsersPageTemplate {
     pub users: Vec<User>,
 }
 
-/// 首次访问返回完整页面
-pub async fn index() -> impl IntoResponse {
-    IndexTemplate
+/// 首次访问返回完整页面，boosted 导航只返回主内容片段
+pub async fn index(
+    hx: HxHeaders,
+    Extension(Db { read: pool, .. }): Extension<Db>,
+) -> impl IntoResponse {
+    if hx.is_boosted_navigation() {
+        boosted_fragment(home_fragment(&pool).await)
+    } else {
+        let show_todo_stats = home_todo_stats_enabled();
+        full_page(IndexTemplate {
+            show_todo_stats,
+            todo_stats: load_home_todo_stats(&pool, show_todo_stats).await,
+        })
+    }
 }
 
-/// 直接访问 /todos 返回完整页面
-pub async fn todos_page(Extension(pool): Extension<SqlitePool>) -> impl IntoResponse {
+/// 直接访问 /todos 返回完整页面，boosted 导航只返回主内容片段
+pub async fn todos_page(
+    hx: HxHeaders,
+    locale: Locale,
+    Extension(Db { read: pool, .. }): Extension<Db>,
+) -> impl IntoResponse {
     match get_todos_with_cache(&pool).await {
-        Ok((todos, completed_count, pending_count)) => TodosFullPageTemplate {
+        Ok((todos, completed_count, pending_count)) if hx.is_boosted_navigation() => {
+            boosted_fragment(TodosPageTemplate {
+                todos,
+                completed_count,
+                pending_count,
+            })
+        }
+        Ok((todos, completed_count, pending_count)) => full_page(TodosFullPageTemplate {
             todos,
             completed_count,
             pending_count,
-        }
-        .into_response(),
+        }),
         Err(e) => {
             tracing::error!("获取待办事项失败: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "获取数据失败，请稍后重试",
-            )
-                .into_response()
+            Message::LoadDataFailed.into_response(StatusCode::INTERNAL_SERVER_ERROR, locale)
         }
     }
 }
 
-/// 直接访问 /users 返回完整页面
-pub async fn users_page(Extension(pool): Extension<SqlitePool>) -> impl IntoResponse {
+/// 直接访问 /users 返回完整页面，boosted 导航只返回主内容片段
+pub async fn users_page(
+    hx: HxHeaders,
+    locale: Locale,
+    Extension(Db { read: pool, .. }): Extension<Db>,
+) -> impl IntoResponse {
     match get_users_with_cache(&pool).await {
-        Ok(users) => UsersFullPageTemplate { users }.into_response(),
+        Ok(users) if hx.is_boosted_navigation() => boosted_fragment(UsersPageTemplate { users }),
+        Ok(users) => full_page(UsersFullPageTemplate { users }),
         Err(e) => {
             tracing::error!("获取用户列表失败: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "获取数据失败，请稍后重试",
-            )
-                .into_response()
+            Message::LoadDataFailed.into_response(StatusCode::INTERNAL_SERVER_ERROR, locale)
         }
     }
 }
 
 /// SPA 页面内容 - 首页
-pub async fn page_home() -> impl IntoResponse {
-    HomePageTemplate
+pub async fn page_home(Extension(Db { read: pool, .. }): Extension<Db>) -> impl IntoResponse {
+    home_fragment(&pool).await
 }
 
-/// SPA 页面内容 - 待办事项
-pub async fn page_todos(Extension(pool): Extension<SqlitePool>) -> impl IntoResponse {
+/// 首页片段
+///
+/// 未开启 `ui.home_todo_stats` 时是静态内容，允许公开缓存；开启后统计区域随片段一起渲染，
+/// 换入后立即显示，不再等到第一次修改待办，此时响应禁止缓存。
+///
+/// 统计区域在片段内部，不能用 OOB 填充：htmx 先执行 OOB 交换再换入主内容，OOB 会找不到目标
+async fn home_fragment(pool: &SqlitePool) -> Response {
+    home_fragment_with(pool, home_todo_stats_enabled()).await
+}
+
+/// 按给定的统计开关渲染首页片段
+async fn home_fragment_with(pool: &SqlitePool, show_todo_stats: bool) -> Response {
+    let page = HtmlFragment(HomePageTemplate {
+        show_todo_stats,
+        todo_stats: load_home_todo_stats(pool, show_todo_stats).await,
+    });
+    if show_todo_stats {
+        CachePolicy::NoStore.apply(page)
+    } else {
+        CachePolicy::Public(300).apply(page)
+    }
+}
+
+/// 开启统计时加载首页的待办统计，失败时记录警告并返回 `None`（显示占位）
+async fn load_home_todo_stats(pool: &SqlitePool, enabled: bool) -> Option<TodoStats> {
+    if !enabled {
+        return None;
+    }
+    super::todos::get_stats(pool)
+        .await
+        .inspect_err(|e| tracing::warn!("获取首页待办统计失败: {}", e))
+        .ok()
+}
+
+/// SPA 页面内容 - 待办事项（数据频繁变化，禁止缓存）
+pub async fn page_todos(
+    locale: Locale,
+    Extension(Db { read: pool, .. }): Extension<Db>,
+) -> impl IntoResponse {
     match get_todos_with_cache(&pool).await {
-        Ok((todos, completed_count, pending_count)) => TodosPageTemplate {
-            todos,
-            completed_count,
-            pending_count,
+        Ok((todos, completed_count, pending_count)) => {
+            CachePolicy::NoStore.apply(HtmlFragment(TodosPageTemplate {
+                todos,
+                completed_count,
+                pending_count,
+            }))
         }
-        .into_response(),
         Err(e) => {
             tracing::error!("获取待办事项失败: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "获取数据失败，请稍后重试",
-            )
-                .into_response()
+            Message::LoadDataFailed.into_response(StatusCode::INTERNAL_SERVER_ERROR, locale)
         }
     }
 }
 
 /// SPA 页面内容 - 用户列表
-pub async fn page_users(Extension(pool): Extension<SqlitePool>) -> impl IntoResponse {
+pub async fn page_users(
+    locale: Locale,
+    Extension(Db { read: pool, .. }): Extension<Db>,
+) -> impl IntoResponse {
     // 使用专门的缓存键存储初始用户列表，避免缓存整个用户列表
+    let count = CONFIG.ui.initial_users;
+    let cache_key = initial_users_cache_key(count);
 
     // 尝试从缓存获取初始用户列表
-    if let Some(users) = get_from_cache(INITIAL_USERS_CACHE_KEY) {
-        return UsersPageTemplate { users }.into_response();
+    if let Some(users) = get_from_cache(&cache_key).await {
+        return HtmlFragment(UsersPageTemplate { users }).into_response();
     }
 
-    // 缓存未命中，从数据库获取前12个用户
-    let users = sqlx::query_as::<_, User>("SELECT id, name, email FROM users ORDER BY id LIMIT 12")
-        .fetch_all(&pool)
-        .await
-        .unwrap_or_default();
+    // 缓存未命中，从数据库获取前 `ui.initial_users` 个用户
+    let users = match get_initial_users(&pool, count).await {
+        Ok(users) => users,
+        Err(e) => {
+            tracing::error!("获取初始用户列表失败: {}", e);
+            return Message::LoadDataFailed
+                .into_response(StatusCode::INTERNAL_SERVER_ERROR, locale);
+        }
+    };
 
     // 缓存初始用户列表，设置较短的过期时间（5分钟）
     set_to_cache(
-        INITIAL_USERS_CACHE_KEY,
+        &cache_key,
         users.clone(),
         Some(std::time::Duration::from_secs(300)),
-    );
+    )
+    .await;
 
-    UsersPageTemplate { users }.into_response()
+    HtmlFragment(UsersPageTemplate { users }).into_response()
 }
 
-// 导出缓存失效函数，供其他模块调用
-pub fn invalidate_todo_cache() {
-    // 使待办事项缓存失效
-    invalidate_cache(CACHE_KEY_TODOS);
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, extract::Request, routing::get, Router};
+    use sqlx::sqlite::SqlitePoolOptions;
+    use tower::ServiceExt;
+
+    /// 单连接的内存数据库，只含 `users` 表和一个用户
+    async fn pool_with_user(name: &str) -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, email TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO users (name, email) VALUES (?, 'user@example.com')")
+            .bind(name)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn users_page_reads_from_read_pool() {
+        let db = Db {
+            write: pool_with_user("来自主库").await,
+            read: pool_with_user("来自副本").await,
+        };
+        let app = Router::new()
+            .route("/app/users", get(page_users))
+            .layer(Extension(db));
+
+        let response = app
+            .oneshot(Request::get("/app/users").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let html = String::from_utf8(body.to_vec()).unwrap();
+        assert!(html.contains("来自副本"));
+        assert!(!html.contains("来自主库"));
+    }
+
+    #[tokio::test]
+    async fn initial_users_returns_requested_count() {
+        let pool = pool_with_user("用户 1").await;
+        for i in 2..=15 {
+            sqlx::query("INSERT INTO users (name, email) VALUES (?, 'user@example.com')")
+                .bind(format!("用户 {}", i))
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(get_initial_users(&pool, 1).await.unwrap().len(), 1);
+        assert_eq!(get_initial_users(&pool, 12).await.unwrap().len(), 12);
+        assert_eq!(get_initial_users(&pool, 100).await.unwrap().len(), 15);
+        assert_ne!(initial_users_cache_key(12), initial_users_cache_key(24));
+    }
+
+    #[tokio::test]
+    async fn load_failure_body_follows_accept_language() {
+        // 没有 todos 表，读取必然失败
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let app = Router::new()
+            .route("/todos", get(todos_page))
+            .route("/app/todos", get(page_todos))
+            .layer(Extension(Db {
+                write: pool.clone(),
+                read: pool,
+            }));
+
+        for uri in ["/todos", "/app/todos"] {
+            for (accept_language, content_language, expected) in [
+                (
+                    Some("en-US,en;q=0.9"),
+                    "en",
+                    Message::LoadDataFailed.text(Locale::En),
+                ),
+                (None, "zh-CN", Message::LoadDataFailed.text(Locale::Zh)),
+            ] {
+                let mut req = Request::get(uri);
+                if let Some(accept_language) = accept_language {
+                    req = req.header("accept-language", accept_language);
+                }
+                let response = app
+                    .clone()
+                    .oneshot(req.body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+                assert_eq!(response.headers()["content-language"], content_language);
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    &body[..],
+                    expected.as_bytes(),
+                    "{} {:?}",
+                    uri,
+                    accept_language
+                );
+            }
+        }
+        assert_eq!(
+            Message::LoadDataFailed.text(Locale::En),
+            "Failed to load data, please try again later"
+        );
+    }
+
+    #[tokio::test]
+    async fn home_renders_todo_stats_on_first_load() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        crate::db::run_migrations(&pool, &crate::helpers::config::DatabaseConfig::default())
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO todos (title, completed) VALUES ('待办 1', 1), ('待办 2', 0), ('待办 3', 0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // SPA 首次加载的首页片段：统计随片段一起渲染，不缓存
+        let response = home_fragment_with(&pool, true).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["cache-control"], "no-store");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let html = String::from_utf8(body.to_vec()).unwrap();
+        for (id, count) in [
+            ("total-count", 3),
+            ("completed-count", 1),
+            ("pending-count", 2),
+        ] {
+            let start = html.find(&format!("id=\"{}\"", id)).unwrap();
+            let span = &html[start..start + html[start..].find("</span").unwrap()];
+            assert!(span.ends_with(&format!(">{}", count)), "{}: {}", id, span);
+        }
 
-#[allow(dead_code)]
-pub fn invalidate_user_cache() {
-    // 使用户缓存失效
-    invalidate_cache(CACHE_KEY_USERS);
+        // 直接访问的完整页面同样内联统计
+        let html = IndexTemplate {
+            show_todo_stats: true,
+            todo_stats: load_home_todo_stats(&pool, true).await,
+        }
+        .render()
+        .unwrap();
+        assert!(html.contains("id=\"total-count\""));
+
+        // 关闭时不渲染统计区域
+        let response = home_fragment_with(&pool, false).await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(!String::from_utf8(body.to_vec())
+            .unwrap()
+            .contains("total-count"));
+    }
 }
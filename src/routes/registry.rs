@@ -0,0 +1,42 @@
+//! 路由登记表
+//!
+//! axum 不对外暴露已注册的路由，`build_router` 添加路由时同时在这里登记方法和路径，
+//! 供开发环境的 `GET /admin/routes` 列出
+
+use serde::Serialize;
+
+/// 一条已注册的路由
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteInfo {
+    pub method: &'static str,
+    /// 路由模式（如 `/block/users/:id/detail`），不含 `base_path`
+    pub path: String,
+}
+
+/// 构建路由时登记的路由表
+#[derive(Debug, Clone, Default)]
+pub struct RouteRegistry {
+    routes: Vec<RouteInfo>,
+}
+
+impl RouteRegistry {
+    /// 登记一条路由并原样返回路径，可直接写在 `.route(...)` 的参数中：
+    ///
+    /// ```
+    /// .route(registry.add("GET", "/app"), get(routes::pages::index))
+    /// ```
+    pub fn add<'a>(&mut self, method: &'static str, path: &'a str) -> &'a str {
+        self.routes.push(RouteInfo {
+            method,
+            path: path.to_string(),
+        });
+        path
+    }
+
+    /// 按路径排序的路由列表
+    pub fn sorted(&self) -> Vec<RouteInfo> {
+        let mut routes = self.routes.clone();
+        routes.sort_by(|a, b| a.path.cmp(&b.path).then(a.method.cmp(b.method)));
+        routes
+    }
+}
@@ -4,12 +4,25 @@
 
 use axum::{
     body::Body,
-    http::{header, StatusCode, Uri},
+    http::{header, HeaderMap, Method, StatusCode, Uri},
     response::{IntoResponse, Response},
 };
-use rust_embed::RustEmbed;
+use rust_embed::{EmbeddedFile, RustEmbed};
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Mutex;
+
+use crate::helpers::config::CONFIG;
+
+/// favicon 地址固定、更换不频繁，缓存 30 天
+const FAVICON_CACHE_CONTROL: &str = "public, max-age=2592000";
+
+lazy_static::lazy_static! {
+    /// 按路径缓存的 ETag，每个文件只计算一次
+    static ref ETAG_CACHE: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+}
 
 /// 静态资源处理错误
 #[derive(Debug)]
@@ -76,14 +89,65 @@ fn get_cache_control(path: &str) -> &'static str {
     }
 }
 
+/// 获取文件的 ETag
+///
+/// 基于内容哈希生成，算法和强弱由 `static_assets` 配置决定，结果按路径缓存
+fn etag_for(path: &str, content: &EmbeddedFile) -> String {
+    if let Some(etag) = ETAG_CACHE.lock().unwrap().get(path) {
+        return etag.clone();
+    }
+
+    let config = &CONFIG.static_assets;
+    let hash = match config.etag_algorithm.to_lowercase().as_str() {
+        "siphash" => {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            content.data.hash(&mut hasher);
+            format!("{:016x}", hasher.finish())
+        }
+        // sha256 由 rust-embed 在编译期计算，取前 16 字节
+        _ => content.metadata.sha256_hash()[..16]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+    };
+
+    let etag = if config.etag_weak {
+        format!("W/\"{}\"", hash)
+    } else {
+        format!("\"{}\"", hash)
+    };
+
+    ETAG_CACHE
+        .lock()
+        .unwrap()
+        .insert(path.to_string(), etag.clone());
+    etag
+}
+
+/// 检查 `If-None-Match` 是否命中（弱比较，忽略 `W/` 前缀）
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    let strip_weak = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = strip_weak(etag);
+
+    headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| {
+            v.split(',')
+                .any(|candidate| candidate.trim() == "*" || strip_weak(candidate) == etag)
+        })
+}
+
 /// 处理静态文件请求
 ///
 /// # Parameters
 /// - `uri`: 请求的 URI
+/// - `method`: 请求方法，HEAD 请求只返回头部，不构建响应体
+/// - `headers`: 请求头，用于 `If-None-Match` 条件请求
 ///
 /// # Returns
-/// 返回对应的静态文件或错误响应
-pub async fn static_handler(uri: Uri) -> impl IntoResponse {
+/// 返回对应的静态文件、304 或错误响应
+pub async fn static_handler(uri: Uri, method: Method, headers: HeaderMap) -> impl IntoResponse {
     let path = uri.path().trim_start_matches("/static/");
 
     // 1. 路径安全检查
@@ -103,25 +167,35 @@ pub async fn static_handler(uri: Uri) -> impl IntoResponse {
     // 2. 获取静态资源
     match StaticAssets::get(path) {
         Some(content) => {
-            // 3. 确定文件类型
+            // 3. 基于内容哈希的 ETag，命中条件请求时返回 304
+            let etag = etag_for(path, &content);
+            if etag_matches(&headers, &etag) {
+                return Response::builder()
+                    .status(StatusCode::NOT_MODIFIED)
+                    .header(header::ETAG, etag)
+                    .header(header::CACHE_CONTROL, get_cache_control(path))
+                    .body(Body::empty())
+                    .unwrap();
+            }
+
+            // 4. 确定文件类型
             let mime = mime_guess::from_path(path).first_or_octet_stream();
 
-            // 4. 创建响应
-            let mut response_builder = Response::builder()
+            // 5. 创建并返回响应，HEAD 请求只声明长度不携带内容
+            let content_length = content.data.len();
+            let body = if method == Method::HEAD {
+                Body::empty()
+            } else {
+                Body::from(content.data)
+            };
+
+            Response::builder()
                 .status(StatusCode::OK)
                 .header(header::CONTENT_TYPE, mime.as_ref())
-                .header(header::CACHE_CONTROL, get_cache_control(path));
-
-            // 5. 对于文本类文件，可以考虑添加ETag支持
-            if mime.type_() == "text" || mime.subtype() == "javascript" || mime.subtype() == "css" {
-                // 生成简单的ETag（基于内容长度）
-                let etag = format!("\"{}\"", content.data.len());
-                response_builder = response_builder.header(header::ETAG, etag);
-            }
-
-            // 6. 返回响应
-            response_builder
-                .body(Body::from(content.data))
+                .header(header::CACHE_CONTROL, get_cache_control(path))
+                .header(header::ETAG, etag)
+                .header(header::CONTENT_LENGTH, content_length)
+                .body(body)
                 .unwrap_or_else(|e| {
                     tracing::error!("创建静态文件响应失败: {}", e);
                     Response::builder()
@@ -139,3 +213,150 @@ pub async fn static_handler(uri: Uri) -> impl IntoResponse {
         }
     }
 }
+
+/// 处理 `/favicon.ico` 请求
+///
+/// 返回 `static_assets.favicon_path` 指向的嵌入资源（默认 `static/favicon.ico`），
+/// 使用长缓存并支持条件请求；`favicon_enabled = false` 时返回 204，避免浏览器反复请求产生 404 日志
+pub async fn favicon_handler(method: Method, headers: HeaderMap) -> impl IntoResponse {
+    let config = &CONFIG.static_assets;
+    if !config.favicon_enabled {
+        return Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .header(header::CACHE_CONTROL, FAVICON_CACHE_CONTROL)
+            .body(Body::empty())
+            .unwrap();
+    }
+
+    let path = config.favicon_path.as_str();
+    let Some(content) = StaticAssets::get(path) else {
+        tracing::warn!("⚠️  favicon 资源不存在: static/{}", path);
+        return Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::from("404 Not Found"))
+            .unwrap();
+    };
+
+    let etag = etag_for(path, &content);
+    if etag_matches(&headers, &etag) {
+        return Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, etag)
+            .header(header::CACHE_CONTROL, FAVICON_CACHE_CONTROL)
+            .body(Body::empty())
+            .unwrap();
+    }
+
+    let mime = mime_guess::from_path(path).first_or_octet_stream();
+    let content_length = content.data.len();
+    let body = if method == Method::HEAD {
+        Body::empty()
+    } else {
+        Body::from(content.data)
+    };
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, mime.as_ref())
+        .header(header::CACHE_CONTROL, FAVICON_CACHE_CONTROL)
+        .header(header::ETAG, etag)
+        .header(header::CONTENT_LENGTH, content_length)
+        .body(body)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 长度相同、内容不同的测试文件
+    #[derive(RustEmbed)]
+    #[folder = "tests/fixtures/etag/"]
+    struct EtagFixtures;
+
+    async fn get(uri: &str, if_none_match: Option<&str>) -> Response {
+        let mut headers = HeaderMap::new();
+        if let Some(etag) = if_none_match {
+            headers.insert(header::IF_NONE_MATCH, etag.parse().unwrap());
+        }
+        static_handler(uri.parse().unwrap(), Method::GET, headers)
+            .await
+            .into_response()
+    }
+
+    #[test]
+    fn same_length_files_get_different_etags() {
+        let a = EtagFixtures::get("a.css").unwrap();
+        let b = EtagFixtures::get("b.css").unwrap();
+        assert_eq!(a.data.len(), b.data.len());
+
+        let etag_a = etag_for("fixtures/etag/a.css", &a);
+        let etag_b = etag_for("fixtures/etag/b.css", &b);
+        assert_ne!(etag_a, etag_b);
+        // 默认配置为强 ETag
+        assert!(
+            etag_a.starts_with('"') && etag_a.ends_with('"'),
+            "{}",
+            etag_a
+        );
+    }
+
+    #[tokio::test]
+    async fn serves_assets_with_etag_and_cache_headers() {
+        let response = get("/static/css/style.css", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers();
+        assert_eq!(headers[header::CONTENT_TYPE], "text/css");
+        assert_eq!(
+            headers[header::CACHE_CONTROL],
+            "public, max-age=31536000, immutable"
+        );
+        let etag = headers[header::ETAG].to_str().unwrap().to_string();
+
+        let response = get("/static/css/style.css", Some(&etag)).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+
+        // 弱比较：带 W/ 前缀的同一标签也命中
+        let weak = format!("W/{}", etag);
+        let response = get("/static/css/style.css", Some(&weak)).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        let other = get("/static/css/official.css", None).await;
+        assert_ne!(other.headers()[header::ETAG], etag.as_str());
+        let response = get("/static/css/style.css", Some("\"stale\"")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn head_returns_headers_without_body() {
+        let get_response = get("/static/css/style.css", None).await;
+        let head_response = static_handler(
+            "/static/css/style.css".parse().unwrap(),
+            Method::HEAD,
+            HeaderMap::new(),
+        )
+        .await
+        .into_response();
+
+        assert_eq!(head_response.status(), StatusCode::OK);
+        for name in [header::CONTENT_LENGTH, header::ETAG, header::CONTENT_TYPE] {
+            assert_eq!(
+                head_response.headers()[&name],
+                get_response.headers()[&name]
+            );
+        }
+        assert_eq!(
+            head_response.headers()[header::CONTENT_LENGTH],
+            StaticAssets::get("css/style.css")
+                .unwrap()
+                .data
+                .len()
+                .to_string()
+        );
+        let body = axum::body::to_bytes(head_response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.is_empty());
+    }
+}
@@ -1,17 +1,26 @@
 use askama::Template;
 use askama_axum::IntoResponse;
 use axum::{
-    extract::{Extension, Path},
+    extract::{Extension, Path, Query},
     http::StatusCode,
-    Form,
+    response::Response,
+    Json,
 };
-use serde::Deserialize;
-use sqlx::SqlitePool;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqliteExecutor, SqlitePool};
 
-// 导入缓存失效函数
-use super::pages::invalidate_todo_cache;
+use super::extract::{CsvBody, StrictForm};
+use super::htmx::{deleted, CachePolicy, HxHeaders};
+use super::{url, BatchTooLarge, EmptyStateTemplate, HtmlFragment};
+use crate::db::{like_pattern, returning_supported, start_transaction, Db, DbError};
+use crate::helpers::cache::invalidate_entity;
+use crate::helpers::monitoring::track_db_query;
+use crate::helpers::pagination::{
+    calculate_display_range, create_pagination, PageQuery, Pagination,
+};
+use crate::helpers::security::normalize_search_query;
 
-#[derive(Clone, Debug, sqlx::FromRow)]
+#[derive(Clone, Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Todo {
     pub id: i64,
     pub title: String,
@@ -28,6 +37,19 @@ pub struct TodoItemTemplate {
 #[template(path = "modules/todos/create_form.html")]
 pub struct CreateFormTemplate;
 
+/// 待办页面骨架屏，不含数据
+#[derive(Template)]
+#[template(path = "modules/todos/skeleton.html")]
+pub struct TodosSkeletonTemplate;
+
+/// 待办统计数据，与展示无关，可直接序列化为 JSON
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct TodoStats {
+    pub total_count: usize,
+    pub completed_count: usize,
+    pub pending_count: usize,
+}
+
 #[derive(Template)]
 #[template(path = "modules/todos/stats.html")]
 pub struct TodoStatsTemplate {
@@ -36,67 +58,309 @@ pub struct TodoStatsTemplate {
     pub pending_count: usize,
 }
 
+impl From<TodoStats> for TodoStatsTemplate {
+    fn from(stats: TodoStats) -> Self {
+        Self {
+            total_count: stats.total_count,
+            completed_count: stats.completed_count,
+            pending_count: stats.pending_count,
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "modules/todos/search_results.html")]
+pub struct TodoSearchResultsTemplate {
+    pub todos: Vec<Todo>,
+    pub query: String,
+    pub pagination: Pagination,
+    pub start_item: i64,
+    pub end_item: i64,
+    pub base_url: String,
+    pub target: String,
+    pub empty_state: EmptyStateTemplate,
+}
+
 #[derive(Deserialize)]
 pub struct CreateTodoForm {
     title: String,
 }
 
+#[derive(Deserialize)]
+pub struct TodoSearchQuery {
+    q: Option<String>,
+    page: Option<i64>,
+    per_page: Option<i64>,
+}
+
 /// 从数据库获取所有待办事项
 /// 使用预编译查询和索引优化性能
 pub async fn get_todos(pool: &SqlitePool) -> Result<Vec<Todo>, sqlx::Error> {
     // 使用预编译查询并利用idx_todos_id_desc索引
-    sqlx::query_as::<_, Todo>("SELECT id, title, completed FROM todos ORDER BY id DESC")
-        .fetch_all(pool)
-        .await
+    track_db_query(
+        "todos.list",
+        sqlx::query_as::<_, Todo>("SELECT id, title, completed FROM todos ORDER BY id DESC")
+            .fetch_all(pool),
+    )
+    .await
 }
 
 /// 获取统计信息 - 直接通过SQL查询统计数据，避免加载所有记录到内存
-pub async fn get_stats(pool: &SqlitePool) -> Result<TodoStatsTemplate, sqlx::Error> {
+/// 既可以传入连接池，也可以传入事务，以便在写操作的同一事务中读取统计
+pub async fn get_stats<'e, E>(executor: E) -> Result<TodoStats, sqlx::Error>
+where
+    E: SqliteExecutor<'e>,
+{
     // 使用单个SQL查询获取所有统计数据，避免加载所有记录
-    let (total_count, completed_count): (i64, i64) = sqlx::query_as(
-        "SELECT COUNT(*), SUM(CASE WHEN completed = 1 THEN 1 ELSE 0 END) FROM todos",
+    let (total_count, completed_count): (i64, i64) = track_db_query("todos.stats", sqlx::query_as(
+        "SELECT COUNT(*), COALESCE(SUM(CASE WHEN completed = 1 THEN 1 ELSE 0 END), 0) FROM todos",
     )
-    .fetch_one(pool)
-    .await?;
+    .fetch_one(executor)).await?;
 
     let total_count = total_count as usize;
     let completed_count = completed_count as usize;
     let pending_count = total_count - completed_count;
 
-    Ok(TodoStatsTemplate {
+    Ok(TodoStats {
         total_count,
         completed_count,
         pending_count,
     })
 }
 
+/// 待办统计（JSON），供仪表盘轮询
+pub async fn stats(Extension(Db { read: pool, .. }): Extension<Db>) -> Response {
+    match get_stats(&pool).await {
+        Ok(stats) => CachePolicy::NoStore.apply(Json(stats)),
+        Err(e) => {
+            tracing::error!("❌ 查询待办统计失败: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "查询待办统计失败").into_response()
+        }
+    }
+}
+
+/// 插入待办事项并返回写入的行
+///
+/// 支持 `RETURNING` 时一条语句完成，否则插入后按 `last_insert_rowid()` 查询（见 [`returning_supported`]）
+async fn insert_todo(conn: &mut SqliteConnection, title: &str) -> Result<Todo, sqlx::Error> {
+    insert_todo_with(conn, title, returning_supported()).await
+}
+
+/// 按指定方式插入待办事项，`use_returning` 为 false 时走回退路径
+async fn insert_todo_with(
+    conn: &mut SqliteConnection,
+    title: &str,
+    use_returning: bool,
+) -> Result<Todo, sqlx::Error> {
+    if use_returning {
+        return track_db_query(
+            "todos.insert",
+            sqlx::query_as::<_, Todo>(
+                "INSERT INTO todos (title, completed) VALUES (?, 0) RETURNING id, title, completed",
+            )
+            .bind(title)
+            .fetch_one(&mut *conn),
+        )
+        .await;
+    }
+
+    let id = track_db_query(
+        "todos.insert",
+        sqlx::query("INSERT INTO todos (title, completed) VALUES (?, 0)")
+            .bind(title)
+            .execute(&mut *conn),
+    )
+    .await?
+    .last_insert_rowid();
+    get_todo(conn, id).await
+}
+
+/// 切换完成状态并返回更新后的行，待办不存在时返回 `RowNotFound`
+async fn toggle_todo(conn: &mut SqliteConnection, id: i64) -> Result<Todo, sqlx::Error> {
+    toggle_todo_with(conn, id, returning_supported()).await
+}
+
+/// 按指定方式切换完成状态，`use_returning` 为 false 时走回退路径
+async fn toggle_todo_with(
+    conn: &mut SqliteConnection,
+    id: i64,
+    use_returning: bool,
+) -> Result<Todo, sqlx::Error> {
+    if use_returning {
+        return track_db_query(
+            "todos.toggle",
+            sqlx::query_as::<_, Todo>(
+                "UPDATE todos SET completed = NOT completed WHERE id = ? RETURNING id, title, completed",
+            )
+            .bind(id)
+            .fetch_one(&mut *conn),
+        )
+        .await;
+    }
+
+    let updated = track_db_query(
+        "todos.toggle",
+        sqlx::query("UPDATE todos SET completed = NOT completed WHERE id = ?")
+            .bind(id)
+            .execute(&mut *conn),
+    )
+    .await?
+    .rows_affected();
+    if updated == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    get_todo(conn, id).await
+}
+
+/// 按 id 读取待办事项（`RETURNING` 不可用时的回退路径）
+async fn get_todo(conn: &mut SqliteConnection, id: i64) -> Result<Todo, sqlx::Error> {
+    track_db_query(
+        "todos.get",
+        sqlx::query_as::<_, Todo>("SELECT id, title, completed FROM todos WHERE id = ?")
+            .bind(id)
+            .fetch_one(conn),
+    )
+    .await
+}
+
+/// 按标题搜索待办事项（分页）
+/// 搜索词为空时返回完整列表，排序利用idx_todos_id_desc索引
+pub async fn search(
+    hx: HxHeaders,
+    Extension(Db { read: pool, .. }): Extension<Db>,
+    Query(params): Query<TodoSearchQuery>,
+) -> impl IntoResponse {
+    let query = normalize_search_query(params.q.as_deref().unwrap_or_default());
+    tracing::debug!("待办搜索: {:?}", query);
+
+    // 使用公共分页模块处理分页参数
+    let page_query = PageQuery {
+        page: params.page,
+        per_page: params.per_page,
+    };
+    if let Err(e) = page_query.validate() {
+        return e.into_response_for(&hx);
+    }
+
+    let page = page_query.get_page();
+    let per_page = page_query.get_per_page();
+    let offset = page_query.get_offset();
+
+    let result = async {
+        if query.is_empty() {
+            let total: i64 = track_db_query(
+                "todos.count",
+                sqlx::query_scalar("SELECT COUNT(*) FROM todos").fetch_one(&pool),
+            )
+            .await?;
+            let todos = track_db_query(
+                "todos.page",
+                sqlx::query_as::<_, Todo>(
+                    "SELECT id, title, completed FROM todos ORDER BY id DESC LIMIT ? OFFSET ?",
+                )
+                .bind(per_page)
+                .bind(offset)
+                .fetch_all(&pool),
+            )
+            .await?;
+            Ok::<_, sqlx::Error>((total, todos))
+        } else {
+            // 转义通配符，避免用户输入的 % 和 _ 改变匹配语义
+            let search_pattern = like_pattern(&query);
+            let total: i64 = track_db_query(
+                "todos.search_count",
+                sqlx::query_scalar("SELECT COUNT(*) FROM todos WHERE title LIKE ? ESCAPE '\\'")
+                    .bind(&search_pattern)
+                    .fetch_one(&pool),
+            )
+            .await?;
+            let todos = track_db_query(
+                "todos.search",
+                sqlx::query_as::<_, Todo>(
+                    "SELECT id, title, completed FROM todos \
+                 WHERE title LIKE ? ESCAPE '\\' \
+                 ORDER BY id DESC LIMIT ? OFFSET ?",
+                )
+                .bind(&search_pattern)
+                .bind(per_page)
+                .bind(offset)
+                .fetch_all(&pool),
+            )
+            .await?;
+            Ok((total, todos))
+        }
+    }
+    .await;
+
+    match result {
+        Ok((total, todos)) => {
+            // 使用公共分页模块创建分页信息和显示范围
+            let pagination = create_pagination(page, per_page, total);
+            let (start_item, end_item) = calculate_display_range(page, per_page, todos.len());
+
+            // 无结果时：清空搜索返回的是完整列表，说明还没有任何任务
+            let empty_state = if query.is_empty() {
+                EmptyStateTemplate::no_todos()
+            } else {
+                EmptyStateTemplate::new(
+                    "search",
+                    "未找到匹配的任务",
+                    format!("搜索关键词: \"{}\"，试试其他关键词", query),
+                )
+            };
+
+            // 搜索结果随数据变化，禁止缓存
+            CachePolicy::NoStore.apply(HtmlFragment(TodoSearchResultsTemplate {
+                todos,
+                query,
+                pagination,
+                start_item,
+                end_item,
+                base_url: url("/block/todos/search"),
+                target: "#todo-list".to_string(),
+                empty_state,
+            }))
+        }
+        Err(e) => {
+            tracing::error!("搜索待办事项失败: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "搜索失败").into_response()
+        }
+    }
+}
+
+/// 创建表单是静态片段，允许公开缓存
 pub async fn create_form() -> impl IntoResponse {
-    CreateFormTemplate
+    CachePolicy::Public(3600).apply(HtmlFragment(CreateFormTemplate))
+}
+
+/// 骨架屏是静态片段，允许公开缓存
+pub async fn skeleton() -> impl IntoResponse {
+    CachePolicy::Public(3600).apply(HtmlFragment(TodosSkeletonTemplate))
 }
 
 pub async fn create(
-    Extension(pool): Extension<SqlitePool>,
-    Form(form): Form<CreateTodoForm>,
+    Extension(Db { write: pool, .. }): Extension<Db>,
+    StrictForm(form): StrictForm<CreateTodoForm>,
 ) -> impl IntoResponse {
-    let result = sqlx::query_as::<_, Todo>(
-        "INSERT INTO todos (title, completed) VALUES (?, 0) RETURNING id, title, completed",
-    )
-    .bind(&form.title)
-    .fetch_one(&pool)
+    // 写入和统计读取放在同一事务中，提交后再渲染
+    let result = async {
+        let mut tx = start_transaction(&pool).await?;
+
+        let todo = insert_todo(&mut tx, &form.title).await?;
+        let stats = get_stats(&mut *tx).await?;
+
+        tx.commit().await?;
+        Ok::<_, DbError>((todo, stats))
+    }
     .await;
 
     match result {
-        Ok(todo) => {
-            // 数据变更，使缓存失效
-            invalidate_todo_cache();
-
-            let stats = get_stats(&pool).await.unwrap_or(TodoStatsTemplate {
-                total_count: 0,
-                completed_count: 0,
-                pending_count: 0,
-            });
+        Ok((todo, stats)) => {
+            // 数据变更，使列表、搜索等相关缓存一起失效
+            invalidate_entity("todos").await;
+
             let todo_html = TodoItemTemplate { todo }.render().unwrap_or_default();
-            let stats_html = stats.render().unwrap_or_default();
+            let stats_html = TodoStatsTemplate::from(stats).render().unwrap_or_default();
 
             // 返回待办项和统计信息，使用 hx-swap-oob 更新统计区域
             format!(
@@ -112,33 +376,88 @@ pub async fn create(
     }
 }
 
+/// 批量导入待办
+///
+/// 请求体为 `text/csv`，每行一个标题，可带 `title` 表头，空行忽略。
+/// 行数超出 `database.max_batch_rows` 时在开启写事务前返回 413，全部插入在同一事务中完成
+pub async fn import(
+    Extension(Db { write: pool, .. }): Extension<Db>,
+    CsvBody(body): CsvBody,
+) -> impl IntoResponse {
+    let titles: Vec<&str> = body
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        // 跳过空行和首行表头
+        .filter(|(index, line)| {
+            !(line.is_empty() || *index == 0 && line.eq_ignore_ascii_case("title"))
+        })
+        .map(|(_, line)| line)
+        .collect();
+    if let Err(e) = BatchTooLarge::check(titles.len()) {
+        return e.into_response();
+    }
+
+    let result = async {
+        let mut tx = start_transaction(&pool).await?;
+        for title in &titles {
+            insert_todo(&mut tx, title).await?;
+        }
+        tx.commit().await?;
+        Ok::<_, DbError>(titles.len())
+    }
+    .await;
+
+    match result {
+        Ok(inserted) => {
+            invalidate_entity("todos").await;
+            Json(serde_json::json!({ "inserted": inserted })).into_response()
+        }
+        Err(e) => {
+            tracing::error!("导入待办失败: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "导入失败").into_response()
+        }
+    }
+}
+
 pub async fn delete(
-    Extension(pool): Extension<SqlitePool>,
+    hx: HxHeaders,
+    Extension(Db { write: pool, .. }): Extension<Db>,
     Path(id): Path<i64>,
 ) -> impl IntoResponse {
-    let result = sqlx::query("DELETE FROM todos WHERE id = ?")
-        .bind(id)
-        .execute(&pool)
-        .await;
+    // 删除和统计读取放在同一事务中，提交后再渲染
+    let result = async {
+        let mut tx = start_transaction(&pool).await?;
+
+        track_db_query(
+            "todos.delete",
+            sqlx::query("DELETE FROM todos WHERE id = ?")
+                .bind(id)
+                .execute(&mut *tx),
+        )
+        .await?;
+        let stats = get_stats(&mut *tx).await?;
+
+        tx.commit().await?;
+        Ok::<_, DbError>(stats)
+    }
+    .await;
 
     match result {
-        Ok(_) => {
-            // 数据变更，使缓存失效
-            invalidate_todo_cache();
-
-            let stats = get_stats(&pool).await.unwrap_or(TodoStatsTemplate {
-                total_count: 0,
-                completed_count: 0,
-                pending_count: 0,
-            });
-            let stats_html = stats.render().unwrap_or_default();
-
-            // 返回空内容（删除当前元素）和更新的统计信息
-            format!(
-                "<div id=\"todo-stats\" class=\"row mt-4\" hx-swap-oob=\"true\">{}</div>",
-                stats_html
+        Ok(stats) => {
+            // 数据变更，使列表、搜索等相关缓存一起失效
+            invalidate_entity("todos").await;
+
+            let stats_html = TodoStatsTemplate::from(stats).render().unwrap_or_default();
+
+            // 删除当前元素，并通过 OOB 更新统计信息
+            deleted(
+                &hx,
+                format!(
+                    "<div id=\"todo-stats\" class=\"row mt-4\" hx-swap-oob=\"true\">{}</div>",
+                    stats_html
+                ),
             )
-            .into_response()
         }
         Err(e) => {
             tracing::error!("删除待办失败: {}", e);
@@ -148,29 +467,28 @@ pub async fn delete(
 }
 
 pub async fn toggle(
-    Extension(pool): Extension<SqlitePool>,
+    Extension(Db { write: pool, .. }): Extension<Db>,
     Path(id): Path<i64>,
 ) -> impl IntoResponse {
-    // 切换完成状态
-    let result = sqlx::query_as::<_, Todo>(
-        "UPDATE todos SET completed = NOT completed WHERE id = ? RETURNING id, title, completed",
-    )
-    .bind(id)
-    .fetch_one(&pool)
+    // 切换完成状态和统计读取放在同一事务中，提交后再渲染
+    let result = async {
+        let mut tx = start_transaction(&pool).await?;
+
+        let todo = toggle_todo(&mut tx, id).await?;
+        let stats = get_stats(&mut *tx).await?;
+
+        tx.commit().await?;
+        Ok::<_, DbError>((todo, stats))
+    }
     .await;
 
     match result {
-        Ok(todo) => {
-            // 数据变更，使缓存失效
-            invalidate_todo_cache();
-
-            let stats = get_stats(&pool).await.unwrap_or(TodoStatsTemplate {
-                total_count: 0,
-                completed_count: 0,
-                pending_count: 0,
-            });
+        Ok((todo, stats)) => {
+            // 数据变更，使列表、搜索等相关缓存一起失效
+            invalidate_entity("todos").await;
+
             let todo_html = TodoItemTemplate { todo }.render().unwrap_or_default();
-            let stats_html = stats.render().unwrap_or_default();
+            let stats_html = TodoStatsTemplate::from(stats).render().unwrap_or_default();
 
             // 返回待办项和统计信息
             format!(
@@ -179,9 +497,276 @@ pub async fn toggle(
             )
             .into_response()
         }
+        Err(DbError::Connection(sqlx::Error::RowNotFound)) => {
+            tracing::warn!("切换待办状态失败: 待办 {} 不存在", id);
+            StatusCode::NOT_FOUND.into_response()
+        }
         Err(e) => {
             tracing::error!("切换待办状态失败: {}", e);
-            StatusCode::NOT_FOUND.into_response()
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::helpers::config::DatabaseConfig;
+    use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+
+    /// 从响应片段中取出统计区域的总数
+    async fn total_count(response: Response) -> usize {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8_lossy(&body);
+        let start = body.find("data-count=\"").unwrap() + "data-count=\"".len();
+        let end = start + body[start..].find('"').unwrap();
+        body[start..end].parse().unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_creates_report_stats_from_their_own_transaction() {
+        let path =
+            std::env::temp_dir().join(format!("htmx-rs-template-todos-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(4)
+            .connect_with(
+                SqliteConnectOptions::new()
+                    .filename(&path)
+                    .create_if_missing(true)
+                    .journal_mode(SqliteJournalMode::Wal),
+            )
+            .await
+            .unwrap();
+        run_migrations(&pool, &DatabaseConfig::default())
+            .await
+            .unwrap();
+        let db = Db {
+            write: pool.clone(),
+            read: pool.clone(),
+        };
+
+        // 统计与写入在同一事务中读取，并发创建时每个响应看到的总数互不相同
+        let tasks: Vec<_> = (0..8)
+            .map(|i| {
+                let db = db.clone();
+                tokio::spawn(async move {
+                    let form = CreateTodoForm {
+                        title: format!("待办 {}", i),
+                    };
+                    total_count(
+                        create(Extension(db), StrictForm(form))
+                            .await
+                            .into_response(),
+                    )
+                    .await
+                })
+            })
+            .collect();
+        let mut totals = Vec::new();
+        for task in tasks {
+            totals.push(task.await.unwrap());
+        }
+        totals.sort_unstable();
+
+        pool.close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let mut file = path.clone().into_os_string();
+            file.push(suffix);
+            let _ = std::fs::remove_file(file);
+        }
+        assert_eq!(totals, (1..=8).collect::<Vec<_>>());
+    }
+
+    async fn memory_db() -> Db {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        run_migrations(&pool, &DatabaseConfig::default())
+            .await
+            .unwrap();
+        Db {
+            write: pool.clone(),
+            read: pool,
+        }
+    }
+
+    #[tokio::test]
+    async fn create_invalidates_list_and_search_caches() {
+        use crate::helpers::cache::{get_from_cache, set_to_cache};
+        use crate::routes::pages::CACHE_KEY_TODOS;
+
+        // 用其他代码不会写入的类型作标记，并发测试重新填充缓存也不会误判
+        let keys = [CACHE_KEY_TODOS, "search:todos:create-test"];
+        for key in keys {
+            set_to_cache(key, "stale".to_string(), None).await;
+            assert!(get_from_cache::<String>(key).await.is_some());
+        }
+
+        let form = CreateTodoForm {
+            title: "买牛奶".to_string(),
+        };
+        let response = create(Extension(memory_db().await), StrictForm(form))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        for key in keys {
+            assert_eq!(get_from_cache::<String>(key).await, None, "{}", key);
+        }
+    }
+
+    async fn count_todos(db: &Db) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM todos")
+            .fetch_one(&db.read)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn import_rejects_batches_over_the_limit_without_writing() {
+        let db = memory_db().await;
+        let before = count_todos(&db).await;
+        let limit = crate::helpers::config::CONFIG.database.max_batch_rows;
+
+        let body = (0..=limit).map(|i| format!("待办 {}\n", i)).collect();
+        let response = import(Extension(db.clone()), CsvBody(body))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "batch_too_large");
+        assert_eq!(body["rows"], limit + 1);
+        assert_eq!(count_todos(&db).await, before);
+
+        let response = import(
+            Extension(db.clone()),
+            CsvBody("title\n买牛奶\n\n写周报\n".to_string()),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(count_todos(&db).await, before + 2);
+    }
+
+    #[tokio::test]
+    async fn failed_import_rolls_back_every_row() {
+        let db = memory_db().await;
+        sqlx::query("INSERT INTO todos (title, completed) VALUES ('已有', 1)")
+            .execute(&db.write)
+            .await
+            .unwrap();
+        // 模拟批次中途写入失败：插入该标题时中止
+        sqlx::query(
+            "CREATE TRIGGER reject_bad_row BEFORE INSERT ON todos WHEN NEW.title = '坏行'
+             BEGIN SELECT RAISE(ABORT, 'bad row'); END",
+        )
+        .execute(&db.write)
+        .await
+        .unwrap();
+        let before = get_stats(&db.read).await.unwrap();
+
+        let response = import(
+            Extension(db.clone()),
+            CsvBody(
+                "title
+买牛奶
+写周报
+坏行
+订机票
+"
+                .to_string(),
+            ),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        // 坏行之前的两行也没有提交
+        let after = get_stats(&db.read).await.unwrap();
+        assert_eq!(
+            (
+                after.total_count,
+                after.completed_count,
+                after.pending_count
+            ),
+            (
+                before.total_count,
+                before.completed_count,
+                before.pending_count
+            )
+        );
+        assert_eq!(count_todos(&db).await, 1);
+    }
+
+    #[tokio::test]
+    async fn delete_removes_the_row_in_both_modes() {
+        let db = memory_db().await;
+
+        for htmx in [false, true] {
+            let id: i64 =
+                sqlx::query_scalar("INSERT INTO todos (title) VALUES ('待删除') RETURNING id")
+                    .fetch_one(&db.write)
+                    .await
+                    .unwrap();
+            let before = count_todos(&db).await;
+            let hx = HxHeaders {
+                request: htmx,
+                ..HxHeaders::default()
+            };
+
+            let response = delete(hx, Extension(db.clone()), Path(id))
+                .await
+                .into_response();
+            assert_eq!(count_todos(&db).await, before - 1);
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            if htmx {
+                // 主内容为空，统计通过 OOB 更新
+                assert_eq!(status, StatusCode::OK);
+                let html = String::from_utf8(body.to_vec()).unwrap();
+                assert!(html.starts_with("<div id=\"todo-stats\""), "{}", html);
+                assert!(html.contains("hx-swap-oob=\"true\""));
+            } else {
+                assert_eq!(status, StatusCode::NO_CONTENT);
+                assert!(body.is_empty());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn returning_and_fallback_paths_return_the_same_rows() {
+        let db = memory_db().await;
+        let mut conn = db.write.acquire().await.unwrap();
+
+        for use_returning in [true, false] {
+            let title = format!("路径 {}", use_returning);
+            let inserted = insert_todo_with(&mut conn, &title, use_returning)
+                .await
+                .unwrap();
+            assert_eq!(inserted.title, title);
+            assert!(!inserted.completed);
+            let stored = get_todo(&mut conn, inserted.id).await.unwrap();
+            assert_eq!((stored.id, stored.title), (inserted.id, title));
+
+            let toggled = toggle_todo_with(&mut conn, inserted.id, use_returning)
+                .await
+                .unwrap();
+            assert_eq!(toggled.id, inserted.id);
+            assert!(toggled.completed);
+
+            let missing = toggle_todo_with(&mut conn, i64::MAX, use_returning).await;
+            assert!(matches!(missing, Err(sqlx::Error::RowNotFound)));
         }
     }
 }
@@ -1,20 +1,38 @@
 use askama::Template;
 use askama_axum::IntoResponse;
+use axum::body::Body;
 use axum::extract::{Extension, Path, Query};
-use axum::http::StatusCode;
-use serde::Deserialize;
-use sqlx::SqlitePool;
+use axum::http::{header, StatusCode};
+use axum::response::Response;
+use axum::Json;
+use futures::{future, stream, Stream, StreamExt};
+use metrics::increment_counter;
+use serde::{Deserialize, Serialize};
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+use std::collections::HashMap;
+
+use super::extract::CsvBody;
+use crate::db::{fetch_stream, like_pattern, start_transaction, Db, DbError};
+use crate::domain::{Email, UserSort};
+use crate::helpers::cache::invalidate_entity;
+use crate::helpers::config::CONFIG;
+use crate::helpers::monitoring::track_db_query;
+use crate::helpers::security::normalize_search_query;
+use crate::helpers::timezone::DisplayTimezone;
+
+use super::htmx::{flash_alert, CachePolicy, HxHeaders};
+use super::{url, BatchTooLarge, EmptyStateTemplate, HtmlFragment};
 
 // 导入公共分页模块
 use crate::helpers::pagination::{
     calculate_display_range, create_pagination, PageQuery, Pagination,
 };
 
-#[derive(Clone, Debug, sqlx::FromRow)]
+#[derive(Clone, Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct User {
     pub id: i64,
     pub name: String,
-    pub email: String,
+    pub email: Email,
 }
 
 #[derive(Template)]
@@ -27,12 +45,106 @@ pub struct UserSearchResultsTemplate {
     pub end_item: i64,
     pub base_url: String,
     pub target: String,
+    pub empty_state: EmptyStateTemplate,
+    /// 请求的 `per_page` 超出 `ui.max_rendered_rows` 被截断时为截断后的每页行数
+    pub truncated_at: Option<i64>,
 }
 
 #[derive(Template)]
 #[template(path = "modules/users/detail.html")]
 pub struct UserDetailTemplate {
     pub user: User,
+    /// 按显示时区格式化的创建时间
+    pub created_at: Option<String>,
+}
+
+/// 批量用户详情，按请求中 id 的顺序排列，不存在的 id 单独列出
+#[derive(Template)]
+#[template(path = "modules/users/details.html")]
+pub struct UserDetailsTemplate {
+    /// 用户及按显示时区格式化的创建时间
+    pub users: Vec<(User, Option<String>)>,
+    pub missing: Vec<i64>,
+}
+
+/// 用户详情查询结果，比列表多出创建时间
+#[derive(sqlx::FromRow)]
+struct UserDetailRow {
+    #[sqlx(flatten)]
+    user: User,
+    created_at: Option<String>,
+}
+
+/// 用户页面骨架屏，不含数据
+#[derive(Template)]
+#[template(path = "modules/users/skeleton.html")]
+pub struct UsersSkeletonTemplate;
+
+/// 单次批量查询详情的最大用户数
+pub const MAX_DETAIL_IDS: usize = 50;
+
+#[derive(Deserialize)]
+pub struct DetailsQuery {
+    /// 逗号分隔的用户 id，如 `1,2,3`
+    ids: Option<String>,
+}
+
+/// `ids` 参数无效
+#[derive(Debug)]
+pub struct InvalidIds {
+    pub message: String,
+}
+
+impl IntoResponse for InvalidIds {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "invalid_ids",
+                "message": self.message,
+            })),
+        )
+            .into_response()
+    }
+}
+
+/// 解析逗号分隔的用户 id 列表，去重并保留顺序
+///
+/// 每项必须是正整数，去重后最多 [`MAX_DETAIL_IDS`] 个
+pub fn parse_ids(raw: &str) -> Result<Vec<i64>, InvalidIds> {
+    let mut ids: Vec<i64> = Vec::new();
+    for item in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+    {
+        let id = item
+            .parse::<i64>()
+            .ok()
+            .filter(|id| *id > 0)
+            .ok_or_else(|| InvalidIds {
+                message: format!("无效的用户 id: {}", item),
+            })?;
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+
+    if ids.is_empty() {
+        return Err(InvalidIds {
+            message: "ids 不能为空".to_string(),
+        });
+    }
+    if ids.len() > MAX_DETAIL_IDS {
+        return Err(InvalidIds {
+            message: format!(
+                "单次最多查询 {} 个用户，本次 {} 个",
+                MAX_DETAIL_IDS,
+                ids.len()
+            ),
+        });
+    }
+    Ok(ids)
 }
 
 #[derive(Deserialize)]
@@ -40,107 +152,993 @@ pub struct SearchQuery {
     q: Option<String>,
     page: Option<i64>,
     per_page: Option<i64>,
+    /// 排序方式，格式为 `字段` 或 `字段:方向`，如 `name:desc`
+    sort: Option<String>,
+}
+
+/// 配置的默认排序（`ui.default_user_sort`），配置在加载时已校验
+pub fn configured_user_sort() -> UserSort {
+    UserSort::try_parse(&CONFIG.ui.default_user_sort).unwrap_or_default()
+}
+
+#[derive(Deserialize)]
+pub struct ImportQuery {
+    /// 严格模式：遇到重复邮箱时回滚整个批次
+    #[serde(default)]
+    strict: bool,
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    /// 导出格式：`csv`（默认）或 `json`
+    #[serde(default)]
+    format: ExportFormat,
+}
+
+/// 用户导出格式
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// `name,email`，与导入格式一致，可直接重新导入
+    #[default]
+    Csv,
+    /// JSON 数组
+    Json,
+}
+
+/// 单行导入结果
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportOutcome {
+    Inserted,
+    SkippedDuplicate,
+    Invalid,
+    /// 已插入，但严格模式下随批次回滚，未写入
+    RolledBack,
+}
+
+/// 单行导入报告
+#[derive(Debug, Serialize)]
+pub struct ImportRowReport {
+    /// CSV 中的行号（从 1 开始）
+    pub line: usize,
+    pub outcome: ImportOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// 批量导入报告
+#[derive(Debug, Serialize, Default)]
+pub struct ImportReport {
+    pub inserted: usize,
+    pub skipped_duplicate: usize,
+    pub invalid: usize,
+    /// 严格模式下因重复而回滚时为 true，此时 `inserted` 为 0
+    pub rolled_back: bool,
+    pub rows: Vec<ImportRowReport>,
+}
+
+impl ImportReport {
+    fn record(&mut self, line: usize, outcome: ImportOutcome, message: Option<String>) {
+        match outcome {
+            ImportOutcome::Inserted => self.inserted += 1,
+            ImportOutcome::SkippedDuplicate => self.skipped_duplicate += 1,
+            ImportOutcome::Invalid => self.invalid += 1,
+            ImportOutcome::RolledBack => {}
+        }
+        self.rows.push(ImportRowReport {
+            line,
+            outcome,
+            message,
+        });
+    }
+
+    /// 严格模式回滚：已插入的行改记为回滚，插入计数清零
+    fn roll_back(&mut self) {
+        for row in &mut self.rows {
+            if row.outcome == ImportOutcome::Inserted {
+                row.outcome = ImportOutcome::RolledBack;
+            }
+        }
+        self.inserted = 0;
+        self.rolled_back = true;
+    }
+}
+
+/// 空行和表头不计入导入结果
+fn is_skipped_row(line_number: usize, line: &str) -> bool {
+    line.is_empty() || (line_number == 1 && line.eq_ignore_ascii_case("name,email"))
+}
+
+/// 按 RFC 4180 解析一行 `name,email`
+///
+/// 字段可用双引号包裹，引号内的逗号原样保留，`""` 表示一个引号；未加引号的字段去除首尾空白。
+/// 按行读取，引号字段不能跨行。字段数不是 2、引号未闭合或出现在未加引号的字段中时返回 `None`
+fn parse_csv_row(line: &str) -> Option<(String, String)> {
+    let mut fields = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        let mut field = String::new();
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next_if_eq(&'"').is_some() {
+            loop {
+                match chars.next()? {
+                    '"' if chars.next_if_eq(&'"').is_some() => field.push('"'),
+                    '"' => break,
+                    c => field.push(c),
+                }
+            }
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            if chars.peek().is_some_and(|c| *c != ',') {
+                return None;
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| *c != ',') {
+                if c == '"' {
+                    return None;
+                }
+                field.push(c);
+            }
+            field.truncate(field.trim_end().len());
+        }
+        fields.push(field);
+        // 取出分隔的逗号，行尾时结束
+        if chars.next().is_none() {
+            break;
+        }
+    }
+
+    let [name, email]: [String; 2] = fields.try_into().ok()?;
+    Some((name, email))
+}
+
+/// 按渲染上限截断后的每页行数和偏移量
+///
+/// `per_page` 超过 `max_rendered_rows` 时以后者作为每页行数，偏移量随之计算，
+/// 不会跳过被截断的行
+fn render_window(page: i64, per_page: i64, max_rendered_rows: i64) -> (i64, i64) {
+    let page_size = per_page.min(max_rendered_rows);
+    (page_size, (page - 1).saturating_mul(page_size))
 }
 
 /// 从数据库获取所有用户
 /// 使用索引优化查询性能
 pub async fn get_all_users(pool: &SqlitePool) -> Result<Vec<User>, sqlx::Error> {
     // 利用主键索引优化查询
-    sqlx::query_as::<_, User>("SELECT id, name, email FROM users ORDER BY id")
-        .fetch_all(pool)
-        .await
+    let sql = format!(
+        "SELECT id, name, email FROM users ORDER BY {}",
+        configured_user_sort().order_by()
+    );
+    track_db_query(
+        "users.list",
+        sqlx::query_as::<_, User>(&sql).fetch_all(pool),
+    )
+    .await
+}
+
+/// 流式读取全部用户，排序与 [`get_all_users`] 一致
+///
+/// 用于导出等需要遍历整表的场景，不会把整张表加载到内存
+pub fn stream_users(
+    pool: &SqlitePool,
+) -> impl Stream<Item = Result<User, sqlx::Error>> + Send + 'static {
+    let sql = format!(
+        "SELECT id, name, email FROM users ORDER BY {}",
+        configured_user_sort().order_by()
+    );
+    fetch_stream(pool, "users.export", sql)
 }
 
 pub async fn search(
-    Extension(pool): Extension<SqlitePool>,
+    hx: HxHeaders,
+    Extension(Db { read: pool, .. }): Extension<Db>,
     Query(params): Query<SearchQuery>,
 ) -> impl IntoResponse {
-    let query = params.q.unwrap_or_default();
+    search_with_cap(hx, pool, params, i64::from(CONFIG.ui.max_rendered_rows)).await
+}
+
+/// 按给定的单次渲染上限执行搜索，上限来自 `ui.max_rendered_rows`
+async fn search_with_cap(
+    hx: HxHeaders,
+    pool: SqlitePool,
+    params: SearchQuery,
+    max_rendered_rows: i64,
+) -> Response {
+    let query = normalize_search_query(params.q.as_deref().unwrap_or_default());
+    let sort = params
+        .sort
+        .as_deref()
+        .and_then(UserSort::try_parse)
+        .unwrap_or_else(configured_user_sort);
+    tracing::debug!(
+        "用户搜索: {:?}，排序: {}，触发元素: {:?}",
+        query,
+        sort.order_by(),
+        hx.trigger
+    );
 
     // 使用公共分页模块处理分页参数
     let page_query = PageQuery {
         page: params.page,
         per_page: params.per_page,
     };
+    if let Err(e) = page_query.validate() {
+        return e.into_response_for(&hx);
+    }
 
     let page = page_query.get_page();
     let per_page = page_query.get_per_page();
-    let offset = page_query.get_offset();
+    // 单个响应最多渲染的行数，分页按截断后的每页行数计算，保证每一行都能翻页看到
+    let (render_limit, offset) = render_window(page, per_page, max_rendered_rows);
 
     // 获取总数 - 使用索引优化统计查询
     let total: i64 = if query.is_empty() {
-        sqlx::query_scalar("SELECT COUNT(*) FROM users")
-            .fetch_one(&pool)
-            .await
-            .unwrap_or(0)
+        track_db_query(
+            "users.count",
+            sqlx::query_scalar("SELECT COUNT(*) FROM users").fetch_one(&pool),
+        )
+        .await
+        .unwrap_or(0)
     } else {
-        let search_pattern = format!("%{}%", query);
+        let search_pattern = like_pattern(&query);
         // 使用子查询避免双重计数，优化搜索统计性能
-        sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE name LIKE ? OR email LIKE ?")
-            .bind(&search_pattern)
-            .bind(&search_pattern)
-            .fetch_one(&pool)
-            .await
-            .unwrap_or(0)
+        track_db_query("users.search_count", sqlx::query_scalar(
+            "SELECT COUNT(*) FROM users WHERE name LIKE ? ESCAPE '\\' OR email LIKE ? ESCAPE '\\'",
+        )
+        .bind(&search_pattern)
+        .bind(&search_pattern)
+        .fetch_one(&pool)).await
+        .unwrap_or(0)
     };
 
     // 获取分页数据 - 使用索引优化查询性能
     let users = if query.is_empty() {
-        // 简单查询使用主键索引
-        sqlx::query_as::<_, User>("SELECT id, name, email FROM users ORDER BY id LIMIT ? OFFSET ?")
-            .bind(per_page)
-            .bind(offset)
-            .fetch_all(&pool)
-            .await
-            .unwrap_or_default()
+        // 按排序字段使用主键或对应索引
+        let sql = format!(
+            "SELECT id, name, email FROM users ORDER BY {} LIMIT ? OFFSET ?",
+            sort.order_by()
+        );
+        track_db_query(
+            "users.page",
+            sqlx::query_as::<_, User>(&sql)
+                .bind(render_limit)
+                .bind(offset)
+                .fetch_all(&pool),
+        )
+        .await
+        .unwrap_or_default()
     } else {
-        let search_pattern = format!("%{}%", query);
+        let search_pattern = like_pattern(&query);
         // 使用索引优化搜索查询
-        sqlx::query_as::<_, User>(
+        let sql = format!(
             "SELECT id, name, email FROM users \
-             WHERE name LIKE ? OR email LIKE ? \
-             ORDER BY id LIMIT ? OFFSET ?",
+             WHERE name LIKE ? ESCAPE '\\' OR email LIKE ? ESCAPE '\\' \
+             ORDER BY {} LIMIT ? OFFSET ?",
+            sort.order_by()
+        );
+        track_db_query(
+            "users.search",
+            sqlx::query_as::<_, User>(&sql)
+                .bind(&search_pattern)
+                .bind(&search_pattern)
+                .bind(render_limit)
+                .bind(offset)
+                .fetch_all(&pool),
         )
-        .bind(&search_pattern)
-        .bind(&search_pattern)
-        .bind(per_page)
-        .bind(offset)
-        .fetch_all(&pool)
         .await
         .unwrap_or_default()
     };
 
     // 使用公共分页模块创建分页信息
-    let pagination = create_pagination(page, per_page, total);
+    let pagination = create_pagination(page, render_limit, total);
+
+    // 请求的每页行数超过渲染上限、且本页之后还有结果时提示缩小范围
+    let truncated_at =
+        (per_page > render_limit && total - offset > render_limit).then_some(render_limit);
+    if truncated_at.is_some() {
+        increment_counter!("search_results_truncated_total", "entity" => "users");
+    }
 
     // 使用公共分页模块计算显示范围
-    let (start_item, end_item) = calculate_display_range(page, per_page, users.len());
+    let (start_item, end_item) = calculate_display_range(page, render_limit, users.len());
 
-    UserSearchResultsTemplate {
+    // 无结果时的空状态提示
+    let empty_state = if query.is_empty() {
+        EmptyStateTemplate::new("people", "暂无用户", "还没有任何用户数据")
+    } else {
+        EmptyStateTemplate::new(
+            "search",
+            "未找到匹配的用户",
+            format!("搜索关键词: \"{}\"，试试其他关键词", query),
+        )
+    };
+
+    HtmlFragment(UserSearchResultsTemplate {
         users,
         query,
         pagination,
         start_item,
         end_item,
-        base_url: "/block/users/search".to_string(),
-        target: "#search-results".to_string(),
-    }
+        base_url: url("/block/users/search"),
+        // 分页链接换入发起请求的目标元素，非 HTMX 请求时使用默认的结果容器
+        target: hx
+            .target
+            .as_deref()
+            .map_or_else(|| "#search-results".to_string(), |id| format!("#{}", id)),
+        empty_state,
+        truncated_at,
+    })
+    .into_response()
+}
+
+/// 骨架屏是静态片段，允许公开缓存
+pub async fn skeleton() -> impl IntoResponse {
+    CachePolicy::Public(3600).apply(HtmlFragment(UsersSkeletonTemplate))
 }
 
 pub async fn detail(
-    Extension(pool): Extension<SqlitePool>,
+    timezone: DisplayTimezone,
+    Extension(Db { read: pool, .. }): Extension<Db>,
     Path(id): Path<i64>,
 ) -> impl IntoResponse {
-    let result = sqlx::query_as::<_, User>("SELECT id, name, email FROM users WHERE id = ?")
+    let result = track_db_query(
+        "users.detail",
+        sqlx::query_as::<_, UserDetailRow>(
+            "SELECT id, name, email, CAST(created_at AS TEXT) AS created_at FROM users WHERE id = ?",
+        )
         .bind(id)
-        .fetch_one(&pool)
-        .await;
+        .fetch_one(&pool),
+    )
+    .await;
 
     match result {
-        Ok(user) => UserDetailTemplate { user }.into_response(),
+        // 用户详情变化不频繁，允许浏览器短时间缓存
+        Ok(row) => CachePolicy::Private(60).apply(HtmlFragment(UserDetailTemplate {
+            user: row.user,
+            created_at: row.created_at.map(|ts| timezone.format(&ts)),
+        })),
         Err(e) => {
             tracing::error!("获取用户详情失败: {}", e);
             (StatusCode::NOT_FOUND, "用户不存在").into_response()
         }
     }
 }
+
+/// 批量获取用户详情
+///
+/// `GET /block/users/details?ids=1,2,3`，一次 `IN` 查询取回所有用户，
+/// id 均以参数绑定；不存在的 id 不报错，在片段末尾列出
+pub async fn details(
+    timezone: DisplayTimezone,
+    hx: HxHeaders,
+    Extension(Db { read: pool, .. }): Extension<Db>,
+    Query(params): Query<DetailsQuery>,
+) -> Response {
+    let ids = match parse_ids(params.ids.as_deref().unwrap_or_default()) {
+        Ok(ids) => ids,
+        // HTMX 请求以提示片段返回，与其他 /block 处理器一致
+        Err(e) if hx.request => {
+            return flash_alert(
+                StatusCode::BAD_REQUEST,
+                "warning",
+                "exclamation-triangle",
+                &e.message,
+            )
+        }
+        Err(e) => return e.into_response(),
+    };
+
+    let mut query = QueryBuilder::<Sqlite>::new(
+        "SELECT id, name, email, CAST(created_at AS TEXT) AS created_at FROM users WHERE id IN (",
+    );
+    let mut separated = query.separated(", ");
+    for id in &ids {
+        separated.push_bind(*id);
+    }
+    separated.push_unseparated(")");
+
+    let result = track_db_query(
+        "users.details",
+        query.build_query_as::<UserDetailRow>().fetch_all(&pool),
+    )
+    .await;
+
+    match result {
+        Ok(rows) => {
+            let mut found: HashMap<i64, UserDetailRow> =
+                rows.into_iter().map(|row| (row.user.id, row)).collect();
+            let mut users = Vec::with_capacity(found.len());
+            let mut missing = Vec::new();
+            for id in ids {
+                match found.remove(&id) {
+                    Some(row) => {
+                        users.push((row.user, row.created_at.map(|ts| timezone.format(&ts))))
+                    }
+                    None => missing.push(id),
+                }
+            }
+
+            CachePolicy::Private(60).apply(HtmlFragment(UserDetailsTemplate { users, missing }))
+        }
+        Err(e) => {
+            tracing::error!("批量获取用户详情失败: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// 批量导入用户
+///
+/// 接收 `name,email` 格式的 CSV（可带表头），在单个事务中插入并逐行报告结果。
+/// 重复邮箱默认跳过并计数；`strict=true` 时遇到重复则回滚整个批次并返回 409，
+/// 此前插入的行在报告中记为 `rolled_back`，插入数为 0
+pub async fn import(
+    Extension(Db { write: pool, .. }): Extension<Db>,
+    Query(params): Query<ImportQuery>,
+    CsvBody(body): CsvBody,
+) -> impl IntoResponse {
+    // 先统计数据行数，超出上限时直接拒绝，不开启写事务
+    let rows = body
+        .lines()
+        .enumerate()
+        .filter(|(index, line)| !is_skipped_row(*index + 1, line.trim()))
+        .count();
+    if let Err(e) = BatchTooLarge::check(rows) {
+        return e.into_response();
+    }
+
+    let result = async {
+        let mut tx = start_transaction(&pool).await?;
+        let mut report = ImportReport::default();
+
+        for (index, line) in body.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+
+            if is_skipped_row(line_number, line) {
+                continue;
+            }
+
+            let Some((name, email)) = parse_csv_row(line) else {
+                report.record(
+                    line_number,
+                    ImportOutcome::Invalid,
+                    Some("格式错误，应为 name,email，含逗号或引号的字段需用双引号包裹".to_string()),
+                );
+                continue;
+            };
+
+            if name.is_empty() {
+                report.record(
+                    line_number,
+                    ImportOutcome::Invalid,
+                    Some("姓名不能为空".to_string()),
+                );
+                continue;
+            }
+
+            let email = match Email::parse(&email) {
+                Ok(email) => email,
+                Err(e) => {
+                    report.record(line_number, ImportOutcome::Invalid, Some(e.to_string()));
+                    continue;
+                }
+            };
+
+            let inserted = track_db_query(
+                "users.insert",
+                sqlx::query("INSERT INTO users (name, email) VALUES (?, ?)")
+                    .bind(&name)
+                    .bind(&email)
+                    .execute(&mut *tx),
+            )
+            .await
+            .map_err(DbError::from);
+
+            match inserted {
+                Ok(_) => report.record(line_number, ImportOutcome::Inserted, None),
+                Err(DbError::Conflict(_)) => {
+                    report.record(
+                        line_number,
+                        ImportOutcome::SkippedDuplicate,
+                        Some(format!("邮箱已存在: {}", email)),
+                    );
+                    if params.strict {
+                        // 严格模式：放弃整个批次
+                        tx.rollback().await?;
+                        report.roll_back();
+                        return Ok::<_, DbError>(report);
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        tx.commit().await?;
+        Ok(report)
+    }
+    .await;
+
+    match result {
+        Ok(report) if report.rolled_back => (StatusCode::CONFLICT, Json(report)).into_response(),
+        Ok(report) => {
+            if report.inserted > 0 {
+                // 数据变更，使所有用户缓存失效
+                invalidate_entity("users").await;
+            }
+            tracing::info!(
+                "批量导入用户完成: 插入 {}，重复 {}，无效 {}",
+                report.inserted,
+                report.skipped_duplicate,
+                report.invalid
+            );
+            (StatusCode::OK, Json(report)).into_response()
+        }
+        Err(e) => {
+            tracing::error!("批量导入用户失败: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "导入失败").into_response()
+        }
+    }
+}
+
+/// 导出全部用户
+///
+/// 挂在管理接口 `/admin/users/export` 下，限内部网段并要求管理令牌。
+/// 边查询边写出响应体，内存占用与用户数量无关。响应头发出后无法再修改状态码，
+/// 因此中途出错时记录错误并中断响应体，客户端会收到不完整的传输而不是截断的"成功"文件
+pub async fn export(
+    Extension(Db { read: pool, .. }): Extension<Db>,
+    Query(params): Query<ExportQuery>,
+) -> impl IntoResponse {
+    let format = params.format;
+    let rows = stream_users(&pool).enumerate().map(move |(index, row)| {
+        let user = row.map_err(|e| {
+            tracing::error!("❌ 导出用户中断（第 {} 行）: {}", index + 1, e);
+            std::io::Error::other(e)
+        })?;
+        Ok::<_, std::io::Error>(match format {
+            ExportFormat::Csv => format!(
+                "{},{}\n",
+                csv_field(&user.name),
+                csv_field(user.email.as_str())
+            ),
+            ExportFormat::Json => {
+                let separator = if index == 0 { "" } else { "," };
+                format!("{}{}", separator, serde_json::to_string(&user)?)
+            }
+        })
+    });
+
+    let (content_type, filename, head, tail) = match format {
+        ExportFormat::Csv => ("text/csv; charset=utf-8", "users.csv", "name,email\n", ""),
+        ExportFormat::Json => ("application/json", "users.json", "[", "]"),
+    };
+    // 出错后立即结束，不再写出结尾，避免响应体看起来完整
+    let body = stream::once(future::ready(Ok(head.to_string())))
+        .chain(rows)
+        .chain(stream::once(future::ready(Ok(tail.to_string()))))
+        .scan(false, |failed, chunk| {
+            if *failed {
+                return future::ready(None);
+            }
+            *failed = chunk.is_err();
+            future::ready(Some(chunk))
+        });
+
+    CachePolicy::NoStore.apply((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        Body::from_stream(body),
+    ))
+}
+
+/// CSV 字段转义：包含逗号、引号或换行时加引号，内部引号加倍
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_window_within_cap_uses_per_page() {
+        assert_eq!(render_window(1, 12, 100), (12, 0));
+        assert_eq!(render_window(3, 12, 100), (12, 24));
+    }
+
+    #[test]
+    fn render_window_exceeding_cap_reaches_every_row() {
+        let total = 95;
+        let (page_size, _) = render_window(1, 50, 20);
+        assert_eq!(page_size, 20);
+
+        let pagination = create_pagination(1, page_size, total);
+        let mut seen = Vec::new();
+        for page in 1..=pagination.total_pages {
+            let (limit, offset) = render_window(page, 50, 20);
+            seen.extend(offset..(offset + limit).min(total));
+        }
+        assert_eq!(seen, (0..total).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn details_invalid_ids_returns_flash_alert_for_htmx() {
+        use axum::{extract::Request, routing::get, Router};
+        use tower::ServiceExt;
+
+        let pool = SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let app = Router::new()
+            .route("/details", get(details))
+            .layer(Extension(Db {
+                write: pool.clone(),
+                read: pool,
+            }));
+        let request = |hx: bool| {
+            let builder = Request::get("/details?ids=1,%3Cb%3E");
+            let builder = if hx {
+                builder.header("HX-Request", "true")
+            } else {
+                builder
+            };
+            builder.body(Body::empty()).unwrap()
+        };
+
+        let response = app.clone().oneshot(request(true)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers()["hx-retarget"], "#flash-messages");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8_lossy(&body);
+        assert!(body.contains("alert-warning"));
+        assert!(body.contains("&lt;b&gt;"));
+
+        let response = app.oneshot(request(false)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+    }
+
+    #[tokio::test]
+    async fn details_renders_found_users_and_lists_missing_ids() {
+        let db = import_db().await;
+        sqlx::query("INSERT INTO users (name, email) VALUES ('第二位', 'second@example.com')")
+            .execute(&db.write)
+            .await
+            .unwrap();
+
+        let response = details(
+            DisplayTimezone::default(),
+            HxHeaders::default(),
+            Extension(db),
+            Query(DetailsQuery {
+                ids: Some("1,999,2".to_string()),
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8_lossy(&body);
+
+        assert!(body.contains("已有"));
+        assert!(body.contains("第二位"));
+        // 找到的用户按请求顺序渲染
+        assert!(body.find("已有").unwrap() < body.find("第二位").unwrap());
+        let (_, note) = body.split_once("以下用户不存在").unwrap();
+        assert!(note.contains("#999"));
+        assert!(!note.contains("#1") && !note.contains("#2"));
+    }
+
+    #[test]
+    fn parse_csv_row_handles_rfc4180_quoting() {
+        let row = |name: &str, email: &str| Some((name.to_string(), email.to_string()));
+        assert_eq!(
+            parse_csv_row(" 张三 , a@example.com "),
+            row("张三", "a@example.com")
+        );
+        assert_eq!(
+            parse_csv_row("\"Doe, John\",john@example.com"),
+            row("Doe, John", "john@example.com")
+        );
+        assert_eq!(
+            parse_csv_row("\"say \"\"hi\"\"\", \"b@example.com\""),
+            row("say \"hi\"", "b@example.com")
+        );
+        assert_eq!(parse_csv_row(",a@example.com"), row("", "a@example.com"));
+    }
+
+    #[test]
+    fn parse_csv_row_rejects_malformed_rows() {
+        assert_eq!(parse_csv_row("only-name"), None);
+        assert_eq!(parse_csv_row("a,b@example.com,extra"), None);
+        assert_eq!(parse_csv_row("\"unterminated,b@example.com"), None);
+        assert_eq!(parse_csv_row("\"a\"x,b@example.com"), None);
+        assert_eq!(parse_csv_row("a\"b,c@example.com"), None);
+    }
+
+    /// 已执行迁移、含一个用户的单连接内存数据库
+    async fn import_db() -> Db {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        crate::db::run_migrations(&pool, &crate::helpers::config::DatabaseConfig::default())
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO users (name, email) VALUES ('已有', 'taken@example.com')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        Db {
+            write: pool.clone(),
+            read: pool,
+        }
+    }
+
+    async fn run_import(db: &Db, strict: bool, csv: &str) -> (StatusCode, serde_json::Value) {
+        let response = import(
+            Extension(db.clone()),
+            Query(ImportQuery { strict }),
+            CsvBody(csv.to_string()),
+        )
+        .await
+        .into_response();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn count_users(db: &Db) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM users")
+            .fetch_one(&db.read)
+            .await
+            .unwrap()
+    }
+
+    const IMPORT_CSV: &str = "name,email\n\
+                              新用户,new@example.com\n\
+                              \"Doe, John\",taken@example.com\n\
+                              ,blank@example.com\n\
+                              坏行\n";
+
+    #[tokio::test]
+    async fn import_reports_each_row_outcome() {
+        let db = import_db().await;
+        let (status, report) = run_import(&db, false, IMPORT_CSV).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report["inserted"], 1);
+        assert_eq!(report["skipped_duplicate"], 1);
+        assert_eq!(report["invalid"], 2);
+        assert_eq!(report["rolled_back"], false);
+        let outcomes: Vec<_> = report["rows"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|row| {
+                (
+                    row["line"].as_u64().unwrap(),
+                    row["outcome"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                (2, "inserted"),
+                (3, "skipped_duplicate"),
+                (4, "invalid"),
+                (5, "invalid")
+            ]
+        );
+        assert_eq!(count_users(&db).await, 2);
+    }
+
+    #[tokio::test]
+    async fn strict_import_rolls_back_and_reports_no_inserts() {
+        let db = import_db().await;
+        let (status, report) = run_import(&db, true, IMPORT_CSV).await;
+
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(report["rolled_back"], true);
+        assert_eq!(report["inserted"], 0);
+        assert_eq!(report["rows"][0]["outcome"], "rolled_back");
+        assert_eq!(report["rows"][1]["outcome"], "skipped_duplicate");
+        assert_eq!(count_users(&db).await, 1);
+    }
+
+    async fn export_body(db: &Db, format: ExportFormat) -> (String, String) {
+        let response = export(Extension(db.clone()), Query(ExportQuery { format }))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let content_type = response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (content_type, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn export_streams_csv_and_json() {
+        let db = import_db().await;
+        sqlx::query("INSERT INTO users (name, email) VALUES ('Doe, John', 'john@example.com')")
+            .execute(&db.write)
+            .await
+            .unwrap();
+
+        let (content_type, csv) = export_body(&db, ExportFormat::Csv).await;
+        assert_eq!(content_type, "text/csv; charset=utf-8");
+        assert_eq!(
+            csv,
+            "name,email\n已有,taken@example.com\n\"Doe, John\",john@example.com\n"
+        );
+        // 导出的 CSV 可以按导入规则逐行解析回来
+        let rows: Vec<_> = csv.lines().skip(1).filter_map(parse_csv_row).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].0, "Doe, John");
+
+        let (content_type, json) = export_body(&db, ExportFormat::Json).await;
+        assert_eq!(content_type, "application/json");
+        let users: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let users = users.as_array().unwrap();
+        assert_eq!(users.len(), 2);
+        assert_eq!(users[0]["name"], "已有");
+        assert_eq!(users[1]["email"], "john@example.com");
+    }
+
+    /// 追加 `count` 个按序编号的用户
+    async fn seed_users(db: &Db, count: usize) {
+        sqlx::query(
+            "INSERT INTO users (name, email)
+             WITH RECURSIVE seq(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM seq WHERE n < ?)
+             SELECT '用户' || n, 'user' || n || '@example.com' FROM seq",
+        )
+        .bind(count as i64)
+        .execute(&db.write)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn export_streams_hundreds_of_rows_in_order() {
+        let db = import_db().await;
+        seed_users(&db, 300).await;
+        let expected: Vec<_> =
+            std::iter::once(("已有".to_string(), "taken@example.com".to_string()))
+                .chain((1..=300).map(|n| (format!("用户{}", n), format!("user{}@example.com", n))))
+                .collect();
+
+        let (_, csv) = export_body(&db, ExportFormat::Csv).await;
+        let rows: Vec<_> = csv.lines().skip(1).filter_map(parse_csv_row).collect();
+        assert_eq!(rows, expected);
+
+        let (_, json) = export_body(&db, ExportFormat::Json).await;
+        let users: Vec<User> = serde_json::from_str(&json).unwrap();
+        let rows: Vec<_> = users
+            .into_iter()
+            .map(|user| (user.name, user.email.as_str().to_string()))
+            .collect();
+        assert_eq!(rows, expected);
+    }
+
+    #[tokio::test]
+    async fn export_error_mid_stream_aborts_body() {
+        let db = import_db().await;
+        seed_users(&db, 300).await;
+        // BLOB 无法解码为 String，查询读到这一行时失败
+        sqlx::query("UPDATE users SET name = X'FF' WHERE email = 'user150@example.com'")
+            .execute(&db.write)
+            .await
+            .unwrap();
+
+        for format in [ExportFormat::Csv, ExportFormat::Json] {
+            let response = export(Extension(db.clone()), Query(ExportQuery { format }))
+                .await
+                .into_response();
+            assert_eq!(response.status(), StatusCode::OK);
+            let chunks: Vec<_> = response.into_body().into_data_stream().collect().await;
+
+            // 出错前的行已经写出，随后响应体以错误结束，不会补上结尾
+            let (last, sent) = chunks.split_last().unwrap();
+            assert!(last.is_err());
+            assert!(sent.iter().all(Result::is_ok));
+            let sent: String = sent
+                .iter()
+                .map(|chunk| String::from_utf8_lossy(chunk.as_ref().unwrap()).into_owned())
+                .collect();
+            assert!(sent.contains("user149@example.com"));
+            assert!(!sent.contains("user151@example.com"));
+            assert!(!sent.ends_with(']'));
+        }
+    }
+
+    /// 以给定渲染上限搜索，返回片段中的卡片数量与是否出现截断提示
+    async fn search_fragment(db: &Db, per_page: i64, max_rendered_rows: i64) -> (usize, bool) {
+        let params = SearchQuery {
+            q: None,
+            page: None,
+            per_page: Some(per_page),
+            sort: None,
+        };
+        let response = search_with_cap(
+            HxHeaders::default(),
+            db.read.clone(),
+            params,
+            max_rendered_rows,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8_lossy(&body);
+        (
+            body.matches("<div class=\"card-body\">").count(),
+            body.contains("结果过多"),
+        )
+    }
+
+    #[tokio::test]
+    async fn search_exceeding_render_cap_truncates_and_notices() {
+        let db = import_db().await;
+        seed_users(&db, 29).await;
+
+        // 共 30 行，请求每页 25 行、上限 10 行：只渲染 10 张卡片并提示
+        assert_eq!(search_fragment(&db, 25, 10).await, (10, true));
+        // 未超过上限时按请求行数渲染，不提示
+        assert_eq!(search_fragment(&db, 8, 10).await, (8, false));
+        assert_eq!(search_fragment(&db, 25, 50).await, (25, false));
+    }
+
+    #[tokio::test]
+    async fn search_pagination_targets_the_requesting_element() {
+        let db = import_db().await;
+        seed_users(&db, 29).await;
+        let fragment = |target: Option<&str>| {
+            let hx = HxHeaders {
+                request: true,
+                target: target.map(str::to_string),
+                ..HxHeaders::default()
+            };
+            let params = SearchQuery {
+                q: None,
+                page: None,
+                per_page: Some(10),
+                sort: None,
+            };
+            let pool = db.read.clone();
+            async move {
+                let response = search_with_cap(hx, pool, params, 100).await;
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                String::from_utf8(body.to_vec()).unwrap()
+            }
+        };
+
+        let body = fragment(Some("user-grid")).await;
+        assert!(body.contains("hx-target=\"#user-grid\""));
+        assert!(!body.contains("hx-target=\"#search-results\""));
+
+        let body = fragment(None).await;
+        assert!(body.contains("hx-target=\"#search-results\""));
+    }
+}
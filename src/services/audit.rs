@@ -0,0 +1,127 @@
+//! 审计日志服务
+//!
+//! 接收审计中间件产生的记录，通过有界通道交给后台任务异步写入 `audit_log` 表，
+//! 请求处理不会等待数据库写入
+
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// 审计通道容量，写入跟不上时丢弃新记录而不是阻塞请求
+const AUDIT_CHANNEL_CAPACITY: usize = 1024;
+
+/// 待写入的审计记录
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    /// 发起请求的主体，未认证时为空
+    pub principal: Option<String>,
+    pub method: String,
+    /// 路由模式（如 `/api/todos/:id`），不含原始路径和查询串
+    pub route: String,
+    /// 路径中的目标 id
+    pub target_id: Option<String>,
+    /// 响应状态码
+    pub status: u16,
+}
+
+/// 已写入的审计记录
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AuditRecord {
+    pub id: i64,
+    pub created_at: String,
+    pub principal: Option<String>,
+    pub method: String,
+    pub route: String,
+    pub target_id: Option<String>,
+    pub status: i64,
+}
+
+/// 审计日志记录器
+///
+/// 可廉价克隆，作为中间件状态共享
+#[derive(Debug, Clone)]
+pub struct AuditLogger {
+    sender: mpsc::Sender<AuditEntry>,
+}
+
+impl AuditLogger {
+    /// 创建记录器和对应的接收端，接收端交给 [`run_audit_writer`]
+    pub fn new() -> (Self, mpsc::Receiver<AuditEntry>) {
+        let (sender, receiver) = mpsc::channel(AUDIT_CHANNEL_CAPACITY);
+        (Self { sender }, receiver)
+    }
+
+    /// 提交一条审计记录（不阻塞）
+    pub fn record(&self, entry: AuditEntry) {
+        if let Err(e) = self.sender.try_send(entry) {
+            metrics::increment_counter!("audit_log_dropped_total");
+            tracing::warn!("⚠️  审计记录被丢弃: {}", e);
+        }
+    }
+}
+
+/// 审计写入任务
+///
+/// 持续写入收到的记录；收到关闭信号后写完通道中剩余的记录再退出
+pub async fn run_audit_writer(
+    pool: SqlitePool,
+    mut receiver: mpsc::Receiver<AuditEntry>,
+    shutdown: CancellationToken,
+) {
+    loop {
+        tokio::select! {
+            entry = receiver.recv() => match entry {
+                Some(entry) => write_entry(&pool, &entry).await,
+                None => break,
+            },
+            _ = shutdown.cancelled() => {
+                receiver.close();
+                while let Some(entry) = receiver.recv().await {
+                    write_entry(&pool, &entry).await;
+                }
+                break;
+            }
+        }
+    }
+}
+
+/// 写入单条审计记录，失败只记录日志
+async fn write_entry(pool: &SqlitePool, entry: &AuditEntry) {
+    let result = sqlx::query(
+        "INSERT INTO audit_log (principal, method, route, target_id, status) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(&entry.principal)
+    .bind(&entry.method)
+    .bind(&entry.route)
+    .bind(&entry.target_id)
+    .bind(entry.status as i64)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        tracing::error!("❌ 写入审计记录失败: {}", e);
+    }
+}
+
+/// 分页查询审计记录，按时间倒序
+pub async fn list_entries(
+    pool: &SqlitePool,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<AuditRecord>, i64), sqlx::Error> {
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM audit_log")
+        .fetch_one(pool)
+        .await?;
+
+    let records = sqlx::query_as::<_, AuditRecord>(
+        "SELECT id, CAST(created_at AS TEXT) AS created_at, principal, method, route, target_id, status \
+         FROM audit_log ORDER BY id DESC LIMIT ? OFFSET ?",
+    )
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((records, total))
+}
@@ -2,19 +2,28 @@
 //!
 //! 提供在应用启动时预加载热点数据到缓存的功能，减少冷启动时间和首次请求延迟
 
+use metrics::increment_counter;
 use sqlx::{Error as SqlxError, SqlitePool};
-use tracing::{info, warn};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
 
 // 定义模块内通用的Result类型
 type Result<T, E = SqlxError> = std::result::Result<T, E>;
 
 use crate::helpers::cache::set_to_cache;
-use crate::routes::pages::{CACHE_KEY_TODOS, CACHE_KEY_USERS, INITIAL_USERS_CACHE_KEY};
+use crate::helpers::config::CONFIG;
+use crate::helpers::monitoring::AppState;
+use crate::routes::pages::{
+    get_initial_users, initial_users_cache_key, CACHE_KEY_TODOS, CACHE_KEY_USERS,
+};
 use crate::routes::todos::{get_stats, get_todos};
 use crate::routes::users::get_all_users;
 
 /// 预加载所有热点数据到缓存
 /// 这个函数应该在应用启动时异步调用
+///
+/// 各项缓存互不影响，全部执行完毕后若有失败项，返回第一个错误
 pub async fn warmup_all_caches(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     info!("开始缓存预热...");
 
@@ -51,7 +60,7 @@ pub async fn warmup_all_caches(pool: &SqlitePool) -> Result<(), sqlx::Error> {
         "缓存预热完成: 成功 {}, 失败 {}",
         success_count, failure_count
     );
-    Ok(())
+    results.0.and(results.1).and(results.2)
 }
 
 /// 预热待办事项缓存
@@ -69,7 +78,8 @@ async fn warmup_todos_cache(pool: &SqlitePool) -> Result<(), sqlx::Error> {
         CACHE_KEY_TODOS,
         (todos, stats.completed_count, stats.pending_count),
         Some(std::time::Duration::from_secs(900)),
-    );
+    )
+    .await;
 
     info!("待办事项缓存预热成功");
     Ok(())
@@ -87,52 +97,134 @@ async fn warmup_users_cache(pool: &SqlitePool) -> Result<(), sqlx::Error> {
         CACHE_KEY_USERS,
         users,
         Some(std::time::Duration::from_secs(600)),
-    );
+    )
+    .await;
 
     info!("用户列表缓存预热成功");
     Ok(())
 }
 
-/// 预热初始用户列表缓存（前12个用户）
+/// 预热初始用户列表缓存（前 `ui.initial_users` 个用户）
 async fn warmup_initial_users_cache(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     info!("预热初始用户列表缓存...");
 
-    // 导入User类型
-    use crate::routes::users::User;
-
-    // 获取前12个用户
-    let users = sqlx::query_as::<_, User>("SELECT id, name, email FROM users ORDER BY id LIMIT 12")
-        .fetch_all(pool)
-        .await?;
+    let count = CONFIG.ui.initial_users;
+    let users = get_initial_users(pool, count).await?;
 
     // 设置缓存，过期时间5分钟
     set_to_cache(
-        INITIAL_USERS_CACHE_KEY,
+        &initial_users_cache_key(count),
         users,
         Some(std::time::Duration::from_secs(300)),
-    );
+    )
+    .await;
 
     info!("初始用户列表缓存预热成功");
     Ok(())
 }
 
-/// 定期刷新缓存的后台任务
-/// 可以在应用中启动一个独立的任务来定期执行
-pub async fn start_cache_refresh_task(pool: SqlitePool) {
-    let refresh_interval = std::time::Duration::from_secs(300); // 5分钟刷新一次
+/// 缓存自动刷新间隔
+const REFRESH_INTERVAL: Duration = Duration::from_secs(300);
 
-    info!("启动缓存自动刷新任务，间隔: {:?}", refresh_interval);
+/// 刷新任务异常退出后的首次重启等待时间，之后每次翻倍
+const RESTART_MIN_BACKOFF: Duration = Duration::from_secs(5);
 
+/// 重启等待时间上限
+const RESTART_MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// 定期刷新缓存的后台任务，取消令牌触发后退出
+///
+/// 刷新循环在独立任务中运行：循环 panic 时记录错误、把 [`AppState`] 中的刷新状态标记为异常
+/// （`/health/ready` 报告 `degraded`），再按指数退避重启；重启后成功完成一轮刷新即恢复正常，
+/// 单轮刷新失败同样标记为异常。
+/// 异常前刚恢复过正常的，退避从头计算
+pub async fn start_cache_refresh_task(state: AppState, shutdown: CancellationToken) {
+    info!("启动缓存自动刷新任务，间隔: {:?}", REFRESH_INTERVAL);
+
+    let mut backoff = RESTART_MIN_BACKOFF;
     loop {
-        tokio::time::sleep(refresh_interval).await;
+        let task = tokio::spawn(run_cache_refresh_loop(state.clone(), shutdown.clone()));
+        let error = match task.await {
+            Ok(()) => return,
+            Err(e) => e,
+        };
+
+        if state.is_cache_refresher_healthy() {
+            backoff = RESTART_MIN_BACKOFF;
+        }
+        state.set_cache_refresher_healthy(false);
+        increment_counter!("cache_refresh_task_restarts_total");
+        error!(
+            "❌ 缓存自动刷新任务异常退出: {}，{:?} 后重启",
+            error, backoff
+        );
+
+        tokio::select! {
+            () = shutdown.cancelled() => return,
+            () = tokio::time::sleep(backoff) => {}
+        }
+        backoff = (backoff * 2).min(RESTART_MAX_BACKOFF);
+    }
+}
 
-        info!("开始自动刷新缓存...");
+/// 刷新循环，每轮完成后把刷新状态标记为正常
+async fn run_cache_refresh_loop(state: AppState, shutdown: CancellationToken) {
+    loop {
+        tokio::select! {
+            () = shutdown.cancelled() => {
+                info!("缓存自动刷新任务收到关闭信号，退出");
+                return;
+            }
+            () = tokio::time::sleep(REFRESH_INTERVAL) => {}
+        }
 
-        // 执行缓存预热
-        let result = warmup_all_caches(&pool).await;
+        refresh_once(&state).await;
+    }
+}
 
-        if result.is_err() {
-            warn!("缓存自动刷新失败: {:?}", result);
+/// 执行一轮缓存刷新
+/// 刷新失败时标记为异常，下一轮成功后恢复
+async fn refresh_once(state: &AppState) {
+    info!("开始自动刷新缓存...");
+
+    match warmup_all_caches(&state.db.read).await {
+        Ok(()) => state.set_cache_refresher_healthy(true),
+        Err(e) => {
+            warn!("缓存自动刷新失败: {:?}", e);
+            state.set_cache_refresher_healthy(false);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Db;
+    use crate::helpers::config::AppConfig;
+    use metrics_exporter_prometheus::PrometheusBuilder;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn failed_refresh_marks_refresher_unhealthy() {
+        // 未执行迁移的库：查询全部失败，也不会写入共享缓存
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let state = AppState::new(
+            Db {
+                write: pool.clone(),
+                read: pool,
+            },
+            Arc::new(AppConfig::default()),
+            PrometheusBuilder::new().build_recorder().handle(),
+        );
+        assert!(state.is_cache_refresher_healthy());
+
+        refresh_once(&state).await;
+
+        assert!(!state.is_cache_refresher_healthy());
+    }
+}
@@ -0,0 +1,64 @@
+//! 数据库连接保活服务
+//!
+//! 定期对空闲连接执行 `SELECT 1`，让 `min_connections` 保持的连接不会被服务端的空闲超时断开。
+//! SQLite 是进程内数据库，连接不会被动断开，因此默认关闭；切换到网络数据库时再按需开启
+
+use sqlx::SqlitePool;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::helpers::monitoring::track_db_query;
+
+/// 启动连接保活任务，直到收到关闭信号
+pub async fn start_db_keepalive_task(
+    pool: SqlitePool,
+    interval: Duration,
+    min_connections: u32,
+    shutdown: CancellationToken,
+) {
+    info!("启动数据库连接保活任务，间隔: {:?}", interval);
+
+    loop {
+        tokio::select! {
+            () = shutdown.cancelled() => {
+                info!("数据库连接保活任务收到关闭信号，退出");
+                return;
+            }
+            () = tokio::time::sleep(interval) => {}
+        }
+
+        ping_idle_connections(&pool, min_connections).await;
+    }
+}
+
+/// ping 最多 `min_connections` 个空闲连接
+///
+/// 只取当前空闲的连接（`try_acquire` 不等待），不与请求争抢连接；
+/// 正在使用的连接本身就是活跃的，无需保活
+async fn ping_idle_connections(pool: &SqlitePool, min_connections: u32) {
+    // 同时持有取出的连接，保证每次 ping 的都是不同的连接
+    let mut connections = Vec::new();
+    while connections.len() < min_connections as usize {
+        match pool.try_acquire() {
+            Some(conn) => connections.push(conn),
+            None => break,
+        }
+    }
+
+    let mut failed = 0;
+    for conn in connections.iter_mut() {
+        let result =
+            track_db_query("db.keepalive", sqlx::query("SELECT 1").execute(&mut **conn)).await;
+        if let Err(e) = result {
+            failed += 1;
+            warn!("⚠️  连接保活 ping 失败: {}", e);
+        }
+    }
+
+    debug!(
+        "连接保活完成: ping {} 个空闲连接，失败 {} 个",
+        connections.len(),
+        failed
+    );
+}
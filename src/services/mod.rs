@@ -2,5 +2,20 @@
 //!
 //! 包含应用的核心业务逻辑和服务实现
 
+// 导出审计日志服务
+pub mod audit;
+
 // 导出缓存预热服务
 pub mod cache_warmup;
+
+// 导出数据库连接保活服务
+pub mod db_keepalive;
+
+// 导出连接池指标服务
+pub mod pool_metrics;
+
+// 导出关闭协调服务
+pub mod shutdown;
+
+// 导出模板自检服务
+pub mod template_check;
@@ -0,0 +1,88 @@
+//! 连接池指标服务
+//!
+//! 定期采样各连接池的连接数，写入带 `pool` 标签的 `db_connections_active`、`db_connections_idle`，
+//! 配置了只读副本时主库（`write`）和副本（`read`）分别上报
+
+use metrics::gauge;
+use sqlx::SqlitePool;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+/// 采样间隔
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// 启动连接池指标采样任务，直到收到关闭信号
+///
+/// `pools` 为（标签, 连接池）列表；同一个连接池只应出现一次，否则连接数会被重复计算
+pub async fn start_pool_metrics_task(
+    pools: Vec<(&'static str, SqlitePool)>,
+    shutdown: CancellationToken,
+) {
+    let labels: Vec<&str> = pools.iter().map(|(label, _)| *label).collect();
+    info!(
+        "启动连接池指标采样任务，间隔: {:?}，连接池: {:?}",
+        SAMPLE_INTERVAL, labels
+    );
+
+    loop {
+        for (label, pool) in &pools {
+            record_pool_metrics(label, pool);
+        }
+
+        tokio::select! {
+            () = shutdown.cancelled() => {
+                info!("连接池指标采样任务收到关闭信号，退出");
+                return;
+            }
+            () = tokio::time::sleep(SAMPLE_INTERVAL) => {}
+        }
+    }
+}
+
+/// 记录一个连接池的当前连接数
+fn record_pool_metrics(label: &'static str, pool: &SqlitePool) {
+    let idle = pool.num_idle() as u32;
+    let active = pool.size().saturating_sub(idle);
+    gauge!("db_connections_active", f64::from(active), "pool" => label);
+    gauge!("db_connections_idle", f64::from(idle), "pool" => label);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::monitoring::test_metrics_handle;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn memory_pool() -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn gauges_are_labelled_per_pool() {
+        let handle = test_metrics_handle();
+        let write = memory_pool().await;
+        let read = memory_pool().await;
+        // 副本的连接被占用，主库连接空闲
+        let _conn = read.acquire().await.unwrap();
+
+        // 已取消的令牌：采样一轮后立即退出
+        let shutdown = CancellationToken::new();
+        shutdown.cancel();
+        start_pool_metrics_task(vec![("write", write), ("read", read.clone())], shutdown).await;
+
+        let rendered = handle.render();
+        for line in [
+            "db_connections_active{pool=\"write\"} 0",
+            "db_connections_idle{pool=\"write\"} 1",
+            "db_connections_active{pool=\"read\"} 1",
+            "db_connections_idle{pool=\"read\"} 0",
+        ] {
+            assert!(rendered.lines().any(|l| l == line), "{}", rendered);
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! 关闭协调服务
+//!
+//! 统一登记后台任务，在收到关闭信号后按注册顺序通知并等待它们结束，
+//! 超时后强制中止剩余任务，避免依赖盲目的 sleep 关闭
+
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+/// 已登记的后台任务
+struct RegisteredTask {
+    name: &'static str,
+    handle: JoinHandle<()>,
+}
+
+/// 后台任务在关闭时的结束方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskExit {
+    /// 收到关闭信号后正常退出
+    Stopped,
+    /// 任务 panic 或已被中止
+    Failed,
+    /// 超时未退出，已强制中止
+    Aborted,
+}
+
+/// 关闭协调器
+///
+/// 持有全局取消令牌和所有后台任务句柄，`main.rs` 在关闭时调用 [`ShutdownCoordinator::shutdown`]
+pub struct ShutdownCoordinator {
+    /// 根取消令牌，各任务持有其子令牌
+    token: CancellationToken,
+    /// 按注册顺序保存的任务
+    tasks: Mutex<Vec<RegisteredTask>>,
+}
+
+impl ShutdownCoordinator {
+    /// 创建新的关闭协调器
+    pub fn new() -> Self {
+        Self {
+            token: CancellationToken::new(),
+            tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 获取一个子取消令牌，供后台任务监听关闭信号
+    pub fn token(&self) -> CancellationToken {
+        self.token.child_token()
+    }
+
+    /// 启动并登记一个后台任务
+    ///
+    /// 任务闭包会收到一个取消令牌，应在令牌取消后尽快退出
+    pub fn spawn<F, Fut>(&self, name: &'static str, task: F)
+    where
+        F: FnOnce(CancellationToken) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let handle = tokio::spawn(task(self.token()));
+        self.register(name, handle);
+    }
+
+    /// 登记一个已经启动的后台任务
+    pub fn register(&self, name: &'static str, handle: JoinHandle<()>) {
+        tracing::debug!("登记后台任务: {}", name);
+        self.tasks
+            .lock()
+            .unwrap()
+            .push(RegisteredTask { name, handle });
+    }
+
+    /// 通知所有任务开始关闭（不等待）
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// 通知所有任务关闭，并按注册顺序等待其结束
+    ///
+    /// 所有任务共享同一个超时期限，超时后仍未结束的任务会被强制中止。
+    /// 按等待顺序返回每个任务的名称和结束方式
+    pub async fn shutdown(&self, timeout: Duration) -> Vec<(&'static str, TaskExit)> {
+        self.cancel();
+
+        let tasks: Vec<RegisteredTask> = std::mem::take(&mut *self.tasks.lock().unwrap());
+        let deadline = Instant::now() + timeout;
+        let mut exits = Vec::with_capacity(tasks.len());
+
+        for task in tasks {
+            let abort_handle = task.handle.abort_handle();
+            let exit = match tokio::time::timeout_at(deadline, task.handle).await {
+                Ok(Ok(())) => {
+                    tracing::info!("✅ 后台任务已停止: {}", task.name);
+                    TaskExit::Stopped
+                }
+                Ok(Err(e)) => {
+                    tracing::warn!("⚠️  后台任务异常退出: {}: {}", task.name, e);
+                    TaskExit::Failed
+                }
+                Err(_) => {
+                    abort_handle.abort();
+                    tracing::warn!(
+                        "⚠️  后台任务未在 {} 秒内停止，已强制中止: {}",
+                        timeout.as_secs(),
+                        task.name
+                    );
+                    TaskExit::Aborted
+                }
+            };
+            exits.push((task.name, exit));
+        }
+        exits
+    }
+}
+
+impl Default for ShutdownCoordinator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn cancels_every_task_and_awaits_them_in_registration_order() {
+        let coordinator = ShutdownCoordinator::new();
+        let stopped = Arc::new(Mutex::new(Vec::new()));
+
+        // 先登记的任务退出得更晚，等待顺序仍按登记顺序
+        for (name, delay_ms) in [("first", 60), ("second", 30), ("third", 0)] {
+            let stopped = stopped.clone();
+            coordinator.spawn(name, move |token| async move {
+                token.cancelled().await;
+                tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                stopped.lock().unwrap().push(name);
+            });
+        }
+
+        let exits = coordinator.shutdown(Duration::from_secs(5)).await;
+
+        assert_eq!(
+            exits,
+            [
+                ("first", TaskExit::Stopped),
+                ("second", TaskExit::Stopped),
+                ("third", TaskExit::Stopped)
+            ]
+        );
+        // 三个任务都收到了取消信号并在关闭返回前退出
+        assert_eq!(*stopped.lock().unwrap(), ["third", "second", "first"]);
+    }
+
+    #[tokio::test]
+    async fn aborts_a_task_that_ignores_cancellation() {
+        let coordinator = ShutdownCoordinator::new();
+        let alive = Arc::new(());
+
+        let held = alive.clone();
+        coordinator.spawn("stubborn", move |_token| async move {
+            let _held = held;
+            std::future::pending::<()>().await;
+        });
+        coordinator.spawn("polite", |token| async move { token.cancelled().await });
+
+        let started = std::time::Instant::now();
+        let exits = coordinator.shutdown(Duration::from_millis(50)).await;
+
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert_eq!(
+            exits,
+            [
+                ("stubborn", TaskExit::Aborted),
+                ("polite", TaskExit::Stopped)
+            ]
+        );
+        // 中止后任务的 future 被丢弃，持有的资源随之释放
+        for _ in 0..100 {
+            if Arc::strong_count(&alive) == 1 {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(Arc::strong_count(&alive), 1);
+    }
+}
@@ -0,0 +1,204 @@
+//! 模板自检服务
+//!
+//! askama 模板在编译期检查语法和字段，但渲染期的错误（过滤器失败、模板中调用的函数 panic 等）
+//! 只有在请求到来时才会暴露。启动时用示例数据把每个模板渲染一遍，让这类问题在启动阶段被发现
+
+use askama::Template;
+use std::panic::{self, AssertUnwindSafe};
+use tracing::{debug, info};
+
+use crate::domain::Email;
+use crate::helpers::pagination::{calculate_display_range, create_pagination};
+use crate::helpers::timezone::DisplayTimezone;
+use crate::routes::modal::ModalExampleTemplate;
+use crate::routes::official::OfficialIndexTemplate;
+use crate::routes::pages::{
+    HomePageTemplate, IndexTemplate, TodosFullPageTemplate, TodosPageTemplate,
+    UsersFullPageTemplate, UsersPageTemplate,
+};
+use crate::routes::todos::{
+    CreateFormTemplate, Todo, TodoItemTemplate, TodoSearchResultsTemplate, TodoStats,
+    TodoStatsTemplate, TodosSkeletonTemplate,
+};
+use crate::routes::users::{
+    User, UserDetailTemplate, UserDetailsTemplate, UserSearchResultsTemplate, UsersSkeletonTemplate,
+};
+use crate::routes::{url, EmptyStateTemplate};
+
+/// 模板渲染失败
+pub struct TemplateCheckFailure {
+    /// 模板结构体名称
+    pub template: &'static str,
+    pub message: String,
+}
+
+/// 用示例数据渲染所有模板，返回失败的模板
+///
+/// 列表类模板分别以空列表和非空列表渲染，覆盖空状态和列表两个分支。
+/// 新增模板时请在这里补充一次渲染
+pub fn validate_templates() -> Vec<TemplateCheckFailure> {
+    let todos = sample_todos();
+    let users = sample_users();
+    let mut checks = TemplateChecks::default();
+
+    let todo_stats = TodoStats {
+        total_count: 2,
+        completed_count: 1,
+        pending_count: 1,
+    };
+    for (show_todo_stats, todo_stats) in [(false, None), (true, None), (true, Some(todo_stats))] {
+        checks.render(IndexTemplate {
+            show_todo_stats,
+            todo_stats,
+        });
+        checks.render(HomePageTemplate {
+            show_todo_stats,
+            todo_stats,
+        });
+    }
+    checks.render(OfficialIndexTemplate);
+    checks.render(ModalExampleTemplate);
+    checks.render(CreateFormTemplate);
+    checks.render(TodosSkeletonTemplate);
+    checks.render(UsersSkeletonTemplate);
+    checks.render(TodoStatsTemplate::from(todo_stats));
+    checks.render(EmptyStateTemplate::no_todos());
+
+    for todos in [Vec::new(), todos] {
+        if let Some(todo) = todos.first() {
+            checks.render(TodoItemTemplate { todo: todo.clone() });
+        }
+        checks.render(TodosFullPageTemplate {
+            todos: todos.clone(),
+            completed_count: 1,
+            pending_count: 1,
+        });
+        checks.render(TodosPageTemplate {
+            todos: todos.clone(),
+            completed_count: 1,
+            pending_count: 1,
+        });
+
+        let (start_item, end_item) = calculate_display_range(1, 12, todos.len());
+        checks.render(TodoSearchResultsTemplate {
+            pagination: create_pagination(1, 12, todos.len() as i64),
+            todos,
+            query: "示例".to_string(),
+            start_item,
+            end_item,
+            base_url: url("/block/todos/search"),
+            target: "#todo-list".to_string(),
+            empty_state: EmptyStateTemplate::new("search", "未找到匹配的任务", "示例"),
+        });
+    }
+
+    for users in [Vec::new(), users] {
+        if let Some(user) = users.first() {
+            checks.render(UserDetailTemplate {
+                user: user.clone(),
+                created_at: Some(DisplayTimezone::default().format("2024-05-01 00:30:00")),
+            });
+        }
+        checks.render(UserDetailsTemplate {
+            users: users
+                .iter()
+                .map(|user| (user.clone(), Some("2024-05-01 08:30 UTC".to_string())))
+                .collect(),
+            missing: vec![404],
+        });
+        checks.render(UsersFullPageTemplate {
+            users: users.clone(),
+        });
+        checks.render(UsersPageTemplate {
+            users: users.clone(),
+        });
+
+        let (start_item, end_item) = calculate_display_range(1, 12, users.len());
+        checks.render(UserSearchResultsTemplate {
+            pagination: create_pagination(1, 12, users.len() as i64),
+            users,
+            query: "示例".to_string(),
+            start_item,
+            end_item,
+            base_url: url("/block/users/search"),
+            target: "#search-results".to_string(),
+            empty_state: EmptyStateTemplate::new("search", "未找到匹配的用户", "示例"),
+            truncated_at: Some(1),
+        });
+    }
+
+    info!(
+        "模板自检完成: 渲染 {} 次，失败 {} 个",
+        checks.rendered,
+        checks.failures.len()
+    );
+    checks.failures
+}
+
+/// 已执行的渲染和失败记录
+#[derive(Default)]
+struct TemplateChecks {
+    rendered: usize,
+    failures: Vec<TemplateCheckFailure>,
+}
+
+impl TemplateChecks {
+    /// 渲染一个模板，记录返回的错误和渲染中的 panic
+    ///
+    /// 只有 debug 构建能捕获 panic；release 构建使用 `panic = "abort"`，
+    /// panic 会使进程在自检阶段直接退出，与 `strict_template_validation` 无关
+    fn render<T: Template>(&mut self, template: T) {
+        let name = std::any::type_name::<T>()
+            .rsplit("::")
+            .next()
+            .unwrap_or_default();
+        self.rendered += 1;
+
+        let message = match panic::catch_unwind(AssertUnwindSafe(|| template.render())) {
+            Ok(Ok(_)) => {
+                debug!("模板自检通过: {}", name);
+                return;
+            }
+            Ok(Err(e)) => e.to_string(),
+            Err(panic) => panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "渲染时 panic".to_string()),
+        };
+
+        self.failures.push(TemplateCheckFailure {
+            template: name,
+            message,
+        });
+    }
+}
+
+fn sample_todos() -> Vec<Todo> {
+    vec![
+        Todo {
+            id: 1,
+            title: "示例任务".to_string(),
+            completed: false,
+        },
+        Todo {
+            id: 2,
+            title: "<已完成的示例任务 & 转义检查>".to_string(),
+            completed: true,
+        },
+    ]
+}
+
+fn sample_users() -> Vec<User> {
+    ["alice@example.com", "bob@example.com"]
+        .iter()
+        .zip(1..)
+        .filter_map(|(email, id)| {
+            Some(User {
+                id,
+                name: format!("示例用户 {}", id),
+                email: Email::parse(email).ok()?,
+            })
+        })
+        .collect()
+}